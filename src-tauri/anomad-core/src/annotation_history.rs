@@ -0,0 +1,256 @@
+// История пометок: каждое извлечение сравнивается с предыдущим для того же документа,
+// разница записывается событиями (добавлена, изменена, удалена) с датой
+
+use anyhow::Context;
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, SecondsFormat};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::fingerprint::compute_fingerprint;
+use super::get_file_highlights::Highlight;
+use super::highlight_diff::diff_highlights;
+use super::pdf_date::parse_pdf_date;
+use crate::error::Result;
+
+pub const HISTORY_FILE: &str = "annotation_history.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryAction {
+    Added,
+    Edited,
+    Deleted,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEvent {
+    pub timestamp: String,
+    // Ключ документа: путь, под которым документ записан впервые; после перемещения не меняется
+    pub document: String,
+    // Путь файла на момент события
+    pub path: String,
+    pub action: HistoryAction,
+    pub highlight: Highlight,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DocumentState {
+    path: String,
+    // /ID трейлера - по нему узнается перемещенный файл
+    #[serde(default)]
+    trailer_id: Option<String>,
+    highlights: Vec<Highlight>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StoredHistory {
+    // Последнее извлечение каждого документа - от него считается следующая разница
+    documents: BTreeMap<String, DocumentState>,
+    events: Vec<HistoryEvent>,
+}
+
+pub struct AnnotationHistory {
+    file: PathBuf,
+    stored: StoredHistory,
+}
+
+fn trailer_id(path: &Path) -> Option<String> {
+    compute_fingerprint(path).ok().and_then(|f| f.trailer_id)
+}
+
+impl AnnotationHistory {
+    // Поврежденная история не должна мешать запуску приложения
+    pub fn load(file: PathBuf) -> Self {
+        let stored = match fs::read_to_string(&file) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                log::warn!("История пометок повреждена и будет начата заново: {}", e);
+                StoredHistory::default()
+            }),
+            Err(_) => StoredHistory::default(),
+        };
+        AnnotationHistory { file, stored }
+    }
+
+    // Ключ документа, к которому относится файл: записанный под тем же путем, иначе
+    // перемещенный - с тем же /ID, чьего файла больше нет. Копии делят /ID с исходником,
+    // но исходник остается на месте, поэтому у каждой копии своя история
+    pub fn document_key(&self, path: &Path) -> String {
+        self.find_document(path, trailer_id(path).as_deref())
+            .unwrap_or_else(|| self.new_document_key(path))
+    }
+
+    // Под тем же путем мог быть записан другой документ, который потом переместили
+    fn new_document_key(&self, path: &Path) -> String {
+        let key = format!("path:{}", path.to_string_lossy());
+        (1..)
+            .map(|n| match n {
+                1 => key.clone(),
+                n => format!("{}#{}", key, n),
+            })
+            .find(|key| !self.stored.documents.contains_key(key))
+            .unwrap_or(key)
+    }
+
+    fn find_document(&self, path: &Path, trailer_id: Option<&str>) -> Option<String> {
+        let path_str = path.to_string_lossy();
+        let documents = &self.stored.documents;
+        if let Some((key, _)) = documents.iter().find(|(_, state)| state.path == path_str) {
+            return Some(key.clone());
+        }
+
+        let trailer_id = trailer_id?;
+        let moved: Vec<&String> = documents
+            .iter()
+            .filter(|(_, state)| {
+                state.trailer_id.as_deref() == Some(trailer_id) && !Path::new(&state.path).exists()
+            })
+            .map(|(key, _)| key)
+            .collect();
+        // Несколько кандидатов - угадывать не беремся
+        match moved.as_slice() {
+            [key] => Some((*key).clone()),
+            _ => None,
+        }
+    }
+
+    fn save(&self) -> Result<()> {
+        if let Some(dir) = self.file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.file, serde_json::to_string(&self.stored)?).with_context(|| {
+            format!("Failed to save annotation history: {}", self.file.display())
+        })?;
+        Ok(())
+    }
+
+    // Возвращает число записанных событий; без изменений файл истории не перезаписывается
+    pub fn record(&mut self, path: &Path, highlights: &[Highlight]) -> Result<usize> {
+        self.record_all([(path, highlights)])
+    }
+
+    // Несколько файлов (например, результаты поиска) - файл истории сохраняется один раз
+    pub fn record_all<'a>(
+        &mut self,
+        extracted: impl IntoIterator<Item = (&'a Path, &'a [Highlight])>,
+    ) -> Result<usize> {
+        let mut changed = false;
+        let mut count = 0;
+        for (path, highlights) in extracted {
+            if let Some(events) = self.apply(path, highlights) {
+                changed = true;
+                count += events;
+            }
+        }
+        if changed {
+            self.save()?;
+        }
+        Ok(count)
+    }
+
+    // None - с прошлого извлечения ничего не изменилось
+    fn apply(&mut self, path: &Path, highlights: &[Highlight]) -> Option<usize> {
+        let trailer_id = trailer_id(path);
+        let document = self
+            .find_document(path, trailer_id.as_deref())
+            .unwrap_or_else(|| self.new_document_key(path));
+        let path_str = path.to_string_lossy().into_owned();
+        let now = Local::now().fixed_offset();
+
+        let previous = self
+            .stored
+            .documents
+            .get(&document)
+            .map(|state| state.highlights.as_slice())
+            .unwrap_or_default();
+        let diff = diff_highlights(previous, highlights);
+        let moved = self
+            .stored
+            .documents
+            .get(&document)
+            .is_some_and(|state| state.path != path_str);
+        if diff.is_empty() && !moved {
+            return None;
+        }
+
+        let event =
+            |action, highlight: &Highlight, timestamp: DateTime<FixedOffset>| HistoryEvent {
+                timestamp: timestamp.to_rfc3339_opts(SecondsFormat::Secs, false),
+                document: document.clone(),
+                path: path_str.clone(),
+                action,
+                highlight: highlight.clone(),
+            };
+
+        // Для добавленных и измененных пометок точнее их собственная дата (/M), если она есть;
+        // о времени удаления знаем только, что оно было не позже этого извлечения
+        let mut events: Vec<HistoryEvent> = Vec::new();
+        for highlight in &diff.added {
+            events.push(event(
+                HistoryAction::Added,
+                highlight,
+                annotation_time(highlight, now),
+            ));
+        }
+        for highlight in &diff.changed {
+            events.push(event(
+                HistoryAction::Edited,
+                highlight,
+                annotation_time(highlight, now),
+            ));
+        }
+        for highlight in &diff.removed {
+            events.push(event(HistoryAction::Deleted, highlight, now));
+        }
+
+        let count = events.len();
+        self.stored.events.extend(events);
+        self.stored.documents.insert(
+            document.clone(),
+            DocumentState {
+                path: path_str.clone(),
+                trailer_id,
+                highlights: highlights.to_vec(),
+            },
+        );
+        Some(count)
+    }
+
+    // document - ключ документа (document_key), None - вся библиотека; даты включительно
+    pub fn timeline(
+        &self,
+        document: Option<&str>,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> Vec<HistoryEvent> {
+        let mut events: Vec<(DateTime<FixedOffset>, &HistoryEvent)> = self
+            .stored
+            .events
+            .iter()
+            .filter(|e| document.map_or(true, |d| e.document == d))
+            .filter_map(|e| Some((DateTime::parse_from_rfc3339(&e.timestamp).ok()?, e)))
+            .filter(|(time, _)| {
+                let day = time.date_naive();
+                from.map_or(true, |from| day >= from) && to.map_or(true, |to| day <= to)
+            })
+            .collect();
+        events.sort_by_key(|(time, _)| *time);
+        events.into_iter().map(|(_, e)| e.clone()).collect()
+    }
+}
+
+// Дата пометки, но не из будущего (часы программы, создавшей пометку, могли спешить)
+fn annotation_time(highlight: &Highlight, now: DateTime<FixedOffset>) -> DateTime<FixedOffset> {
+    highlight
+        .date
+        .as_deref()
+        .and_then(parse_pdf_date)
+        .filter(|date| *date <= now)
+        .unwrap_or(now)
+}
+
+// Дата в виде "2024-05-31"
+pub fn parse_day(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").ok()
+}
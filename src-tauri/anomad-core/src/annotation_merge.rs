@@ -0,0 +1,128 @@
+// Объединение аннотаций из нескольких копий одного PDF.
+// Одинаковые аннотации (тип, страница, рамка, текст) пропускаются; одна и та же
+// аннотация с разным текстом - конфликт: она не добавляется, а попадает в отчет
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::annotations::Annotation;
+
+// Метка для аннотаций, которые уже есть в целевом файле
+pub const TARGET_SOURCE: &str = "target";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConflictVariant {
+    pub source: String,
+    pub contents: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnnotationConflict {
+    pub page: u32,
+    pub subtype: String,
+    pub rect: [f32; 4],
+    // Первый вариант - тот, что остается в целевом файле
+    pub variants: Vec<ConflictVariant>,
+}
+
+#[derive(Debug, Default)]
+pub struct MergePlan {
+    pub to_add: Vec<Annotation>,
+    pub duplicates: usize,
+    pub conflicts: Vec<AnnotationConflict>,
+}
+
+// Разные программы пишут координаты с разной точностью - сравниваем с шагом 0.1pt
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct PositionKey {
+    page: u32,
+    subtype: String,
+    rect: [i64; 4],
+}
+
+impl PositionKey {
+    fn of(annotation: &Annotation) -> Self {
+        PositionKey {
+            page: annotation.page,
+            subtype: annotation.subtype.clone(),
+            rect: annotation.rect.map(|v| (v * 10.0).round() as i64),
+        }
+    }
+}
+
+fn normalized_contents(annotation: &Annotation) -> Option<String> {
+    annotation
+        .contents
+        .as_ref()
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty())
+}
+
+struct Slot {
+    first: Annotation,
+    variants: Vec<ConflictVariant>,
+}
+
+// existing - аннотации целевого файла, sources - (путь копии, ее аннотации)
+pub fn plan_merge(existing: &[Annotation], sources: &[(String, Vec<Annotation>)]) -> MergePlan {
+    let mut slots: BTreeMap<PositionKey, Slot> = BTreeMap::new();
+    let mut plan = MergePlan::default();
+
+    let incoming = existing
+        .iter()
+        .map(|a| (TARGET_SOURCE, a))
+        .chain(
+            sources
+                .iter()
+                .flat_map(|(source, list)| list.iter().map(move |a| (source.as_str(), a))),
+        );
+
+    for (source, annotation) in incoming {
+        let contents = normalized_contents(annotation);
+
+        match slots.get_mut(&PositionKey::of(annotation)) {
+            Some(slot) => {
+                if slot.variants.iter().any(|v| v.contents == contents) {
+                    if source != TARGET_SOURCE {
+                        plan.duplicates += 1;
+                    }
+                } else {
+                    slot.variants.push(ConflictVariant {
+                        source: source.to_string(),
+                        contents,
+                    });
+                }
+            }
+            None => {
+                if source != TARGET_SOURCE {
+                    plan.to_add.push(annotation.clone());
+                }
+                slots.insert(
+                    PositionKey::of(annotation),
+                    Slot {
+                        first: annotation.clone(),
+                        variants: vec![ConflictVariant {
+                            source: source.to_string(),
+                            contents,
+                        }],
+                    },
+                );
+            }
+        }
+    }
+
+    plan.conflicts = slots
+        .into_values()
+        .filter(|slot| slot.variants.len() > 1)
+        // Расхождения внутри самого целевого файла объединение не касаются
+        .filter(|slot| slot.variants.iter().any(|v| v.source != TARGET_SOURCE))
+        .map(|slot| AnnotationConflict {
+            page: slot.first.page,
+            subtype: slot.first.subtype,
+            rect: slot.first.rect,
+            variants: slot.variants,
+        })
+        .collect();
+
+    plan
+}
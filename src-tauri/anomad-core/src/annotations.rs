@@ -0,0 +1,188 @@
+// Общая модель аннотации PDF: чтение из lopdf-документа и запись обратно.
+// Используется для обмена аннотациями (XFDF) и операций над ними.
+
+use anyhow::Context;
+use lopdf::{Dictionary, Document, Object, ObjectId, StringFormat};
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use super::get_file_highlights::{decode_pdf_string, extract_color, page_annotations};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Annotation {
+    pub page: u32, // номер страницы, как в Document::get_pages (с 1)
+    pub subtype: String,
+    pub rect: [f32; 4],
+    pub quad_points: Option<Vec<f32>>,
+    pub color: Option<Vec<f32>>,
+    pub contents: Option<String>,
+    pub author: Option<String>,
+    pub name: Option<String>,
+    pub modified: Option<String>,
+    pub created: Option<String>,
+    pub opacity: Option<f32>,
+    pub flags: Option<i64>,
+}
+
+// Служебные аннотации, которые не являются пометками читателя
+const SKIPPED_SUBTYPES: [&str; 3] = ["Link", "Widget", "Popup"];
+
+pub fn read_annotations(doc: &Document) -> Result<Vec<Annotation>> {
+    let mut annotations = Vec::new();
+
+    for (page_num, page_id) in doc.get_pages() {
+        for dict in page_annotations(doc, page_id)? {
+            let subtype = match dict.get(b"Subtype").and_then(|o| o.as_name_str()) {
+                Ok(subtype) if !SKIPPED_SUBTYPES.contains(&subtype) => subtype.to_string(),
+                _ => continue,
+            };
+
+            let rect = match read_numbers(&dict, b"Rect") {
+                Some(values) if values.len() == 4 => [values[0], values[1], values[2], values[3]],
+                _ => continue,
+            };
+
+            annotations.push(Annotation {
+                page: page_num,
+                subtype,
+                rect,
+                quad_points: read_numbers(&dict, b"QuadPoints"),
+                color: extract_color(&dict),
+                contents: read_string(&dict, b"Contents"),
+                author: read_string(&dict, b"T"),
+                name: read_string(&dict, b"NM"),
+                modified: read_string(&dict, b"M"),
+                created: read_string(&dict, b"CreationDate"),
+                opacity: dict.get(b"CA").and_then(|o| o.as_float()).ok(),
+                flags: dict.get(b"F").and_then(|o| o.as_i64()).ok(),
+            });
+        }
+    }
+
+    Ok(annotations)
+}
+
+// Добавляет аннотации на страницы документа. Возвращает количество добавленных
+pub fn add_annotations(doc: &mut Document, annotations: &[Annotation]) -> Result<usize> {
+    let pages = doc.get_pages();
+    let mut added = 0;
+
+    for annotation in annotations {
+        let page_id = match pages.get(&annotation.page) {
+            Some(id) => *id,
+            None => continue,
+        };
+
+        let dict = annotation_to_dict(annotation, page_id);
+        let annot_id = doc.add_object(dict);
+        append_to_annots(doc, page_id, annot_id)?;
+        added += 1;
+    }
+
+    Ok(added)
+}
+
+fn annotation_to_dict(annotation: &Annotation, page_id: ObjectId) -> Dictionary {
+    let mut dict = Dictionary::new();
+    dict.set("Type", Object::Name(b"Annot".to_vec()));
+    dict.set("Subtype", Object::Name(annotation.subtype.as_bytes().to_vec()));
+    dict.set("P", Object::Reference(page_id));
+    dict.set("Rect", numbers_to_array(&annotation.rect));
+    // По умолчанию аннотация печатается (флаг Print)
+    dict.set("F", Object::Integer(annotation.flags.unwrap_or(4)));
+
+    if let Some(quad_points) = &annotation.quad_points {
+        dict.set("QuadPoints", numbers_to_array(quad_points));
+    }
+    if let Some(color) = &annotation.color {
+        dict.set("C", numbers_to_array(color));
+    }
+    if let Some(contents) = &annotation.contents {
+        dict.set("Contents", encode_pdf_string(contents));
+    }
+    if let Some(author) = &annotation.author {
+        dict.set("T", encode_pdf_string(author));
+    }
+    if let Some(name) = &annotation.name {
+        dict.set("NM", encode_pdf_string(name));
+    }
+    if let Some(modified) = &annotation.modified {
+        dict.set("M", encode_pdf_string(modified));
+    }
+    if let Some(created) = &annotation.created {
+        dict.set("CreationDate", encode_pdf_string(created));
+    }
+    if let Some(opacity) = annotation.opacity {
+        dict.set("CA", Object::Real(opacity));
+    }
+
+    dict
+}
+
+fn append_to_annots(doc: &mut Document, page_id: ObjectId, annot_id: ObjectId) -> Result<()> {
+    let annots_ref = match doc.get_dictionary(page_id)?.get(b"Annots") {
+        Ok(Object::Reference(id)) => Some(*id),
+        _ => None,
+    };
+
+    // /Annots может быть отдельным объектом-массивом, тогда дописываем в него
+    if let Some(array_id) = annots_ref {
+        doc.get_object_mut(array_id)
+            .and_then(|o| o.as_array_mut())
+            .context("Failed to resolve Annots reference")?
+            .push(Object::Reference(annot_id));
+        return Ok(());
+    }
+
+    let page_dict = doc
+        .get_dictionary_mut(page_id)
+        .context("Failed to get page dictionary")?;
+
+    match page_dict.get_mut(b"Annots") {
+        Ok(Object::Array(list)) => list.push(Object::Reference(annot_id)),
+        _ => page_dict.set("Annots", Object::Array(vec![Object::Reference(annot_id)])),
+    }
+
+    Ok(())
+}
+
+fn read_numbers(dict: &Dictionary, key: &[u8]) -> Option<Vec<f32>> {
+    let values: Vec<f32> = dict
+        .get(key)
+        .and_then(|o| o.as_array())
+        .ok()?
+        .iter()
+        .filter_map(|o| o.as_float().ok())
+        .collect();
+
+    if values.is_empty() {
+        None
+    } else {
+        Some(values)
+    }
+}
+
+fn read_string(dict: &Dictionary, key: &[u8]) -> Option<String> {
+    dict.get(key)
+        .ok()
+        .and_then(decode_pdf_string)
+        .filter(|s| !s.is_empty())
+}
+
+fn numbers_to_array(values: &[f32]) -> Object {
+    Object::Array(values.iter().map(|v| Object::Real(*v)).collect())
+}
+
+// ASCII пишем как есть, остальное - в UTF-16BE с BOM, как того требует спецификация
+pub fn encode_pdf_string(text: &str) -> Object {
+    if text.is_ascii() {
+        return Object::String(text.as_bytes().to_vec(), StringFormat::Literal);
+    }
+
+    let mut bytes = vec![0xFE, 0xFF];
+    for unit in text.encode_utf16() {
+        bytes.extend_from_slice(&unit.to_be_bytes());
+    }
+
+    Object::String(bytes, StringFormat::Hexadecimal)
+}
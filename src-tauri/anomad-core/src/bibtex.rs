@@ -0,0 +1,245 @@
+// Формирование BibTeX-записей из метаданных PDF и найденных идентификаторов
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::error::Result;
+use super::identifiers::{read_identifiers, DocumentIdentifiers};
+
+#[derive(Debug, Clone, Default)]
+pub struct BibSource {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub year: Option<String>,
+    pub file_path: String,
+    pub identifiers: DocumentIdentifiers,
+}
+
+// Все, что удалось узнать о файле без сети. Без заглавия в метаданных берем имя файла
+pub fn read_bib_source(path: &Path) -> Result<BibSource> {
+    let scanned = read_identifiers(path)?;
+
+    let title = scanned.metadata.title.or_else(|| {
+        path.file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+    });
+
+    Ok(BibSource {
+        title,
+        author: scanned.metadata.author,
+        year: scanned.year,
+        file_path: path.to_string_lossy().into_owned(),
+        identifiers: scanned.identifiers,
+    })
+}
+
+// Служебные слова, которые не годятся для ключа цитирования (русские - в транслитерации)
+const STOP_WORDS: [&str; 17] = [
+    "a", "an", "the", "on", "of", "in", "and", "for", "i", "v", "o", "ob", "na", "s", "k", "po",
+    "ot",
+];
+
+pub fn entry_type(identifiers: &DocumentIdentifiers) -> &'static str {
+    if !identifiers.isbns.is_empty() {
+        "book"
+    } else if !identifiers.dois.is_empty() {
+        "article"
+    } else {
+        "misc"
+    }
+}
+
+// Ключ вида "smith2020deep"; used_keys нужен для уникальности в пределах одного .bib
+pub fn citation_key(source: &BibSource, used_keys: &mut HashSet<String>) -> String {
+    let author = split_authors(source.author.as_deref().unwrap_or_default())
+        .first()
+        .map(|a| last_name(a))
+        .unwrap_or_default();
+
+    let word = source
+        .title
+        .as_deref()
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(ascii_key)
+        .find(|w| !w.is_empty() && !STOP_WORDS.contains(&w.as_str()))
+        .unwrap_or_default();
+
+    let mut base = format!(
+        "{}{}{}",
+        ascii_key(&author),
+        source.year.as_deref().unwrap_or_default(),
+        word
+    );
+    if base.is_empty() {
+        base = "untitled".to_string();
+    }
+
+    let mut key = base.clone();
+    let mut index = 0;
+    while used_keys.contains(&key) {
+        key = format!("{}{}", base, key_suffix(index));
+        index += 1;
+    }
+    used_keys.insert(key.clone());
+    key
+}
+
+// Суффиксы a, b, ..., z, aa, ab, ... - как буквенные номера столбцов в таблицах
+fn key_suffix(mut index: usize) -> String {
+    let mut letters = Vec::new();
+    loop {
+        letters.push((b'a' + (index % 26) as u8) as char);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    letters.into_iter().rev().collect()
+}
+
+pub fn format_entry(key: &str, source: &BibSource) -> String {
+    let ids = &source.identifiers;
+    let mut fields: Vec<(&str, String)> = Vec::new();
+
+    if let Some(title) = &source.title {
+        // Двойные скобки сохраняют регистр заглавия
+        fields.push(("title", format!("{{{}}}", escape_latex(title))));
+    }
+    if let Some(author) = &source.author {
+        let authors = split_authors(author)
+            .iter()
+            .map(|a| escape_latex(a))
+            .collect::<Vec<_>>()
+            .join(" and ");
+        fields.push(("author", authors));
+    }
+    if let Some(year) = &source.year {
+        fields.push(("year", year.clone()));
+    }
+    if let Some(doi) = ids.dois.first() {
+        fields.push(("doi", doi.clone()));
+    }
+    if let Some(isbn) = ids.isbns.first() {
+        fields.push(("isbn", isbn.clone()));
+    }
+    if let Some(arxiv_id) = ids.arxiv_ids.first() {
+        fields.push(("eprint", arxiv_id.clone()));
+        fields.push(("archiveprefix", "arXiv".to_string()));
+    }
+    // BibTeX-менеджеры понимают прямые слэши в путях и на Windows
+    fields.push(("file", source.file_path.replace('\\', "/")));
+
+    let body = fields
+        .iter()
+        .map(|(name, value)| format!("  {} = {{{}}}", name, value))
+        .collect::<Vec<_>>()
+        .join(",\n");
+
+    format!("@{}{{{},\n{}\n}}\n", entry_type(ids), key, body)
+}
+
+// Авторы в метаданных разделяются как попало: "A; B", "A and B", "A & B"
+pub fn split_authors(author: &str) -> Vec<String> {
+    author
+        .split(';')
+        .flat_map(|part| part.split(" and "))
+        .flat_map(|part| part.split(" & "))
+        .map(|a| a.trim().to_string())
+        .filter(|a| !a.is_empty())
+        .collect()
+}
+
+// "Smith, John" -> Smith, "John Smith" -> Smith
+pub fn last_name(author: &str) -> String {
+    match author.split_once(',') {
+        Some((last, _)) => last.trim().to_string(),
+        None => author
+            .split_whitespace()
+            .last()
+            .unwrap_or_default()
+            .to_string(),
+    }
+}
+
+// Кириллица и латиница с диакритикой транслитерируются, остальное отбрасывается:
+// "Толстой" -> "tolstoy", "Gödel" -> "godel"
+fn ascii_key(value: &str) -> String {
+    value
+        .chars()
+        .flat_map(char::to_lowercase)
+        .map(|c| match transliterate(c) {
+            Some(latin) => latin.to_string(),
+            None if c.is_ascii_alphanumeric() => c.to_string(),
+            None => String::new(),
+        })
+        .collect()
+}
+
+fn transliterate(c: char) -> Option<&'static str> {
+    let latin = match c {
+        'а' => "a",
+        'б' => "b",
+        'в' => "v",
+        'г' | 'ґ' => "g",
+        'д' => "d",
+        'е' | 'ё' | 'э' => "e",
+        'ж' => "zh",
+        'з' => "z",
+        'и' | 'і' => "i",
+        'й' | 'ы' => "y",
+        'к' => "k",
+        'л' => "l",
+        'м' => "m",
+        'н' => "n",
+        'о' => "o",
+        'п' => "p",
+        'р' => "r",
+        'с' => "s",
+        'т' => "t",
+        'у' | 'ў' => "u",
+        'ф' => "f",
+        'х' => "kh",
+        'ц' => "ts",
+        'ч' => "ch",
+        'ш' => "sh",
+        'щ' => "shch",
+        'ъ' | 'ь' => "",
+        'ю' => "yu",
+        'я' => "ya",
+        'ї' => "yi",
+        'є' => "ye",
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ą' => "a",
+        'æ' => "ae",
+        'ç' | 'č' | 'ć' => "c",
+        'è' | 'é' | 'ê' | 'ë' | 'ę' | 'ě' => "e",
+        'ì' | 'í' | 'î' | 'ï' => "i",
+        'ł' => "l",
+        'ñ' | 'ń' | 'ň' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' => "o",
+        'œ' => "oe",
+        'ř' => "r",
+        'ß' => "ss",
+        'š' | 'ś' => "s",
+        'ù' | 'ú' | 'û' | 'ü' | 'ů' => "u",
+        'ý' | 'ÿ' => "y",
+        'ž' | 'ź' | 'ż' => "z",
+        _ => return None,
+    };
+    Some(latin)
+}
+
+fn escape_latex(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
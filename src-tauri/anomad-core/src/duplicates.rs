@@ -0,0 +1,188 @@
+// Поиск дубликатов: размер -> отпечаток (начало и конец файла) -> хеш всего файла.
+// Почти дубликаты - PDF с одним /ID, но разным содержимым (например, в одной копии больше пометок)
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::fingerprint::{compute_fingerprint, file_hash, Fingerprint};
+use super::get_file_highlights::get_file_highlights;
+use super::pdf_date::parse_pdf_date;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateKind {
+    // Файлы совпадают байт в байт
+    Exact,
+    // Тот же /ID трейлера, но содержимое различается
+    SameDocument,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateFile {
+    pub path: String,
+    pub size: u64,
+    pub highlight_count: usize,
+    pub newest_annotation: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateGroup {
+    pub kind: DuplicateKind,
+    // Хеш содержимого для Exact, /ID для SameDocument
+    pub key: String,
+    pub files: Vec<DuplicateFile>,
+    // Путь копии с наибольшим числом пометок и копии с самой свежей пометкой
+    pub most_highlights: Option<String>,
+    pub newest_annotation: Option<String>,
+}
+
+// Результаты чтения файла переиспользуются между этапами
+#[derive(Default)]
+struct Scanner {
+    fingerprints: HashMap<PathBuf, Option<Fingerprint>>,
+    hashes: HashMap<PathBuf, Option<String>>,
+}
+
+impl Scanner {
+    fn fingerprint(&mut self, path: &Path) -> Option<Fingerprint> {
+        self.fingerprints
+            .entry(path.to_path_buf())
+            .or_insert_with(|| compute_fingerprint(path).ok())
+            .clone()
+    }
+
+    fn hash(&mut self, path: &Path) -> Option<String> {
+        self.hashes
+            .entry(path.to_path_buf())
+            .or_insert_with(|| file_hash(path).ok())
+            .clone()
+    }
+}
+
+pub fn group_duplicates(paths: &[PathBuf]) -> Vec<DuplicateGroup> {
+    let mut scanner = Scanner::default();
+    let mut groups = Vec::new();
+
+    // Один и тот же путь, переданный дважды, - не дубликат
+    let mut seen = HashSet::new();
+    let paths: Vec<&PathBuf> = paths.iter().filter(|p| seen.insert(*p)).collect();
+
+    // Размер читается из метаданных ФС без открытия файла
+    let mut by_size: BTreeMap<u64, Vec<&PathBuf>> = BTreeMap::new();
+    for &path in &paths {
+        if let Ok(metadata) = fs::metadata(path) {
+            if metadata.is_file() {
+                by_size.entry(metadata.len()).or_default().push(path);
+            }
+        }
+    }
+
+    for same_size in by_size.values().filter(|p| p.len() > 1) {
+        let mut by_fingerprint: BTreeMap<String, Vec<&PathBuf>> = BTreeMap::new();
+        for path in same_size {
+            if let Some(fingerprint) = scanner.fingerprint(path) {
+                by_fingerprint.entry(fingerprint.content_hash).or_default().push(path);
+            }
+        }
+
+        for candidates in by_fingerprint.values().filter(|p| p.len() > 1) {
+            let mut by_hash: BTreeMap<String, Vec<&PathBuf>> = BTreeMap::new();
+            for path in candidates {
+                if let Some(hash) = scanner.hash(path) {
+                    by_hash.entry(hash).or_default().push(path);
+                }
+            }
+
+            for (hash, files) in by_hash.into_iter().filter(|(_, p)| p.len() > 1) {
+                groups.push(make_group(DuplicateKind::Exact, hash, &files));
+            }
+        }
+    }
+
+    // /ID есть только у PDF, поэтому отпечаток остальных файлов не нужен
+    let mut by_trailer_id: BTreeMap<String, Vec<&PathBuf>> = BTreeMap::new();
+    for &path in paths.iter().filter(|p| is_pdf(p)) {
+        if let Some(trailer_id) = scanner.fingerprint(path).and_then(|f| f.trailer_id) {
+            by_trailer_id.entry(trailer_id).or_default().push(path);
+        }
+    }
+
+    // Один файл с таким /ID - не дубликат, читать его целиком незачем. Копии разного
+    // размера заведомо различаются, хеш всего файла нужен только при равных размерах
+    for (trailer_id, files) in by_trailer_id.into_iter().filter(|(_, p)| p.len() > 1) {
+        let sizes: HashSet<u64> = files
+            .iter()
+            .filter_map(|path| fs::metadata(path).ok().map(|m| m.len()))
+            .collect();
+        let differ = sizes.len() > 1
+            || files
+                .iter()
+                .filter_map(|path| scanner.hash(path))
+                .collect::<HashSet<_>>()
+                .len()
+                > 1;
+        if differ {
+            groups.push(make_group(DuplicateKind::SameDocument, trailer_id, &files));
+        }
+    }
+
+    groups
+}
+
+fn make_group(kind: DuplicateKind, key: String, paths: &[&PathBuf]) -> DuplicateGroup {
+    let files: Vec<DuplicateFile> = paths.iter().map(|path| describe_file(path)).collect();
+
+    // При равенстве побеждает первая копия (rev + max_by_key возвращает первый максимум)
+    let most_highlights = files
+        .iter()
+        .rev()
+        .filter(|f| f.highlight_count > 0)
+        .max_by_key(|f| f.highlight_count)
+        .map(|f| f.path.clone());
+    let newest_annotation = files
+        .iter()
+        .rev()
+        .filter_map(|f| Some((parse_pdf_date(f.newest_annotation.as_deref()?)?, f)))
+        .max_by_key(|(date, _)| *date)
+        .map(|(_, f)| f.path.clone());
+
+    DuplicateGroup {
+        kind,
+        key,
+        files,
+        most_highlights,
+        newest_annotation,
+    }
+}
+
+fn describe_file(path: &Path) -> DuplicateFile {
+    let highlights = if is_pdf(path) {
+        get_file_highlights(&path.to_string_lossy()).unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+
+    // Даты сравниваем разобранными: форматы и часовые пояса в /M бывают разные
+    let newest_annotation = highlights
+        .iter()
+        .filter_map(|h| {
+            let date = h.date.as_ref()?;
+            Some((parse_pdf_date(date)?, date.clone()))
+        })
+        .max_by_key(|(parsed, _)| *parsed)
+        .map(|(_, date)| date);
+
+    DuplicateFile {
+        path: path.to_string_lossy().into_owned(),
+        size: fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+        highlight_count: highlights.len(),
+        newest_annotation,
+    }
+}
+
+fn is_pdf(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"))
+}
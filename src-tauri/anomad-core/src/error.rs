@@ -0,0 +1,148 @@
+// Error type of the core library
+
+use std::fmt;
+use std::io;
+
+// Вид ошибки - стабильная часть, по которой вызывающий код выбирает реакцию.
+// Текст и цепочка причин хранятся в anyhow::Error внутри
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    NotFound,
+    AlreadyExists,
+    PermissionDenied,
+    // Файл открыт в другой программе (Windows)
+    Locked,
+    // Прочие ошибки файловой системы
+    Io,
+    // PDF не удалось разобрать или сохранить
+    InvalidPdf,
+    // Зашифрованный PDF нельзя изменить
+    Encrypted,
+    // Неверный шаблон, поле или параметр
+    InvalidInput,
+    // Внешний компонент недоступен: Everything, pdfium
+    Unavailable,
+    Other,
+}
+
+pub struct Error {
+    kind: ErrorKind,
+    inner: anyhow::Error,
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl Error {
+    pub fn new(kind: ErrorKind, message: impl fmt::Display) -> Self {
+        Error {
+            kind,
+            inner: anyhow::anyhow!("{}", message),
+        }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    // Исходная ошибка ОС, если она есть в цепочке причин
+    pub fn io_error(&self) -> Option<&io::Error> {
+        self.inner
+            .chain()
+            .find_map(|e| e.downcast_ref::<io::Error>())
+    }
+}
+
+fn io_error_kind(error: &io::Error) -> ErrorKind {
+    if cfg!(windows) && matches!(error.raw_os_error(), Some(32 | 33)) {
+        return ErrorKind::Locked;
+    }
+    match error.kind() {
+        io::ErrorKind::NotFound => ErrorKind::NotFound,
+        io::ErrorKind::AlreadyExists => ErrorKind::AlreadyExists,
+        io::ErrorKind::PermissionDenied => ErrorKind::PermissionDenied,
+        _ => ErrorKind::Io,
+    }
+}
+
+fn pdf_error_kind(error: &lopdf::Error) -> ErrorKind {
+    match error {
+        lopdf::Error::IO(io_error) => io_error_kind(io_error),
+        lopdf::Error::Decryption(_) => ErrorKind::Encrypted,
+        _ => ErrorKind::InvalidPdf,
+    }
+}
+
+// Вид определяется по первой известной ошибке в цепочке: контекст,
+// добавленный через anyhow, не должен терять вид исходной ошибки
+impl From<anyhow::Error> for Error {
+    fn from(inner: anyhow::Error) -> Self {
+        let kind = inner
+            .chain()
+            .find_map(|e| {
+                if let Some(error) = e.downcast_ref::<Error>() {
+                    Some(error.kind)
+                } else if let Some(error) = e.downcast_ref::<io::Error>() {
+                    Some(io_error_kind(error))
+                } else {
+                    e.downcast_ref::<lopdf::Error>().map(pdf_error_kind)
+                }
+            })
+            .unwrap_or(ErrorKind::Other);
+        Error { kind, inner }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error {
+            kind: io_error_kind(&error),
+            inner: error.into(),
+        }
+    }
+}
+
+impl From<lopdf::Error> for Error {
+    fn from(error: lopdf::Error) -> Self {
+        Error {
+            kind: pdf_error_kind(&error),
+            inner: error.into(),
+        }
+    }
+}
+
+impl From<xmp_toolkit::XmpError> for Error {
+    fn from(error: xmp_toolkit::XmpError) -> Self {
+        Error {
+            kind: ErrorKind::Other,
+            inner: error.into(),
+        }
+    }
+}
+
+// Поврежденные JSON-файлы ядра (журнал, история, sidecar) - ошибка данных
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Self {
+        Error {
+            kind: ErrorKind::InvalidInput,
+            inner: error.into(),
+        }
+    }
+}
+
+impl fmt::Debug for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Error")
+            .field("kind", &self.kind)
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+// Сообщение включает всю цепочку контекста: "Failed to open PDF: ...: ..."
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#}", self.inner)
+    }
+}
+
+impl std::error::Error for Error {}
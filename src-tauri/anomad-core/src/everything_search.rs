@@ -1,6 +1,5 @@
 // Shared search functionality for everything commands
 
-use chrono::{DateTime, TimeZone, Utc};
 use everything_rs::{Everything, EverythingRequestFlags, EverythingSort};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -10,8 +9,8 @@ use tokio::time::{sleep, timeout};
 use super::filetime_to_datetime::filetime_to_datetime;
 use super::get_file_info::get_file_info;
 use super::get_file_highlights::get_file_highlights;
+use super::file_info::{FileResult, SearchParams};
 use super::get_file_info_all_meta::get_file_info_all_meta;
-use crate::commands::types::{SearchParams, FileResult};
 
 lazy_static::lazy_static! {
     pub static ref EVERYTHING_LOCK: Arc<Mutex<()>> = Arc::new(Mutex::new(()));
@@ -78,7 +77,7 @@ pub async fn everything_search(params: &SearchParams, use_full_meta: bool) -> Op
             everything.set_max_results(max_results + 1);
 
             if let Err(e) = everything.query() {
-                eprintln!("Ошибка запроса: {}", e);
+                log::error!("Ошибка запроса: {}", e);
                 return None;
             }
 
@@ -132,8 +131,9 @@ pub async fn everything_search(params: &SearchParams, use_full_meta: bool) -> Op
                                     Some(hl)
                                 }
                                 Err(_) => {
-                                    // Если не удалось получить хайлайты, возвращаем пустой массив
-                                    Some(Vec::new())
+                                    // Файл не прочитан - None, как и без поиска: пустой массив
+                                    // означал бы, что пометок в файле нет
+                                    None
                                 }
                             }
                         } else {
@@ -160,6 +160,9 @@ pub async fn everything_search(params: &SearchParams, use_full_meta: bool) -> Op
                             pdf_title: info.pdf_title,
                             pdf_author: info.pdf_author,
                             pdf_creator: info.pdf_creator,
+                            pdf_details: info.pdf_details,
+                            content_hash: info.fingerprint.as_ref().map(|f| f.content_hash.clone()),
+                            trailer_id: info.fingerprint.and_then(|f| f.trailer_id),
                             highlights,
                         })
                     } else {
@@ -184,7 +187,7 @@ pub async fn everything_search(params: &SearchParams, use_full_meta: bool) -> Op
             everything.set_sort(EverythingSort::DateModifiedDescending);
 
             if let Err(e) = everything.query() {
-                eprintln!("Ошибка запроса: {}", e);
+                log::error!("Ошибка запроса: {}", e);
                 return None;
             }
 
@@ -240,6 +243,9 @@ pub async fn everything_search(params: &SearchParams, use_full_meta: bool) -> Op
                             pdf_title: info.pdf_title,
                             pdf_author: info.pdf_author,
                             pdf_creator: info.pdf_creator,
+                            pdf_details: info.pdf_details,
+                            content_hash: info.fingerprint.as_ref().map(|f| f.content_hash.clone()),
+                            trailer_id: info.fingerprint.and_then(|f| f.trailer_id),
                             highlights,
                         })
                     } else {
@@ -256,3 +262,35 @@ pub async fn everything_search(params: &SearchParams, use_full_meta: bool) -> Op
 
     result
 }
+
+// Только полные пути, без метаданных. Используется для служебных запросов (например, поиска перемещенных файлов)
+pub async fn everything_paths(query: &str, max_results: u32) -> Option<Vec<String>> {
+    let lock_result = timeout(Duration::from_secs(5), EVERYTHING_LOCK.lock()).await;
+    let _lock = match lock_result {
+        Ok(lock) => lock,
+        Err(_) => return None,
+    };
+
+    let everything = Everything::new();
+    everything.set_search(query);
+    everything.set_request_flags(EverythingRequestFlags::FullPathAndFileName);
+    everything.set_max_results(max_results);
+
+    if let Err(e) = everything.query() {
+        log::error!("Ошибка запроса: {}", e);
+        return None;
+    }
+
+    let paths = (0..everything.get_result_count())
+        .filter_map(|idx| everything.get_result_full_path(idx).ok())
+        .collect();
+
+    Some(paths)
+}
+
+// Версия запущенного Everything для диагностики. Если служба не отвечает,
+// SDK возвращает нули - тогда None
+pub fn everything_version() -> Option<String> {
+    let version = Everything::version();
+    (version != "0.0.0.0").then_some(version)
+}
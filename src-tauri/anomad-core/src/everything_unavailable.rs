@@ -0,0 +1,19 @@
+// Search stand-in for systems without Everything: same API, nothing is ever found
+
+use super::file_info::{FileResult, SearchParams};
+
+// None - так же, как при незапущенном Everything: команды сообщают, что поиск недоступен
+pub async fn everything_search(
+    _params: &SearchParams,
+    _use_full_meta: bool,
+) -> Option<(Vec<FileResult>, bool)> {
+    None
+}
+
+pub async fn everything_paths(_query: &str, _max_results: u32) -> Option<Vec<String>> {
+    None
+}
+
+pub fn everything_version() -> Option<String> {
+    None
+}
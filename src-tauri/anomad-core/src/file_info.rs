@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+
+use super::fingerprint::Fingerprint;
+use super::get_file_highlights::Highlight;
+use super::pdf_metadata::PdfDetails;
+
+#[derive(Debug)]
+pub struct FileInfo {
+    pub is_locked: bool,
+    pub file_id: Option<String>,
+    pub pdf_title: Option<String>,
+    pub pdf_author: Option<String>,
+    pub pdf_creator: Option<String>,
+    pub pdf_details: Option<PdfDetails>,
+    pub fingerprint: Option<Fingerprint>,
+}
+
+// Строка таблицы файлов: результат поиска или файл проекта
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileResult {
+    pub file_name: String,
+    pub full_path: String,
+    pub title: String,
+
+    pub size: Option<u64>,
+    pub created_date: String,
+    pub modified_date: String,
+    pub extension: Option<String>,
+    pub is_locked: bool,
+    pub id: Option<String>,
+    pub pdf_title: Option<String>,
+    pub pdf_author: Option<String>,
+    pub pdf_creator: Option<String>,
+    pub pdf_details: Option<PdfDetails>,
+    pub content_hash: Option<String>,
+    pub trailer_id: Option<String>,
+    pub highlights: Option<Vec<Highlight>>,
+}
+
+// Параметры для функции поиска
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SearchParams {
+    pub query: String,
+    pub path: Option<String>,
+    pub count: Option<u32>,
+    pub include_highlights: Option<bool>,
+}
@@ -0,0 +1,32 @@
+use anyhow::Context;
+use fs2::FileExt;
+use std::fs::OpenOptions;
+use std::path::Path;
+
+use crate::error::{Error, ErrorKind, Result};
+
+// Файл считается занятым, если открыть его мешает другая программа (ошибки ОС 32 и 33
+// на Windows получают вид Locked при преобразовании) или не берется эксклюзивная блокировка
+// (так ведут себя читалки PDF на Windows, пока документ открыт).
+// Прочие ошибки открытия - нет прав, путь ведет к папке - возвращаются как есть
+pub fn ensure_not_locked(path: &Path) -> Result<()> {
+    let file = OpenOptions::new()
+        .write(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    if file.try_lock_exclusive().is_err() {
+        return Err(Error::new(
+            ErrorKind::Locked,
+            format!("File is open in another program: {}", path.display()),
+        ));
+    }
+    Ok(())
+}
+
+// Для отображения и ожидания: ошибки, не связанные с блокировкой, занятостью не считаются
+pub fn is_file_locked(path: &Path) -> bool {
+    std::panic::catch_unwind(
+        || matches!(ensure_not_locked(path), Err(e) if e.kind() == ErrorKind::Locked),
+    )
+    .unwrap_or_default()
+}
@@ -0,0 +1,157 @@
+// Разбор имен файлов по пользовательским шаблонам.
+// Шаблон: "{author} - {year} - {title}"; регулярное выражение: "re:^(?P<year>\d{4})_(?P<author>[^_]+)_(?P<title>.+)$"
+
+use anyhow::Context;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::error::{Error, ErrorKind, Result};
+
+const REGEX_PREFIX: &str = "re:";
+
+// Прежнее правило: четыре части через пробел, четвертая - заглавие
+pub const DEFAULT_PATTERNS: [&str; 1] = [r"re:^\S+ \S+ \S+ (?P<title>.+)$"];
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ParsedFileName {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub year: Option<String>,
+    // Прочие именованные группы шаблона
+    pub fields: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FilenamePattern {
+    regex: Regex,
+}
+
+impl FilenamePattern {
+    pub fn parse(pattern: &str) -> Result<Self> {
+        let source = match pattern.strip_prefix(REGEX_PREFIX) {
+            Some(regex) => regex.to_string(),
+            None => template_to_regex(pattern)?,
+        };
+
+        let regex = Regex::new(&source).with_context(|| format!("Invalid pattern '{}'", pattern))?;
+        if regex.capture_names().flatten().next().is_none() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Pattern '{}' has no named fields", pattern),
+            ));
+        }
+        Ok(FilenamePattern { regex })
+    }
+
+    // Сопоставляем с именем файла без расширения
+    pub fn parse_file_name(&self, stem: &str) -> Option<ParsedFileName> {
+        let captures = self.regex.captures(stem)?;
+        let mut parsed = ParsedFileName::default();
+
+        for name in self.regex.capture_names().flatten() {
+            let value = match captures.name(name) {
+                Some(m) => m.as_str().trim().to_string(),
+                None => continue,
+            };
+            if value.is_empty() {
+                continue;
+            }
+
+            match name {
+                "title" => parsed.title = Some(value),
+                "author" => parsed.author = Some(value),
+                "year" => parsed.year = Some(value),
+                _ => {
+                    parsed.fields.insert(name.to_string(), value);
+                }
+            }
+        }
+
+        Some(parsed)
+    }
+}
+
+// {name} -> именованная группа, {year} - четыре цифры, {*} - пропустить, {{ и }} - фигурные скобки.
+// Пробелы в шаблоне совпадают с любым количеством пробелов в имени
+fn template_to_regex(template: &str) -> Result<String> {
+    let mut regex = String::from("^");
+    let mut literal = String::new();
+    let mut chars = template.chars().peekable();
+
+    let flush = |literal: &mut String, regex: &mut String| {
+        for (i, part) in literal.split(' ').enumerate() {
+            if i > 0 {
+                regex.push_str(r"\s+");
+            }
+            regex.push_str(&regex::escape(part));
+        }
+        literal.clear();
+    };
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                literal.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                literal.push('}');
+            }
+            '{' => {
+                let name: String = chars.by_ref().take_while(|c| *c != '}').collect();
+                flush(&mut literal, &mut regex);
+
+                match name.as_str() {
+                    "*" => regex.push_str(".*?"),
+                    "year" => regex.push_str(r"(?P<year>\d{4})"),
+                    name if is_field_name(name) => {
+                        regex.push_str(&format!("(?P<{}>.+?)", name));
+                    }
+                    _ => {
+                        return Err(Error::new(
+                            ErrorKind::InvalidInput,
+                            format!("Invalid field '{{{}}}' in template", name),
+                        ))
+                    }
+                }
+            }
+            _ => literal.push(c),
+        }
+    }
+
+    flush(&mut literal, &mut regex);
+    regex.push('$');
+    Ok(regex)
+}
+
+fn is_field_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// Некорректные шаблоны пропускаем, чтобы одна ошибка в настройках не отключала остальные.
+// Пустые строки остаются от редактирования списка в настройках
+pub fn compile_patterns<S: AsRef<str>>(patterns: &[S]) -> Vec<FilenamePattern> {
+    patterns
+        .iter()
+        .map(|pattern| pattern.as_ref().trim())
+        .filter(|pattern| !pattern.is_empty())
+        .filter_map(|pattern| match FilenamePattern::parse(pattern) {
+            Ok(pattern) => Some(pattern),
+            Err(e) => {
+                log::warn!("Шаблон имени файла пропущен: {}", e);
+                None
+            }
+        })
+        .collect()
+}
+
+// Первый подошедший шаблон побеждает
+pub fn parse_file_name(stem: &str, patterns: &[FilenamePattern]) -> Option<ParsedFileName> {
+    patterns
+        .iter()
+        .find_map(|pattern| pattern.parse_file_name(stem))
+}
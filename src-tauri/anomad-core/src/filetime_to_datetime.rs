@@ -1,4 +1,4 @@
-use chrono::{DateTime, TimeZone, Utc};
+use chrono::{TimeZone, Utc};
 
 pub fn filetime_to_datetime(filetime: u64) -> String {
     const WINDOWS_TO_UNIX_EPOCH_SECS: i64 = 11_644_473_600;
@@ -0,0 +1,107 @@
+// Отпечаток файла, не зависящий от пути и тома: хеш начала и конца файла плюс /ID из трейлера PDF
+
+use regex::bytes::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::error::Result;
+
+// Сколько байт читаем с начала и с конца файла
+const CHUNK_SIZE: u64 = 64 * 1024;
+
+lazy_static::lazy_static! {
+    // Первый элемент массива /ID - постоянный идентификатор документа, он не меняется при сохранении
+    static ref TRAILER_ID_RE: Regex =
+        Regex::new(r"/ID\s*\[\s*<([0-9A-Fa-f\s]+)>").unwrap();
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fingerprint {
+    pub size: u64,
+    pub content_hash: String,
+    pub trailer_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FingerprintMatch {
+    // Тот же файл, только перемещен или переименован
+    Exact,
+    // Тот же документ, но файл менялся (например, добавлены аннотации)
+    SameDocument,
+}
+
+pub fn compute_fingerprint(path: &Path) -> Result<Fingerprint> {
+    let mut file = File::open(path)?;
+    let size = file.metadata()?.len();
+
+    let head = read_chunk(&mut file, 0, CHUNK_SIZE.min(size))?;
+    let tail_start = size.saturating_sub(CHUNK_SIZE).max(head.len() as u64);
+    let tail = read_chunk(&mut file, tail_start, size - tail_start)?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(size.to_le_bytes());
+    hasher.update(&head);
+    hasher.update(&tail);
+    let content_hash = hasher
+        .finalize()
+        .iter()
+        .take(16)
+        .map(|b| format!("{:02x}", b))
+        .collect();
+
+    // Трейлер в конце файла; у маленьких файлов он может оказаться в head
+    let trailer_id = find_trailer_id(&tail).or_else(|| find_trailer_id(&head));
+
+    Ok(Fingerprint {
+        size,
+        content_hash,
+        trailer_id,
+    })
+}
+
+// Хеш всего файла - для окончательной проверки, что файлы совпадают байт в байт
+pub fn file_hash(path: &Path) -> Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)?;
+    Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+fn read_chunk(file: &mut File, start: u64, len: u64) -> Result<Vec<u8>> {
+    let mut buffer = vec![0; len as usize];
+    file.seek(SeekFrom::Start(start))?;
+    file.read_exact(&mut buffer)?;
+    Ok(buffer)
+}
+
+// Берем последний /ID: после инкрементальных сохранений актуален последний трейлер
+fn find_trailer_id(bytes: &[u8]) -> Option<String> {
+    let captures = TRAILER_ID_RE.captures_iter(bytes).last()?;
+    let id: String = String::from_utf8_lossy(&captures[1])
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    Some(id.to_uppercase()).filter(|id| !id.is_empty())
+}
+
+// content_hash включает размер файла, поэтому отдельно размер не сравниваем
+pub fn match_fingerprint(
+    content_hash: Option<&str>,
+    trailer_id: Option<&str>,
+    actual: &Fingerprint,
+) -> Option<FingerprintMatch> {
+    if content_hash == Some(actual.content_hash.as_str()) {
+        return Some(FingerprintMatch::Exact);
+    }
+
+    match (trailer_id, actual.trailer_id.as_deref()) {
+        (Some(expected), Some(actual)) if expected.eq_ignore_ascii_case(actual) => {
+            Some(FingerprintMatch::SameDocument)
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,260 @@
+use anyhow::Context;
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::pdf_metadata::load_pdf;
+use crate::error::Result;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Highlight {
+    pub page: u32,
+    pub highlighted_text: String,
+    pub annotation_text: Option<String>,
+    pub date: Option<String>,
+    pub highlight_type: String,
+    pub color: Option<Vec<f32>>, // RGB или CMYK значения (0.0 - 1.0)
+    // Чем пометка узнается между извлечениями: /NM, номер объекта или /Rect
+    #[serde(default)]
+    pub annotation_id: Option<String>,
+}
+
+pub fn get_file_highlights(path_str: &str) -> Result<Vec<Highlight>> {
+    let path = Path::new(path_str);
+
+    // Через load_pdf, чтобы прочитать пометки и в зашифрованных пустым паролем файлах
+    let (doc, _) = load_pdf(path)?;
+
+    let mut highlights = Vec::new();
+
+    for (page_num, page_id) in doc.get_pages() {
+        for (object_id, dict) in page_annotation_entries(&doc, page_id)? {
+            // Проверяем Subtype аннотации
+            let subtype = dict
+                .get(b"Subtype")
+                .and_then(|o| o.as_name_str())
+                .unwrap_or("");
+
+            // Принимаем Highlight, Text (обычные комментарии/sticky notes) и FreeText
+            let is_supported = matches!(subtype, "Highlight" | "Text" | "FreeText");
+
+            if !is_supported {
+                continue;
+            }
+
+            // Извлекаем текст аннотации из поля Contents
+            let annotation_text = dict
+                .get(b"Contents")
+                .ok()
+                .and_then(decode_pdf_string)
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty());
+
+            // Извлекаем дату модификации
+            let date = dict
+                .get(b"M")
+                .ok()
+                .and_then(decode_pdf_string)
+                .filter(|s| !s.is_empty());
+
+            // Извлекаем цвет аннотации
+            let color = extract_color(&dict);
+
+            // Определяем тип и добавляем highlight
+            let (highlight_type, highlighted_text) = if annotation_text.is_some() {
+                ("annotation".to_string(), String::new())
+            } else {
+                ("highlight".to_string(), String::new())
+            };
+
+            highlights.push(Highlight {
+                page: page_num,
+                highlighted_text,
+                annotation_text,
+                date,
+                highlight_type,
+                color,
+                annotation_id: annotation_id(object_id, &dict),
+            });
+        }
+    }
+
+    Ok(highlights)
+}
+
+// /NM задает сама программа чтения и переживает пересохранение файла; без него годится
+// номер объекта, а у аннотации прямо в /Annots остается только прямоугольник
+fn annotation_id(object_id: Option<ObjectId>, dict: &Dictionary) -> Option<String> {
+    if let Some(name) = dict
+        .get(b"NM")
+        .ok()
+        .and_then(decode_pdf_string)
+        .filter(|s| !s.is_empty())
+    {
+        return Some(format!("nm:{}", name));
+    }
+    if let Some((number, generation)) = object_id {
+        return Some(format!("obj:{} {}", number, generation));
+    }
+    match dict.get(b"Rect") {
+        Ok(Object::Array(values)) => {
+            let values: Option<Vec<String>> = values
+                .iter()
+                .map(|o| o.as_float().ok().map(|v| format!("{:.2}", v)))
+                .collect();
+            values.map(|values| format!("rect:{}", values.join(" ")))
+        }
+        _ => None,
+    }
+}
+
+// Словари аннотаций страницы: /Annots бывает как массивом, так и ссылкой на массив
+pub fn page_annotations(doc: &Document, page_id: ObjectId) -> Result<Vec<Dictionary>> {
+    Ok(page_annotation_entries(doc, page_id)?
+        .into_iter()
+        .map(|(_, dict)| dict)
+        .collect())
+}
+
+// То же с номерами объектов; у прямых словарей в /Annots номера нет
+pub fn page_annotation_entries(
+    doc: &Document,
+    page_id: ObjectId,
+) -> Result<Vec<(Option<ObjectId>, Dictionary)>> {
+    let page_dict = doc
+        .get_dictionary(page_id)
+        .context("Failed to get page dictionary")?;
+
+    let annots_list = match page_dict.get(b"Annots") {
+        Ok(Object::Array(list)) => list.clone(),
+        Ok(Object::Reference(id)) => doc
+            .get_object(*id)
+            .and_then(|o| o.as_array())
+            .cloned()
+            .context("Failed to resolve Annots reference")?,
+        _ => return Ok(Vec::new()),
+    };
+
+    let annots = annots_list
+        .into_iter()
+        .filter_map(|annot_obj| match annot_obj {
+            Object::Reference(id) => doc.get_dictionary(id).ok().map(|d| (Some(id), d.clone())),
+            Object::Dictionary(dict) => Some((None, dict)),
+            _ => None,
+        })
+        .collect();
+
+    Ok(annots)
+}
+
+pub fn extract_color(dict: &Dictionary) -> Option<Vec<f32>> {
+    // Цвет может быть в поле "C" (Color) аннотации
+    dict.get(b"C")
+        .ok()
+        .and_then(|obj| match obj {
+            Object::Array(arr) => {
+                let colors: Option<Vec<f32>> = arr
+                    .iter()
+                    .map(|o| match o {
+                        Object::Real(f) => Some(*f),
+                        Object::Integer(i) => Some(*i as f32),
+                        _ => None,
+                    })
+                    .collect();
+                colors
+            }
+            _ => None,
+        })
+        .filter(|v| !v.is_empty())
+}
+
+pub fn decode_pdf_string(obj: &Object) -> Option<String> {
+    let bytes = match obj {
+        Object::String(bytes, _) => bytes,
+        _ => return None,
+    };
+
+    if bytes.len() >= 2 && bytes[0] == 0xFE && bytes[1] == 0xFF {
+        let u16_vec: Vec<u16> = bytes[2..]
+            .chunks_exact(2)
+            .map(|chunk| u16::from_be_bytes([chunk[0], chunk[1]]))
+            .collect();
+        return String::from_utf16(&u16_vec).ok();
+    }
+
+    if bytes.len() >= 3 && bytes[0] == 0xEF && bytes[1] == 0xBB && bytes[2] == 0xBF {
+        return String::from_utf8(bytes[3..].to_vec()).ok();
+    }
+
+    if let Some(s) = decode_pdf_doc_encoding(bytes) {
+        return Some(s);
+    }
+
+    if let Ok(s) = String::from_utf8(bytes.clone()) {
+        return Some(s);
+    }
+
+    let s: String = bytes.iter().map(|&b| b as char).collect();
+
+    Some(s)
+}
+
+// Символы PDFDocEncoding, которые отличаются от Latin-1: 0x18-0x1F и 0x80-0xA0.
+// None - код в кодировке не определен
+fn pdf_doc_char(byte: u8) -> Option<char> {
+    let c = match byte {
+        0x18 => '\u{02D8}',
+        0x19 => '\u{02C7}',
+        0x1A => '\u{02C6}',
+        0x1B => '\u{02D9}',
+        0x1C => '\u{02DD}',
+        0x1D => '\u{02DB}',
+        0x1E => '\u{02DA}',
+        0x1F => '\u{02DC}',
+        0x80 => '\u{2022}',
+        0x81 => '\u{2020}',
+        0x82 => '\u{2021}',
+        0x83 => '\u{2026}',
+        0x84 => '\u{2014}',
+        0x85 => '\u{2013}',
+        0x86 => '\u{0192}',
+        0x87 => '\u{2044}',
+        0x88 => '\u{2039}',
+        0x89 => '\u{203A}',
+        0x8A => '\u{2212}',
+        0x8B => '\u{2030}',
+        0x8C => '\u{201E}',
+        0x8D => '\u{201C}',
+        0x8E => '\u{201D}',
+        0x8F => '\u{2018}',
+        0x90 => '\u{2019}',
+        0x91 => '\u{201A}',
+        0x92 => '\u{2122}',
+        0x93 => '\u{FB01}',
+        0x94 => '\u{FB02}',
+        0x95 => '\u{0141}',
+        0x96 => '\u{0152}',
+        0x97 => '\u{0160}',
+        0x98 => '\u{0178}',
+        0x99 => '\u{017D}',
+        0x9A => '\u{0131}',
+        0x9B => '\u{0142}',
+        0x9C => '\u{0153}',
+        0x9D => '\u{0161}',
+        0x9E => '\u{017E}',
+        0xA0 => '\u{20AC}',
+        0x7F | 0x9F | 0xAD => return None,
+        byte => byte as char,
+    };
+    Some(c)
+}
+
+// Строка без BOM по спецификации записана в PDFDocEncoding. Но многие программы
+// пишут UTF-8 без BOM: корректный многобайтовый UTF-8 в PDFDocEncoding дал бы
+// бессмыслицу вида "Ã©", поэтому такие строки оставляем разбору как UTF-8
+fn decode_pdf_doc_encoding(bytes: &[u8]) -> Option<String> {
+    if !bytes.is_ascii() && std::str::from_utf8(bytes).is_ok() {
+        return None;
+    }
+    bytes.iter().map(|&b| pdf_doc_char(b)).collect()
+}
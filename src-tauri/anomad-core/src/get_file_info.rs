@@ -0,0 +1,41 @@
+use std::path::Path;
+
+use super::file_info::FileInfo;
+use super::file_lock::is_file_locked;
+
+// Номер файла в ФС (индекс NTFS, в других системах inode): не меняется
+// при переименовании и переносе внутри тома
+#[cfg(windows)]
+pub fn file_index(path: &Path) -> Option<String> {
+    match file_id::get_low_res_file_id(path).ok()? {
+        file_id::FileId::LowRes { file_index, .. } => Some(file_index.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(not(windows))]
+pub fn file_index(path: &Path) -> Option<String> {
+    match file_id::get_file_id(path).ok()? {
+        file_id::FileId::Inode { inode_number, .. } => Some(inode_number.to_string()),
+        _ => None,
+    }
+}
+
+pub fn get_file_info(path_str: &str) -> FileInfo {
+    let path = Path::new(path_str);
+    
+    let is_locked = is_file_locked(path);
+
+    let file_id = file_index(path);
+
+    // Быстрый путь для поиска: PDF не открываем, метаданные читает get_file_info_all_meta
+    FileInfo {
+        is_locked,
+        file_id,
+        pdf_title: None,
+        pdf_author: None,
+        pdf_creator: None,
+        pdf_details: None,
+        fingerprint: None,
+    }
+}
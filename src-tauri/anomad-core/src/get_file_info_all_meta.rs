@@ -1,46 +1,40 @@
 use anyhow::Context;
-use fs2::FileExt;
-use std::fs::OpenOptions;
 use std::path::Path;
-use xmp_toolkit::{xmp_ns, OpenFileOptions, XmpFile, XmpMeta};
+use xmp_toolkit::{xmp_ns, OpenFileOptions, XmpFile};
 
 use super::file_info::FileInfo;
+use super::file_lock::is_file_locked;
+use super::get_file_info::file_index;
+use super::fingerprint::compute_fingerprint;
+use super::pdf_metadata::read_pdf_metadata;
 
 pub fn get_file_info_all_meta(path_str: &str) -> FileInfo {
     let path = Path::new(path_str);
 
-    let is_locked = match std::panic::catch_unwind(|| {
-        if let Ok(file) = OpenOptions::new().write(true).open(path) {
-            file.try_lock_exclusive().is_err()
-        } else {
-            true
-        }
-    }) {
-        Ok(result) => result,
-        Err(_) => false,
-    };
+    let is_locked = is_file_locked(path);
 
-    let file_index = file_id::get_low_res_file_id(path).ok().and_then(|id| {
-        if let file_id::FileId::LowRes { file_index, .. } = id {
-            Some(file_index.to_string())
-        } else {
-            None
-        }
-    });
-
-    let file_id = match file_index {
-        Some(index) => Some(index),
-        None => None,
-    };
+    let file_id = file_index(path);
 
     // Получаем PDF метаданные только для PDF файлов
-    let (pdf_title, pdf_author, pdf_creator) = if path_str.to_lowercase().ends_with(".pdf") {
-        match get_pdf_metadata_xmp(path_str) {
-            Ok((title, author, creator)) => (title, author, creator),
-            Err(_) => (None, None, None),
-        }
+    let (pdf_title, pdf_author, pdf_creator, pdf_details) =
+        if path_str.to_lowercase().ends_with(".pdf") {
+            match read_pdf_metadata(path) {
+                Ok(meta) => (meta.title, meta.author, meta.creator, Some(meta.details)),
+                // lopdf не смог разобрать файл - пробуем хотя бы найти XMP-пакет
+                Err(_) => match get_pdf_metadata_xmp(path_str) {
+                    Ok((title, author, creator)) => (title, author, creator, None),
+                    Err(_) => (None, None, None, None),
+                },
+            }
+        } else {
+            (None, None, None, None)
+        };
+
+    // Отпечаток нужен, чтобы найти файл после перемещения (relink_files)
+    let fingerprint = if path_str.to_lowercase().ends_with(".pdf") {
+        compute_fingerprint(path).ok()
     } else {
-        (None, None, None)
+        None
     };
 
     FileInfo {
@@ -49,6 +43,8 @@ pub fn get_file_info_all_meta(path_str: &str) -> FileInfo {
         pdf_title,
         pdf_author,
         pdf_creator,
+        pdf_details,
+        fingerprint,
     }
 }
 
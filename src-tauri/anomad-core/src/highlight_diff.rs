@@ -0,0 +1,80 @@
+// Разница между двумя извлечениями пометок одного файла.
+// Пометка узнается по странице и annotation_id (/NM, номер объекта или /Rect), а без него - по типу;
+// тот же ключ с другой заметкой, цветом или датой - измененная пометка
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::get_file_highlights::Highlight;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HighlightDiff {
+    pub added: Vec<Highlight>,
+    pub changed: Vec<Highlight>,
+    pub removed: Vec<Highlight>,
+}
+
+impl HighlightDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty() && self.removed.is_empty()
+    }
+}
+
+type HighlightKey = (u32, String);
+
+// Тип в ключ с annotation_id не входит: добавленная к выделению заметка меняет тип,
+// но пометка остается той же
+fn key(highlight: &Highlight) -> HighlightKey {
+    let id = match &highlight.annotation_id {
+        Some(id) => id.clone(),
+        None => format!("type:{}", highlight.highlight_type),
+    };
+    (highlight.page, id)
+}
+
+fn same_content(a: &Highlight, b: &Highlight) -> bool {
+    a.annotation_text == b.annotation_text
+        && a.color == b.color
+        && a.date == b.date
+        && a.highlight_type == b.highlight_type
+}
+
+pub fn diff_highlights(previous: &[Highlight], current: &[Highlight]) -> HighlightDiff {
+    let mut old_by_key: BTreeMap<HighlightKey, Vec<&Highlight>> = BTreeMap::new();
+    for highlight in previous {
+        old_by_key
+            .entry(key(highlight))
+            .or_default()
+            .push(highlight);
+    }
+
+    let mut diff = HighlightDiff::default();
+    let mut unmatched = Vec::new();
+
+    // Сначала снимаем полностью совпавшие, чтобы одинаковые пометки не путались с измененными
+    for highlight in current {
+        let candidates = old_by_key.entry(key(highlight)).or_default();
+        match candidates
+            .iter()
+            .position(|old| same_content(old, highlight))
+        {
+            Some(index) => {
+                candidates.remove(index);
+            }
+            None => unmatched.push(highlight),
+        }
+    }
+
+    for highlight in unmatched {
+        let candidates = old_by_key.entry(key(highlight)).or_default();
+        if candidates.is_empty() {
+            diff.added.push(highlight.clone());
+        } else {
+            candidates.remove(0);
+            diff.changed.push(highlight.clone());
+        }
+    }
+
+    diff.removed = old_by_key.into_values().flatten().cloned().collect();
+    diff
+}
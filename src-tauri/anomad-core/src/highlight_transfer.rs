@@ -0,0 +1,217 @@
+// Перенос пометок в новое издание той же книги.
+// Текст под пометкой берется из старого файла, ищется в тексте нового (точно, затем нечетко)
+// и пометка создается заново по координатам найденных символов
+
+use anyhow::Context;
+use pdfium_render::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::annotations::Annotation;
+use super::text_layer::{LayerChar, TextLayer};
+use crate::error::Result;
+
+// Пометки, привязанные к тексту; остальные аннотации по тексту не найти
+pub const MARKUP_SUBTYPES: [&str; 4] = ["Highlight", "Underline", "StrikeOut", "Squiggly"];
+pub const DEFAULT_MIN_CONFIDENCE: f32 = 0.8;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferredHighlight {
+    pub old_page: u32,
+    pub new_page: u32,
+    pub subtype: String,
+    pub text: String,
+    pub confidence: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnplacedReason {
+    // Аннотация не привязана к тексту (заметка, рисунок и т.п.)
+    Unsupported,
+    // Под пометкой в старом файле нет текстового слоя
+    NoText,
+    // Текст не найден с нужной уверенностью
+    NotFound,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnplacedHighlight {
+    pub page: u32,
+    pub subtype: String,
+    pub text: Option<String>,
+    pub contents: Option<String>,
+    pub reason: UnplacedReason,
+}
+
+#[derive(Debug, Default)]
+pub struct TransferPlan {
+    pub to_add: Vec<Annotation>,
+    pub placed: Vec<TransferredHighlight>,
+    pub unplaced: Vec<UnplacedHighlight>,
+}
+
+// annotations - аннотации старого файла (read_annotations), страницы с 1
+pub fn plan_transfer(
+    pdfium: &Pdfium,
+    old_path: &Path,
+    new_path: &Path,
+    annotations: &[Annotation],
+    min_confidence: f32,
+) -> Result<TransferPlan> {
+    let old_doc = pdfium
+        .load_pdf_from_file(old_path, None)
+        .with_context(|| format!("Failed to open {}", old_path.display()))?;
+    let new_doc = pdfium
+        .load_pdf_from_file(new_path, None)
+        .with_context(|| format!("Failed to open {}", new_path.display()))?;
+
+    let layer = read_text_layer(&new_doc)?;
+    let old_page_count = u32::from(old_doc.pages().len()).max(1);
+    let mut plan = TransferPlan::default();
+
+    for annotation in annotations {
+        let unplaced = |text: Option<String>, reason| UnplacedHighlight {
+            page: annotation.page,
+            subtype: annotation.subtype.clone(),
+            text,
+            contents: annotation.contents.clone(),
+            reason,
+        };
+
+        if !MARKUP_SUBTYPES.contains(&annotation.subtype.as_str()) {
+            plan.unplaced
+                .push(unplaced(None, UnplacedReason::Unsupported));
+            continue;
+        }
+
+        let text = highlighted_text(&old_doc, annotation).filter(|t| !t.trim().is_empty());
+        let text = match text {
+            Some(text) => text,
+            None => {
+                plan.unplaced.push(unplaced(None, UnplacedReason::NoText));
+                continue;
+            }
+        };
+
+        // Ожидаемая страница - та же доля книги; вставки и сокращения сдвигают ее на несколько страниц
+        let expected_page = (annotation.page - 1) * layer.page_count / old_page_count + 1;
+        let found = match layer.find(&text, expected_page, min_confidence) {
+            Some(found) => found,
+            None => {
+                plan.unplaced
+                    .push(unplaced(Some(text), UnplacedReason::NotFound));
+                continue;
+            }
+        };
+
+        let areas = layer.areas(&found);
+        let new_page = match areas.first() {
+            Some(area) => area.page,
+            None => {
+                plan.unplaced
+                    .push(unplaced(Some(text), UnplacedReason::NotFound));
+                continue;
+            }
+        };
+
+        // Пометка через границу страниц становится несколькими; текст заметки - только у первой
+        for (i, area) in areas.into_iter().enumerate() {
+            plan.to_add.push(Annotation {
+                page: area.page,
+                rect: area.rect,
+                quad_points: Some(area.quad_points),
+                contents: if i == 0 {
+                    annotation.contents.clone()
+                } else {
+                    None
+                },
+                name: if i == 0 {
+                    annotation.name.clone()
+                } else {
+                    None
+                },
+                ..annotation.clone()
+            });
+        }
+
+        plan.placed.push(TransferredHighlight {
+            old_page: annotation.page,
+            new_page,
+            subtype: annotation.subtype.clone(),
+            text,
+            confidence: found.confidence,
+        });
+    }
+
+    Ok(plan)
+}
+
+fn read_text_layer(doc: &PdfDocument) -> Result<TextLayer> {
+    let mut chars = Vec::new();
+
+    for (index, page) in doc.pages().iter().enumerate() {
+        let page_number = index as u32 + 1;
+        let text = page
+            .text()
+            .with_context(|| format!("Failed to read text of page {}", page_number))?;
+
+        for ch in text.chars().iter() {
+            let Some(unicode) = ch.unicode_char() else {
+                continue;
+            };
+            let bounds = if ch.is_generated().unwrap_or(false) {
+                None
+            } else {
+                ch.loose_bounds().ok().map(|r| {
+                    [
+                        r.left().value,
+                        r.bottom().value,
+                        r.right().value,
+                        r.top().value,
+                    ]
+                })
+            };
+            chars.push(LayerChar {
+                page: page_number,
+                ch: unicode,
+                bounds,
+            });
+        }
+    }
+
+    Ok(TextLayer::new(chars, u32::from(doc.pages().len())))
+}
+
+// Текст под четырехугольниками пометки (или под ее рамкой, если QuadPoints нет)
+fn highlighted_text(doc: &PdfDocument, annotation: &Annotation) -> Option<String> {
+    let index = u16::try_from(annotation.page.checked_sub(1)?).ok()?;
+    let page = doc.pages().get(index).ok()?;
+    let text = page.text().ok()?;
+
+    let rects: Vec<[f32; 4]> = match &annotation.quad_points {
+        Some(points) if points.len() >= 8 => points
+            .chunks_exact(8)
+            .map(|quad| {
+                let xs = [quad[0], quad[2], quad[4], quad[6]];
+                let ys = [quad[1], quad[3], quad[5], quad[7]];
+                [
+                    xs.iter().copied().fold(f32::MAX, f32::min),
+                    ys.iter().copied().fold(f32::MAX, f32::min),
+                    xs.iter().copied().fold(f32::MIN, f32::max),
+                    ys.iter().copied().fold(f32::MIN, f32::max),
+                ]
+            })
+            .collect(),
+        _ => vec![annotation.rect],
+    };
+
+    let lines: Vec<String> = rects
+        .into_iter()
+        .map(|[left, bottom, right, top]| {
+            text.inside_rect(PdfRect::new_from_values(bottom, left, top, right))
+        })
+        .collect();
+
+    Some(lines.join("\n"))
+}
@@ -0,0 +1,262 @@
+// Поиск DOI, ISBN и arXiv-идентификаторов в метаданных и тексте PDF. Работает без сети
+
+use lopdf::Document;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use xmp_toolkit::{xmp_ns, XmpMeta};
+
+use crate::error::Result;
+use super::pdf_metadata::{document_metadata, load_pdf, read_xmp_packet, PdfMetadata};
+
+// Страницы, на которых обычно печатают DOI/ISBN: титульные и последние
+const FIRST_PAGES: u32 = 3;
+const LAST_PAGES: u32 = 2;
+
+// Версии пространства имен PRISM, в которых встречается prism:doi
+const PRISM_NAMESPACES: [&str; 3] = [
+    "http://prismstandard.org/namespaces/basic/2.0/",
+    "http://prismstandard.org/namespaces/basic/2.1/",
+    "http://prismstandard.org/namespaces/basic/3.0/",
+];
+
+lazy_static::lazy_static! {
+    static ref DOI_RE: Regex = Regex::new(r#"(?i)\b(10\.\d{4,9}/[^\s"<>]+)"#).unwrap();
+    static ref ISBN_RE: Regex =
+        Regex::new(r"(?i)\bISBN(?:-1[03])?:?\s*([0-9][0-9\- ]{8,15}[0-9X])\b").unwrap();
+    static ref BARE_ISBN13_RE: Regex = Regex::new(r"\b(97[89][0-9\-]{10,14})\b").unwrap();
+    static ref ARXIV_RE: Regex = Regex::new(
+        r"(?i)(?:arxiv:\s*|arxiv\.org/(?:abs|pdf)/)((?:\d{4}\.\d{4,5}|[a-z\-]+(?:\.[a-z]{2})?/\d{7})(?:v\d+)?)"
+    )
+    .unwrap();
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DocumentIdentifiers {
+    pub dois: Vec<String>,
+    pub isbns: Vec<String>,
+    pub arxiv_ids: Vec<String>,
+}
+
+impl DocumentIdentifiers {
+    pub fn is_empty(&self) -> bool {
+        self.dois.is_empty() && self.isbns.is_empty() && self.arxiv_ids.is_empty()
+    }
+
+    fn scan(&mut self, text: &str) {
+        for doi in find_dois(text) {
+            push_unique(&mut self.dois, doi);
+        }
+        for isbn in find_isbns(text) {
+            push_unique(&mut self.isbns, isbn);
+        }
+        for arxiv_id in find_arxiv_ids(text) {
+            push_unique(&mut self.arxiv_ids, arxiv_id);
+        }
+    }
+}
+
+// Результат одного чтения файла: метаданные, идентификаторы и год публикации из XMP
+pub struct ScannedDocument {
+    pub metadata: PdfMetadata,
+    pub identifiers: DocumentIdentifiers,
+    pub year: Option<String>,
+}
+
+pub fn read_identifiers(path: &Path) -> Result<ScannedDocument> {
+    let (doc, is_encrypted) = load_pdf(path)?;
+    let metadata = document_metadata(&doc, is_encrypted);
+
+    let mut identifiers = DocumentIdentifiers::default();
+    let xmp = read_xmp_packet(&doc);
+
+    // Сначала метаданные: они надежнее текста страниц
+    if let Some(xmp) = &xmp {
+        for doi in xmp_dois(xmp) {
+            push_unique(&mut identifiers.dois, doi);
+        }
+    }
+    for field in [
+        &metadata.title,
+        &metadata.details.subject,
+        &metadata.details.keywords,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        identifiers.scan(field);
+    }
+
+    identifiers.scan(&scan_pages_text(&doc));
+
+    Ok(ScannedDocument {
+        metadata,
+        identifiers,
+        year: xmp.as_ref().and_then(xmp_year),
+    })
+}
+
+pub fn xmp_year(xmp: &XmpMeta) -> Option<String> {
+    PRISM_NAMESPACES
+        .iter()
+        .flat_map(|ns| [xmp.property(ns, "publicationDate"), xmp.property(ns, "coverDate")])
+        .flatten()
+        .map(|p| p.value)
+        .chain(xmp.property_array(xmp_ns::DC, "date").map(|p| p.value))
+        .find_map(|date| {
+            let year: String = date.trim().chars().take(4).collect();
+            Some(year).filter(|y| y.len() == 4 && y.chars().all(|c| c.is_ascii_digit()))
+        })
+}
+
+fn xmp_dois(xmp: &XmpMeta) -> Vec<String> {
+    let mut dois: Vec<String> = PRISM_NAMESPACES
+        .iter()
+        .filter_map(|ns| xmp.property(ns, "doi"))
+        .filter_map(|p| find_dois(&p.value).into_iter().next())
+        .collect();
+
+    // dc:identifier часто содержит "doi:10.xxx/yyy"
+    dois.extend(
+        xmp.property_array(xmp_ns::DC, "identifier")
+            .flat_map(|p| find_dois(&p.value)),
+    );
+    dois
+}
+
+fn scan_pages_text(doc: &Document) -> String {
+    let page_count = doc.get_pages().len() as u32;
+    let mut pages: Vec<u32> = (1..=page_count.min(FIRST_PAGES)).collect();
+    for page in page_count.saturating_sub(LAST_PAGES) + 1..=page_count {
+        if !pages.contains(&page) {
+            pages.push(page);
+        }
+    }
+
+    // Страницы по одной: битая страница не должна ломать поиск на остальных
+    pages
+        .into_iter()
+        .filter_map(|page| doc.extract_text(&[page]).ok())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn find_dois(text: &str) -> Vec<String> {
+    DOI_RE
+        .captures_iter(text)
+        .map(|c| trim_doi(&c[1]).to_string())
+        .filter(|doi| doi.contains('/') && !doi.ends_with('/'))
+        .collect()
+}
+
+// Убираем пунктуацию, прилипшую к DOI в конце предложения, и непарные скобки
+fn trim_doi(doi: &str) -> &str {
+    let mut doi = doi.trim_end_matches(['.', ',', ';', ':', '\'']);
+    while doi.ends_with(')') && doi.matches('(').count() < doi.matches(')').count() {
+        doi = doi[..doi.len() - 1].trim_end_matches(['.', ',', ';', ':']);
+    }
+    doi
+}
+
+pub fn find_isbns(text: &str) -> Vec<String> {
+    ISBN_RE
+        .captures_iter(text)
+        .chain(BARE_ISBN13_RE.captures_iter(text))
+        .filter_map(|c| leading_isbn(&c[1]))
+        .collect()
+}
+
+// Захват может прихватить соседние числа ("ISBN 0-306-40615-2 12"), поэтому ISBN ищется
+// среди начальных групп цифр длиной 13 или 10 знаков. Более длинный вариант проверяется
+// первым, но только с префиксом 978/979: иначе ISBN-10 с хвостом из трех цифр
+// примерно в каждом десятом случае сходился бы по контрольной сумме ISBN-13
+fn leading_isbn(raw: &str) -> Option<String> {
+    let mut candidates = Vec::new();
+    let mut digits = 0;
+    for (i, c) in raw.char_indices() {
+        if !c.is_ascii_alphanumeric() {
+            continue;
+        }
+        digits += 1;
+        let end = i + c.len_utf8();
+        let group_ends = raw[end..].starts_with(['-', ' ']) || end == raw.len();
+        let prefix_ok = digits == 10 || is_isbn13_prefix(&raw[..end]);
+        if group_ends && (digits == 10 || digits == 13) && prefix_ok {
+            candidates.push(&raw[..end]);
+        }
+        if digits >= 13 {
+            break;
+        }
+    }
+
+    candidates.into_iter().rev().find_map(normalize_isbn)
+}
+
+fn is_isbn13_prefix(raw: &str) -> bool {
+    let digits: String = raw.chars().filter(|c| c.is_ascii_digit()).take(3).collect();
+    digits == "978" || digits == "979"
+}
+
+// Возвращает ISBN без дефисов и пробелов, если контрольная сумма сходится
+pub fn normalize_isbn(raw: &str) -> Option<String> {
+    let isbn: String = raw
+        .chars()
+        .filter(|c| c.is_ascii_digit() || *c == 'X' || *c == 'x')
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+
+    let valid = match isbn.len() {
+        10 => is_valid_isbn10(&isbn),
+        13 => is_valid_isbn13(&isbn),
+        _ => false,
+    };
+
+    if valid {
+        Some(isbn)
+    } else {
+        None
+    }
+}
+
+fn is_valid_isbn10(isbn: &str) -> bool {
+    let mut sum = 0;
+    for (i, c) in isbn.chars().enumerate() {
+        let value = match c {
+            'X' if i == 9 => 10,
+            c => match c.to_digit(10) {
+                Some(d) => d,
+                None => return false,
+            },
+        };
+        sum += value * (10 - i as u32);
+    }
+    sum % 11 == 0
+}
+
+fn is_valid_isbn13(isbn: &str) -> bool {
+    let digits: Option<Vec<u32>> = isbn.chars().map(|c| c.to_digit(10)).collect();
+    let digits = match digits {
+        Some(digits) => digits,
+        None => return false,
+    };
+
+    let sum: u32 = digits
+        .iter()
+        .enumerate()
+        .map(|(i, d)| if i % 2 == 0 { *d } else { d * 3 })
+        .sum();
+    sum % 10 == 0
+}
+
+pub fn find_arxiv_ids(text: &str) -> Vec<String> {
+    ARXIV_RE
+        .captures_iter(text)
+        .map(|c| c[1].to_string())
+        .collect()
+}
+
+fn push_unique(list: &mut Vec<String>, value: String) {
+    if !list.iter().any(|v| v.eq_ignore_ascii_case(&value)) {
+        list.push(value);
+    }
+}
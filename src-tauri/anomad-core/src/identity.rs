@@ -0,0 +1,281 @@
+// Идентификатор документа, по которому файл находится после переименования или перемещения.
+// Способ хранения выбирается в настройках; только Xmp меняет сам PDF, и только на месте:
+// если в XMP-пакете нет запаса, файл не переписывается целиком
+
+use anyhow::{anyhow, Context};
+use rand::{distributions::Alphanumeric, Rng};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use xmp_toolkit::{OpenFileOptions, XmpFile, XmpMeta, XmpValue};
+
+use crate::error::Result;
+use super::fingerprint::file_hash;
+use super::pdf_metadata::{document_metadata, load_pdf, read_xmp_packet};
+use super::safe_write::update_pdf_in_place;
+
+// Собственное пространство имен, чтобы не занимать стандартные поля вроде xmp:CreatorTool
+pub const ANOMAD_NS: &str = "https://academynomad.app/ns/1.0/";
+const ANOMAD_PREFIX: &str = "anomad";
+const ID_PROPERTY: &str = "DocumentID";
+
+// Имя атрибута файловой системы (на Windows - альтернативного потока NTFS)
+#[cfg(unix)]
+const XATTR_NAME: &str = "user.anomad.id";
+#[cfg(windows)]
+const ADS_NAME: &str = "anomad.id";
+
+const SIDECAR_EXTENSION: &str = "anomad";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IdentityStrategy {
+    Xmp,
+    // По умолчанию файл рядом: PDF не меняется, а копируется и переносится sidecar вместе с ним
+    #[default]
+    Sidecar,
+    Xattr,
+    ContentHash,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SidecarData {
+    id: String,
+    file_name: String,
+}
+
+pub fn read_identity(path: &Path, strategy: IdentityStrategy) -> Result<Option<String>> {
+    match strategy {
+        IdentityStrategy::Xmp => read_xmp_id(path),
+        IdentityStrategy::Sidecar => read_sidecar_id(path),
+        IdentityStrategy::Xattr => read_xattr_id(path),
+        IdentityStrategy::ContentHash => content_hash_id(path).map(Some),
+    }
+}
+
+// Возвращает существующий идентификатор или записывает новый
+pub fn ensure_identity(path: &Path, strategy: IdentityStrategy) -> Result<String> {
+    if let Some(id) = read_identity(path, strategy)? {
+        return Ok(id);
+    }
+
+    let id = generate_id();
+    match strategy {
+        IdentityStrategy::Xmp => write_xmp_id(path, &id)?,
+        IdentityStrategy::Sidecar => write_sidecar_id(path, &id)?,
+        IdentityStrategy::Xattr => write_xattr_id(path, &id)?,
+        // Хеш нигде не хранится - read_identity уже вернул бы его или ошибку чтения
+        IdentityStrategy::ContentHash => return content_hash_id(path),
+    }
+    Ok(id)
+}
+
+// Удаляет идентификатор, записанный вне PDF. Xmp откатывается восстановлением копии файла
+pub fn remove_identity(path: &Path, strategy: IdentityStrategy) -> Result<()> {
+    match strategy {
+        IdentityStrategy::Sidecar => {
+            let sidecar = sidecar_path(path);
+            if sidecar.is_file() {
+                fs::remove_file(&sidecar)?;
+            }
+            Ok(())
+        }
+        IdentityStrategy::Xattr => remove_xattr_id(path),
+        IdentityStrategy::Xmp | IdentityStrategy::ContentHash => Ok(()),
+    }
+}
+
+fn generate_id() -> String {
+    let id: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(12)
+        .map(char::from)
+        .collect();
+    format!("id_{}", id)
+}
+
+pub fn register_namespace() -> Result<()> {
+    XmpMeta::register_namespace(ANOMAD_NS, ANOMAD_PREFIX)
+        .map(|_| ())
+        .map_err(|e| anyhow!("Failed to register XMP namespace: {}", e).into())
+}
+
+fn read_xmp_id(path: &Path) -> Result<Option<String>> {
+    register_namespace()?;
+
+    let (doc, is_encrypted) = load_pdf(path)?;
+    if let Some(id) = read_xmp_packet(&doc).and_then(|xmp| xmp.property(ANOMAD_NS, ID_PROPERTY)) {
+        return Ok(Some(id.value));
+    }
+
+    // Файлы, помеченные старыми версиями через CreatorTool / Creator
+    let creator = document_metadata(&doc, is_encrypted).creator;
+    Ok(creator.filter(|c| c.starts_with("id_")))
+}
+
+// XMP-пакет обновляется на месте средствами xmp_toolkit, если в нем достаточно места
+// (padding). Без места ошибка возвращается вызывающему коду: переписывать весь документ
+// ради идентификатора нельзя, вместо этого он перейдет на хеш содержимого
+fn write_xmp_id(path: &Path, id: &str) -> Result<()> {
+    register_namespace()?;
+
+    write_xmp_in_place(path, id).map_err(|e| {
+        anyhow::Error::from(e)
+            .context("XMP packet cannot be updated in place, the PDF was left unchanged")
+            .into()
+    })
+}
+
+fn write_xmp_in_place(path: &Path, id: &str) -> Result<()> {
+    update_pdf_in_place(path, |temp| {
+        let mut xmp_file = XmpFile::new()?;
+        xmp_file
+            .open_file(temp, OpenFileOptions::default().for_update().use_packet_scanning())
+            .with_context(|| format!("Failed to open file for XMP update: {}", temp.display()))?;
+
+        let mut xmp = match xmp_file.xmp() {
+            Some(xmp) => xmp,
+            None => {
+                xmp_file.close();
+                return Err(anyhow!("No XMP packet found").into());
+            }
+        };
+        xmp.set_property(ANOMAD_NS, ID_PROPERTY, &XmpValue::new(id.to_string()))?;
+
+        if !xmp_file.can_put_xmp(&xmp) {
+            xmp_file.close();
+            return Err(anyhow!("Not enough space in XMP packet").into());
+        }
+
+        xmp_file.put_xmp(&xmp)?;
+        xmp_file
+            .try_close()
+            .with_context(|| format!("Failed to save XMP: {}", temp.display()))?;
+
+        // Сканер пакетов мог обновить не тот пакет (например, XMP картинки) - проверяем
+        if read_xmp_id(temp)?.as_deref() == Some(id) {
+            Ok(())
+        } else {
+            Err(anyhow!("XMP packet of the document was not updated").into())
+        }
+    })
+}
+
+// book.pdf -> book.pdf.anomad
+pub fn sidecar_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(SIDECAR_EXTENSION);
+    path.with_file_name(name)
+}
+
+// Sidecar с идентификатором переезжает вместе с файлом
+pub fn move_sidecar(from: &Path, to: &Path) -> io::Result<()> {
+    let sidecar = sidecar_path(from);
+    if sidecar.is_file() {
+        fs::rename(&sidecar, sidecar_path(to))?;
+    }
+    Ok(())
+}
+
+// Копия файла получает тот же идентификатор: sidecar и атрибут не переносятся
+// обычным копированием (а XMP внутри PDF копируется вместе с файлом)
+pub fn copy_identity(from: &Path, to: &Path) -> Result<()> {
+    if let Some(id) = read_sidecar_id(from)? {
+        write_sidecar_id(to, &id)?;
+    }
+    // ФС назначения может не поддерживать атрибуты - это не повод отменять копирование
+    if let Ok(Some(id)) = read_xattr_id(from) {
+        if let Err(e) = write_xattr_id(to, &id) {
+            log::warn!("Атрибут с идентификатором не скопирован: {}", e);
+        }
+    }
+    Ok(())
+}
+
+fn read_sidecar_id(path: &Path) -> Result<Option<String>> {
+    let sidecar = sidecar_path(path);
+    if !sidecar.is_file() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&sidecar)?;
+    let data: SidecarData = serde_json::from_str(&content)
+        .with_context(|| format!("Invalid sidecar file: {}", sidecar.display()))?;
+    Ok(Some(data.id))
+}
+
+fn write_sidecar_id(path: &Path, id: &str) -> Result<()> {
+    let data = SidecarData {
+        id: id.to_string(),
+        file_name: path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned(),
+    };
+    fs::write(sidecar_path(path), serde_json::to_string_pretty(&data)?)?;
+    Ok(())
+}
+
+#[cfg(unix)]
+fn read_xattr_id(path: &Path) -> Result<Option<String>> {
+    let value = xattr::get(path, XATTR_NAME)
+        .with_context(|| format!("Failed to read xattr: {}", path.display()))?;
+    Ok(value.map(|v| String::from_utf8_lossy(&v).into_owned()))
+}
+
+#[cfg(unix)]
+fn write_xattr_id(path: &Path, id: &str) -> Result<()> {
+    xattr::set(path, XATTR_NAME, id.as_bytes())
+        .with_context(|| format!("Failed to write xattr: {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(unix)]
+fn remove_xattr_id(path: &Path) -> Result<()> {
+    if read_xattr_id(path)?.is_some() {
+        xattr::remove(path, XATTR_NAME)
+            .with_context(|| format!("Failed to remove xattr: {}", path.display()))?;
+    }
+    Ok(())
+}
+
+// На NTFS вместо xattr используем альтернативный поток данных: file.pdf:anomad.id
+#[cfg(windows)]
+fn ads_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(":");
+    name.push(ADS_NAME);
+    PathBuf::from(name)
+}
+
+#[cfg(windows)]
+fn read_xattr_id(path: &Path) -> Result<Option<String>> {
+    match fs::read_to_string(ads_path(path)) {
+        Ok(id) => Ok(Some(id.trim().to_string()).filter(|id| !id.is_empty())),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).context("Failed to read alternate data stream"),
+    }
+}
+
+#[cfg(windows)]
+fn write_xattr_id(path: &Path, id: &str) -> Result<()> {
+    fs::write(ads_path(path), id).context("Failed to write alternate data stream")
+}
+
+#[cfg(windows)]
+fn remove_xattr_id(path: &Path) -> Result<()> {
+    match fs::remove_file(ads_path(path)) {
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e).context("Failed to remove alternate data stream"),
+    }
+}
+
+// Хеш содержимого ничего не записывает, но меняется при любом изменении файла
+fn content_hash_id(path: &Path) -> Result<String> {
+    let hash = file_hash(path)?;
+    Ok(format!("sha_{}", &hash[..16]))
+}
@@ -0,0 +1,297 @@
+// Журнал изменяющих операций. Для каждой операции хранится то, что нужно для отмены:
+// старое имя, элемент корзины или копия файла до записи метаданных
+
+use anyhow::{anyhow, Context};
+use chrono::{Local, SecondsFormat};
+use rand::{distributions::Alphanumeric, Rng};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, ErrorKind, Result};
+use super::fingerprint::compute_fingerprint;
+use super::identity::{remove_identity, sidecar_path, IdentityStrategy};
+use super::organize::move_file;
+use super::safe_write::{write_pdf_with, PdfSnapshot, BACKUP_DIR};
+use super::trash::{move_to_trash, restore_from_trash};
+
+pub const JOURNAL_FILE: &str = "journal.json";
+// Старые записи вытесняются вместе с их резервными копиями
+const MAX_ENTRIES: usize = 100;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Operation {
+    Rename {
+        from: String,
+        to: String,
+    },
+    // Копия файла; отмена отправляет ее в корзину
+    Copy {
+        from: String,
+        to: String,
+    },
+    // trash_location - идентификатор элемента системной корзины
+    Delete {
+        path: String,
+        trash_location: String,
+    },
+    // Запись в PDF; content_hash - отпечаток файла сразу после записи
+    RestoreBackup {
+        path: String,
+        backup_path: String,
+        content_hash: String,
+    },
+    // Идентификатор, записанный рядом с файлом или в атрибут
+    RemoveIdentity {
+        path: String,
+        strategy: IdentityStrategy,
+    },
+}
+
+impl Operation {
+    // Файл, который затрагивает отмена операции
+    pub fn path(&self) -> &str {
+        match self {
+            Operation::Rename { to, .. } | Operation::Copy { to, .. } => to,
+            Operation::Delete { path, .. }
+            | Operation::RestoreBackup { path, .. }
+            | Operation::RemoveIdentity { path, .. } => path,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalOperation {
+    #[serde(flatten)]
+    pub operation: Operation,
+    #[serde(default)]
+    pub undone: bool,
+}
+
+// Пакетная операция - одна запись, отменяется целиком
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub id: String,
+    pub timestamp: String,
+    pub description: String,
+    pub operations: Vec<JournalOperation>,
+    pub undone: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct Journal {
+    file: PathBuf,
+    entries: Vec<JournalEntry>,
+}
+
+impl Journal {
+    // Поврежденный журнал не должен мешать запуску приложения
+    pub fn load(file: PathBuf) -> Self {
+        let entries = match fs::read_to_string(&file) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                log::warn!("Журнал операций поврежден и будет начат заново: {}", e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+        Journal { file, entries }
+    }
+
+    fn save(&self) -> Result<()> {
+        if let Some(dir) = self.file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.file, serde_json::to_string_pretty(&self.entries)?)
+            .with_context(|| format!("Failed to save journal: {}", self.file.display()))?;
+        Ok(())
+    }
+
+    // Пустые операции (ничего не изменилось) не записываются
+    pub fn record(&mut self, description: &str, operations: Vec<Operation>) -> Result<Option<String>> {
+        if operations.is_empty() {
+            return Ok(None);
+        }
+
+        let id = random_token(12);
+        self.entries.push(JournalEntry {
+            id: id.clone(),
+            timestamp: Local::now().to_rfc3339_opts(SecondsFormat::Secs, false),
+            description: description.to_string(),
+            operations: operations
+                .into_iter()
+                .map(|operation| JournalOperation {
+                    operation,
+                    undone: false,
+                })
+                .collect(),
+            undone: false,
+        });
+
+        if self.entries.len() > MAX_ENTRIES {
+            let evicted: Vec<JournalEntry> =
+                self.entries.drain(..self.entries.len() - MAX_ENTRIES).collect();
+            evicted.iter().for_each(discard_backups);
+        }
+
+        self.save()?;
+        Ok(Some(id))
+    }
+
+    // Новые записи первыми
+    pub fn recent(&self, limit: usize) -> Vec<JournalEntry> {
+        self.entries.iter().rev().take(limit).cloned().collect()
+    }
+
+    // Операции откатываются в обратном порядке. Удачно отмененные помечаются сразу,
+    // поэтому повторная отмена после ошибки продолжит с того же места.
+    // Возвращает неудавшиеся операции: путь файла и ошибку
+    pub fn undo(&mut self, id: &str) -> Result<Vec<(String, Error)>> {
+        let entry = self
+            .entries
+            .iter_mut()
+            .find(|e| e.id == id)
+            .ok_or_else(|| {
+                Error::new(ErrorKind::NotFound, format!("Operation {} not found", id))
+            })?;
+        if entry.undone {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Operation {} is already undone", id),
+            ));
+        }
+
+        let mut errors = Vec::new();
+        for item in entry.operations.iter_mut().rev().filter(|o| !o.undone) {
+            match undo_operation(&item.operation) {
+                Ok(_) => item.undone = true,
+                Err(e) => errors.push((item.operation.path().to_string(), e)),
+            }
+        }
+        entry.undone = errors.is_empty();
+
+        self.save()?;
+        Ok(errors)
+    }
+}
+
+fn undo_operation(operation: &Operation) -> Result<()> {
+    match operation {
+        Operation::Rename { from, to } => {
+            let (from, to) = (Path::new(from), Path::new(to));
+            if !to.exists() {
+                return Err(Error::new(
+                    ErrorKind::NotFound,
+                    format!("File not found: {}", to.display()),
+                ));
+            }
+            move_file(to, from)
+        }
+        Operation::Copy { to, .. } => {
+            let to = Path::new(to);
+            let sidecar = sidecar_path(to);
+            move_to_trash(to)?;
+            if sidecar.is_file() {
+                move_to_trash(&sidecar)?;
+            }
+            Ok(())
+        }
+        Operation::Delete {
+            path,
+            trash_location,
+        } => restore_from_trash(trash_location, Path::new(path)),
+        Operation::RestoreBackup {
+            path,
+            backup_path,
+            content_hash,
+        } => {
+            let (path, backup_path) = (Path::new(path), Path::new(backup_path));
+            if !backup_path.is_file() {
+                return Err(Error::new(
+                    ErrorKind::NotFound,
+                    format!("Backup not found: {}", backup_path.display()),
+                ));
+            }
+
+            // Файл менялся после записи: восстановление копии потеряет эти изменения
+            let actual = compute_fingerprint(path)?;
+            if &actual.content_hash != content_hash {
+                return Err(anyhow!(
+                    "File was modified after the operation: {}",
+                    path.display()
+                )
+                .into());
+            }
+
+            let expected = PdfSnapshot::of_file(backup_path)?;
+            write_pdf_with(path, expected, |temp| {
+                fs::copy(backup_path, temp).context("Failed to copy backup")?;
+                Ok(())
+            })?;
+            let _ = fs::remove_file(backup_path);
+            Ok(())
+        }
+        Operation::RemoveIdentity { path, strategy } => remove_identity(Path::new(path), *strategy),
+    }
+}
+
+fn discard_backups(entry: &JournalEntry) {
+    for item in &entry.operations {
+        if let Operation::RestoreBackup { backup_path, .. } = &item.operation {
+            let _ = fs::remove_file(backup_path);
+        }
+    }
+}
+
+fn random_token(len: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(len)
+        .map(char::from)
+        .collect()
+}
+
+// Копия файла до записи, которая станет операцией журнала, если запись удалась
+pub struct UndoBackup {
+    path: PathBuf,
+    backup_path: PathBuf,
+}
+
+impl UndoBackup {
+    // book.pdf -> .anomad-backups/book.pdf.<token>.undo рядом с ротацией safe_write
+    pub fn create(path: &Path) -> Result<Self> {
+        let dir = path.parent().context("File has no parent directory")?;
+        let name = path.file_name().context("Invalid file name")?.to_string_lossy();
+        let backup_dir = dir.join(BACKUP_DIR);
+        fs::create_dir_all(&backup_dir).context("Failed to create backup directory")?;
+
+        let backup_path = backup_dir.join(format!("{}.{}.undo", name, random_token(8)));
+        fs::copy(path, &backup_path).context("Failed to create backup")?;
+
+        Ok(UndoBackup {
+            path: path.to_path_buf(),
+            backup_path,
+        })
+    }
+
+    pub fn into_operation(self) -> Result<Operation> {
+        let content_hash = match compute_fingerprint(&self.path) {
+            Ok(fingerprint) => fingerprint.content_hash,
+            Err(e) => {
+                self.discard();
+                return Err(e);
+            }
+        };
+
+        Ok(Operation::RestoreBackup {
+            path: self.path.to_string_lossy().into_owned(),
+            backup_path: self.backup_path.to_string_lossy().into_owned(),
+            content_hash,
+        })
+    }
+
+    // Запись не удалась или ничего не изменила
+    pub fn discard(self) {
+        let _ = fs::remove_file(&self.backup_path);
+    }
+}
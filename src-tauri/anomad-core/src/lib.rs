@@ -0,0 +1,48 @@
+// Core library of AcademyNomad: search, highlight extraction, metadata and file identity.
+// Tauri commands and the anomad-cli binary are thin adapters over it
+
+pub mod error;
+pub mod get_file_info;
+pub mod get_file_highlights;
+pub mod get_file_info_all_meta;
+pub mod filetime_to_datetime;
+pub mod identity;
+pub mod file_info;
+// Everything есть только в Windows; на других системах поиск недоступен
+#[cfg(windows)]
+pub mod everything_search;
+#[cfg(not(windows))]
+#[path = "everything_unavailable.rs"]
+pub mod everything_search;
+pub mod annotations;
+pub mod xfdf;
+pub mod pdf_date;
+pub mod pdf_metadata;
+pub mod identifiers;
+pub mod bibtex;
+pub mod file_lock;
+pub mod safe_write;
+pub mod fingerprint;
+pub mod metadata_edit;
+pub mod filename_patterns;
+pub mod rename_template;
+pub mod journal;
+pub mod trash;
+pub mod organize;
+pub mod duplicates;
+pub mod annotation_merge;
+pub mod text_layer;
+pub mod pdfium;
+pub mod highlight_transfer;
+pub mod watch_events;
+pub mod highlight_diff;
+pub mod watch_config;
+pub mod annotation_history;
+pub mod markdown;
+
+pub use error::{Error, ErrorKind, Result};
+pub use everything_search::everything_search;
+pub use file_info::{FileInfo, FileResult, SearchParams};
+pub use get_file_highlights::{get_file_highlights, Highlight};
+pub use get_file_info_all_meta::get_file_info_all_meta;
+pub use identity::{ensure_identity, read_identity, IdentityStrategy};
@@ -0,0 +1,52 @@
+// Пометки документа в Markdown: заголовок из метаданных, затем пометки по страницам
+
+use super::bibtex::BibSource;
+use super::get_file_highlights::Highlight;
+
+// Подпись выделения, текст которого не извлечен
+pub const PLAIN_HIGHLIGHT: &str = "(выделение)";
+
+pub fn format_document(source: &BibSource, highlights: &[Highlight]) -> String {
+    let mut out = format!(
+        "## {}\n\n",
+        source.title.as_deref().unwrap_or("Без названия")
+    );
+
+    let byline: Vec<&str> = [source.author.as_deref(), source.year.as_deref()]
+        .into_iter()
+        .flatten()
+        .collect();
+    if !byline.is_empty() {
+        out.push_str(&format!("*{}*\n\n", byline.join(", ")));
+    }
+    out.push_str(&format!("`{}`\n\n", source.file_path));
+
+    for highlight in highlights {
+        let text = single_line(&highlight.highlighted_text);
+        let note = highlight
+            .annotation_text
+            .as_deref()
+            .map(single_line)
+            .filter(|note| !note.is_empty());
+
+        // Заметка без выделенного текста (стикер) выводится сама по себе. Выделение без текста
+        // и без заметки тоже выводится: по странице его можно найти в книге
+        match (text.is_empty(), note) {
+            (false, Some(note)) => out.push_str(&format!(
+                "- с. {}: {}\n  - {}\n",
+                highlight.page, text, note
+            )),
+            (false, None) => out.push_str(&format!("- с. {}: {}\n", highlight.page, text)),
+            (true, Some(note)) => out.push_str(&format!("- с. {}: {}\n", highlight.page, note)),
+            (true, None) => {
+                out.push_str(&format!("- с. {}: {}\n", highlight.page, PLAIN_HIGHLIGHT))
+            }
+        }
+    }
+    out
+}
+
+// Переносы строк внутри пометки разорвали бы элемент списка
+fn single_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
@@ -0,0 +1,276 @@
+// Правка метаданных PDF: одни и те же значения пишутся и в /Info, и в XMP,
+// чтобы Everything и другие программы видели одинаковое
+
+use anyhow::Context;
+use chrono::{DateTime, FixedOffset, Local, SecondsFormat};
+use lopdf::{Dictionary, Document, Object};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use xmp_toolkit::{xmp_ns, XmpMeta, XmpValue};
+
+use crate::error::{Error, ErrorKind, Result};
+use super::annotations::encode_pdf_string;
+use super::bibtex::split_authors;
+use super::get_file_highlights::decode_pdf_string;
+use super::identity::{register_namespace, ANOMAD_NS};
+use super::pdf_date::format_pdf_date;
+use super::pdf_metadata::{document_metadata, read_xmp_packet, set_xmp_packet};
+
+// Ключи /Info, которые нельзя занимать пользовательскими полями
+const RESERVED_INFO_KEYS: [&str; 9] = [
+    "Title",
+    "Author",
+    "Subject",
+    "Keywords",
+    "Creator",
+    "Producer",
+    "CreationDate",
+    "ModDate",
+    "Trapped",
+];
+
+// Имена стандартных полей в MetadataChanges и FieldChange
+const STANDARD_FIELDS: [&str; 4] = ["title", "authors", "subject", "keywords"];
+
+// None - поле не трогаем, пустая строка (или пустой список) - удаляем поле
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MetadataChanges {
+    pub title: Option<String>,
+    pub authors: Option<Vec<String>>,
+    pub subject: Option<String>,
+    pub keywords: Option<Vec<String>>,
+    #[serde(default)]
+    pub custom: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+    pub changed: bool,
+}
+
+impl MetadataChanges {
+    pub fn validate(&self) -> Result<()> {
+        for name in self.custom.keys() {
+            if !is_valid_field_name(name) {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Invalid custom field name '{}'", name),
+                ));
+            }
+            // Регистр не важен: "title" попал бы в разбор стандартных полей,
+            // а "TITLE" в XMP выглядел бы как второе заглавие
+            let reserved = RESERVED_INFO_KEYS
+                .iter()
+                .chain(&STANDARD_FIELDS)
+                .any(|key| key.eq_ignore_ascii_case(name));
+            if reserved {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Field '{}' is a standard field", name),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn new_values(&self) -> Vec<(String, Option<String>)> {
+        let mut values = Vec::new();
+
+        if let Some(title) = &self.title {
+            values.push(("title".to_string(), non_empty(title)));
+        }
+        if let Some(authors) = &self.authors {
+            values.push(("authors".to_string(), join_list(authors, "; ")));
+        }
+        if let Some(subject) = &self.subject {
+            values.push(("subject".to_string(), non_empty(subject)));
+        }
+        if let Some(keywords) = &self.keywords {
+            values.push(("keywords".to_string(), join_list(keywords, ", ")));
+        }
+        for (name, value) in &self.custom {
+            values.push((name.clone(), non_empty(value)));
+        }
+
+        values
+    }
+}
+
+// Имя должно годиться и для ключа /Info, и для имени XML-элемента в XMP
+fn is_valid_field_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    Some(value.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+fn join_list(items: &[String], separator: &str) -> Option<String> {
+    let items: Vec<&str> = items
+        .iter()
+        .map(|i| i.trim())
+        .filter(|i| !i.is_empty())
+        .collect();
+    Some(items.join(separator)).filter(|v| !v.is_empty())
+}
+
+// Старые и новые значения каждого затронутого поля; документ не меняется
+pub fn preview_changes(doc: &Document, changes: &MetadataChanges) -> Vec<FieldChange> {
+    let metadata = document_metadata(doc, doc.is_encrypted());
+    let xmp = read_xmp_packet(doc);
+
+    changes
+        .new_values()
+        .into_iter()
+        .map(|(field, new)| {
+            let old = match field.as_str() {
+                "title" => metadata.title.clone(),
+                "authors" => metadata
+                    .author
+                    .as_deref()
+                    .and_then(|a| join_list(&split_authors(a), "; ")),
+                "subject" => metadata.details.subject.clone(),
+                "keywords" => metadata.details.keywords.clone(),
+                name => custom_field(doc, xmp.as_ref(), name),
+            };
+
+            FieldChange {
+                changed: old != new,
+                field,
+                old,
+                new,
+            }
+        })
+        .collect()
+}
+
+fn custom_field(doc: &Document, xmp: Option<&XmpMeta>, name: &str) -> Option<String> {
+    info_dict(doc)
+        .and_then(|info| info.get(name.as_bytes()).ok())
+        .and_then(decode_pdf_string)
+        .or_else(|| xmp.and_then(|x| x.property(ANOMAD_NS, name)).map(|p| p.value))
+        .and_then(|v| non_empty(&v))
+}
+
+fn info_dict(doc: &Document) -> Option<&Dictionary> {
+    match doc.trailer.get(b"Info").ok()? {
+        Object::Reference(id) => doc.get_dictionary(*id).ok(),
+        Object::Dictionary(dict) => Some(dict),
+        _ => None,
+    }
+}
+
+// Вносит изменения в документ в памяти; сохранение - через safe_write::save_pdf
+pub fn apply_changes(doc: &mut Document, changes: &MetadataChanges) -> Result<()> {
+    changes.validate()?;
+
+    let now: DateTime<FixedOffset> = Local::now().into();
+    let pdf_now = format_pdf_date(&now);
+    let xmp_now = now.to_rfc3339_opts(SecondsFormat::Secs, false);
+
+    // /Info
+    {
+        let info = info_dict_mut(doc)?;
+        let mut set = |key: &str, value: Option<String>| match value {
+            Some(value) => info.set(key, encode_pdf_string(&value)),
+            None => {
+                info.remove(key.as_bytes());
+            }
+        };
+
+        for (field, value) in changes.new_values() {
+            match field.as_str() {
+                "title" => set("Title", value),
+                "authors" => set("Author", value),
+                "subject" => set("Subject", value),
+                "keywords" => set("Keywords", value),
+                name => set(name, value),
+            }
+        }
+        set("ModDate", Some(pdf_now));
+    }
+
+    // XMP
+    register_namespace()?;
+    let mut xmp = match read_xmp_packet(doc) {
+        Some(xmp) => xmp,
+        None => XmpMeta::new()?,
+    };
+
+    if let Some(title) = &changes.title {
+        set_lang_alt(&mut xmp, "title", non_empty(title))?;
+    }
+    if let Some(subject) = &changes.subject {
+        set_lang_alt(&mut xmp, "description", non_empty(subject))?;
+    }
+    if let Some(authors) = &changes.authors {
+        // dc:creator - упорядоченный список (rdf:Seq), порядок авторов важен
+        set_array(&mut xmp, "creator", authors, true)?;
+    }
+    if let Some(keywords) = &changes.keywords {
+        set_text(&mut xmp, xmp_ns::PDF, "Keywords", join_list(keywords, ", "))?;
+        set_array(&mut xmp, "subject", keywords, false)?;
+    }
+    for (name, value) in &changes.custom {
+        set_text(&mut xmp, ANOMAD_NS, name, non_empty(value))?;
+    }
+    xmp.set_property(xmp_ns::XMP, "ModifyDate", &XmpValue::new(xmp_now.clone()))?;
+    xmp.set_property(xmp_ns::XMP, "MetadataDate", &XmpValue::new(xmp_now))?;
+
+    set_xmp_packet(doc, &xmp)
+}
+
+fn info_dict_mut(doc: &mut Document) -> Result<&mut Dictionary> {
+    let info_id = match doc.trailer.get(b"Info") {
+        Ok(Object::Reference(id)) => *id,
+        Ok(Object::Dictionary(dict)) => {
+            // Встроенный словарь выносим в отдельный объект, чтобы менять единообразно
+            let id = doc.add_object(dict.clone());
+            doc.trailer.set("Info", Object::Reference(id));
+            id
+        }
+        _ => {
+            let id = doc.add_object(Dictionary::new());
+            doc.trailer.set("Info", Object::Reference(id));
+            id
+        }
+    };
+
+    Ok(doc
+        .get_dictionary_mut(info_id)
+        .context("Info dictionary is not a dictionary")?)
+}
+
+fn set_text(xmp: &mut XmpMeta, ns: &str, name: &str, value: Option<String>) -> Result<()> {
+    match value {
+        Some(value) => xmp.set_property(ns, name, &XmpValue::new(value))?,
+        None => xmp.delete_property(ns, name)?,
+    }
+    Ok(())
+}
+
+fn set_lang_alt(xmp: &mut XmpMeta, name: &str, value: Option<String>) -> Result<()> {
+    // Удаляем целиком, иначе останутся переводы на других языках со старым значением
+    xmp.delete_property(xmp_ns::DC, name)?;
+    if let Some(value) = value {
+        xmp.set_localized_text(xmp_ns::DC, name, None, "x-default", &value)?;
+    }
+    Ok(())
+}
+
+fn set_array(xmp: &mut XmpMeta, name: &str, items: &[String], ordered: bool) -> Result<()> {
+    xmp.delete_property(xmp_ns::DC, name)?;
+
+    let array = XmpValue::new(name.to_string())
+        .set_is_array(true)
+        .set_is_ordered(ordered);
+    for item in items.iter().filter_map(|i| non_empty(i)) {
+        xmp.append_array_item(xmp_ns::DC, &array, &XmpValue::new(item))?;
+    }
+    Ok(())
+}
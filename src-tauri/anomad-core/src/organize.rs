@@ -0,0 +1,224 @@
+// Раскладка файлов по папкам библиотеки: путь папки строится по шаблону
+// вида "{project}/{author_last}/{year}", имя файла сохраняется
+
+use anyhow::{anyhow, Context};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, ErrorKind, Result};
+use super::fingerprint::compute_fingerprint;
+use super::identity::{copy_identity, move_sidecar, sidecar_path};
+use super::filename_patterns::FilenamePattern;
+use super::rename_template::{
+    collision_key, fit_file_name, missing_fields, rename_fields, render_template,
+    sanitize_file_name, PlanReason,
+};
+
+// Сколько вариантов "name (N)" пробуем, прежде чем сдаться
+const MAX_SUFFIX: usize = 1000;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferMode {
+    #[default]
+    Move,
+    Copy,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrganizeFile {
+    pub path: String,
+    // Имя списка (проекта) для поля {project}
+    pub project: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrganizeStatus {
+    Ready,
+    // Файл уже лежит там, куда его положил бы шаблон
+    Unchanged,
+    Invalid,
+    Moved,
+    Copied,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrganizeItem {
+    pub source: String,
+    pub target: Option<String>,
+    pub status: OrganizeStatus,
+    pub reason: Option<PlanReason>,
+    // Заполняет приложение: описание reason или ошибки переноса
+    pub message: Option<String>,
+}
+
+// Каждый уровень шаблона заполняется и очищается отдельно, пустые уровни пропускаются
+pub fn render_folder(template: &str, fields: &BTreeMap<String, String>) -> Result<PathBuf> {
+    let mut folder = PathBuf::new();
+    for segment in template.split(['/', '\\']) {
+        let name = sanitize_file_name(&render_template(segment, fields)?);
+        if !name.is_empty() {
+            folder.push(name);
+        }
+    }
+    Ok(folder)
+}
+
+// Свободное имя в папке: не занято на диске и другими файлами того же пакета
+pub fn unique_target(dir: &Path, source: &Path, taken: &mut HashSet<String>) -> Result<PathBuf> {
+    let stem = source
+        .file_stem()
+        .context("Invalid file name")?
+        .to_string_lossy();
+    let extension = source.extension().map(|e| e.to_string_lossy());
+
+    for index in 1..=MAX_SUFFIX {
+        let candidate_stem = if index == 1 {
+            stem.to_string()
+        } else {
+            format!("{} ({})", stem, index)
+        };
+        let candidate = fit_file_name(dir, &candidate_stem, extension.as_deref())?;
+
+        let key = collision_key(&candidate);
+        let is_source = key == collision_key(source);
+        if (is_source || !candidate.exists()) && !taken.contains(&key) {
+            taken.insert(key);
+            return Ok(candidate);
+        }
+    }
+
+    Err(anyhow!("No free file name for {} in {}", stem, dir.display()).into())
+}
+
+// Куда шаблон кладет файл внутри root. taken - имена, уже занятые файлами того же пакета
+pub fn plan_organize(
+    file: &OrganizeFile,
+    root: &Path,
+    template: &str,
+    patterns: &[FilenamePattern],
+    taken: &mut HashSet<String>,
+) -> OrganizeItem {
+    let item = |target: Option<&Path>, status, reason| OrganizeItem {
+        source: file.path.clone(),
+        target: target.map(|t| t.to_string_lossy().into_owned()),
+        status,
+        reason,
+        message: None,
+    };
+
+    let source = Path::new(&file.path);
+    if !source.is_file() {
+        return item(None, OrganizeStatus::Invalid, Some(PlanReason::FileNotFound));
+    }
+
+    let mut fields = rename_fields(source, patterns);
+    if let Some(project) = file.project.as_ref().filter(|p| !p.trim().is_empty()) {
+        fields.insert("project".to_string(), project.trim().to_string());
+    }
+
+    let dir = match render_folder(template, &fields) {
+        Ok(folder) => root.join(folder),
+        Err(_) => {
+            let fields = missing_fields(template, &fields);
+            return item(
+                None,
+                OrganizeStatus::Invalid,
+                Some(PlanReason::MissingFields { fields }),
+            );
+        }
+    };
+
+    // Файл уже на своем месте - не трогаем и не занимаем другим файлам его имя
+    if source.parent().map(collision_key) == Some(collision_key(&dir)) {
+        taken.insert(collision_key(source));
+        return item(Some(source), OrganizeStatus::Unchanged, None);
+    }
+
+    match unique_target(&dir, source, taken) {
+        Ok(target) => item(Some(&target), OrganizeStatus::Ready, None),
+        Err(_) => item(None, OrganizeStatus::Invalid, Some(PlanReason::InvalidName)),
+    }
+}
+
+// Переименование в пределах тома; между томами - копия, проверка и удаление оригинала
+pub fn move_file(from: &Path, to: &Path) -> Result<()> {
+    if to.exists() && collision_key(from) != collision_key(to) {
+        return Err(Error::new(
+            ErrorKind::AlreadyExists,
+            format!("File already exists: {}", to.display()),
+        ));
+    }
+    if let Some(dir) = to.parent() {
+        fs::create_dir_all(dir).context("Failed to create directory")?;
+    }
+
+    match fs::rename(from, to) {
+        Ok(_) => {
+            let _ = move_sidecar(from, to);
+            return Ok(());
+        }
+        // Занятый или защищенный файл копией не перенести - сообщаем исходную ошибку
+        Err(e) if !is_cross_device(&e) => {
+            return Err(anyhow::Error::new(e)
+                .context(format!("Failed to move {}", from.display()))
+                .into())
+        }
+        Err(_) => {}
+    }
+
+    copy_file(from, to)?;
+    if let Err(e) = fs::remove_file(from) {
+        // Иначе файл остался бы в двух местах, а журнал записал бы только перенос
+        let _ = fs::remove_file(to);
+        let _ = fs::remove_file(sidecar_path(to));
+        return Err(anyhow::Error::new(e)
+            .context(format!("Failed to remove {}", from.display()))
+            .into());
+    }
+    let sidecar = sidecar_path(from);
+    if sidecar.is_file() {
+        let _ = fs::remove_file(sidecar);
+    }
+    Ok(())
+}
+
+// Перенос на другой том: EXDEV в Unix, ERROR_NOT_SAME_DEVICE в Windows
+fn is_cross_device(error: &io::Error) -> bool {
+    let code = if cfg!(windows) { 17 } else { 18 };
+    error.raw_os_error() == Some(code)
+}
+
+// Копия сверяется с оригиналом по отпечатку и получает тот же идентификатор документа
+pub fn copy_file(from: &Path, to: &Path) -> Result<()> {
+    if to.exists() {
+        return Err(Error::new(
+            ErrorKind::AlreadyExists,
+            format!("File already exists: {}", to.display()),
+        ));
+    }
+    if let Some(dir) = to.parent() {
+        fs::create_dir_all(dir).context("Failed to create directory")?;
+    }
+
+    let result = fs::copy(from, to)
+        .with_context(|| format!("Failed to copy {}", from.display()))
+        .map_err(Error::from)
+        .and_then(|_| {
+            if compute_fingerprint(from)? != compute_fingerprint(to)? {
+                return Err(anyhow!("Copy differs from original: {}", to.display()).into());
+            }
+            copy_identity(from, to)
+        });
+
+    if result.is_err() {
+        let _ = fs::remove_file(to);
+        let _ = fs::remove_file(sidecar_path(to));
+    }
+    result
+}
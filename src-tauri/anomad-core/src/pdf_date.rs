@@ -0,0 +1,77 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeZone};
+
+// Разбор даты PDF вида D:YYYYMMDDHHmmSSOHH'mm'. Все части после года необязательны
+pub fn parse_pdf_date(value: &str) -> Option<DateTime<FixedOffset>> {
+    let value = value.trim();
+    let value = value.strip_prefix("D:").unwrap_or(value);
+
+    let digits: String = value.chars().take_while(|c| c.is_ascii_digit()).collect();
+    if digits.len() < 4 {
+        return None;
+    }
+
+    let part = |start: usize, len: usize, default: u32| -> u32 {
+        digits
+            .get(start..start + len)
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(default)
+    };
+
+    let year = digits[0..4].parse::<i32>().ok()?;
+    let date = NaiveDate::from_ymd_opt(year, part(4, 2, 1), part(6, 2, 1))?;
+    let naive = date.and_hms_opt(part(8, 2, 0), part(10, 2, 0), part(12, 2, 0))?;
+
+    let offset = parse_offset(&value[digits.len()..]).unwrap_or(FixedOffset::east_opt(0)?);
+    offset.from_local_datetime(&naive).single()
+}
+
+// Дата XMP в формате ISO 8601; секунды и часовой пояс могут отсутствовать
+pub fn parse_xmp_date(value: &str) -> Option<DateTime<FixedOffset>> {
+    let value = value.trim();
+
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Some(date);
+    }
+
+    let utc = FixedOffset::east_opt(0)?;
+    ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"]
+        .iter()
+        .find_map(|format| chrono::NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+        })
+        .and_then(|naive| utc.from_local_datetime(&naive).single())
+}
+
+// Обратное преобразование: D:YYYYMMDDHHmmSS+HH'mm'
+pub fn format_pdf_date(date: &DateTime<FixedOffset>) -> String {
+    let offset = date.offset().local_minus_utc();
+    let sign = if offset < 0 { '-' } else { '+' };
+    let minutes = offset.abs() / 60;
+
+    format!(
+        "D:{}{}{:02}'{:02}'",
+        date.format("%Y%m%d%H%M%S"),
+        sign,
+        minutes / 60,
+        minutes % 60
+    )
+}
+
+fn parse_offset(rest: &str) -> Option<FixedOffset> {
+    let mut chars = rest.chars();
+    let sign = match chars.next()? {
+        'Z' | 'z' => return FixedOffset::east_opt(0),
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+
+    let digits: String = chars.filter(|c| c.is_ascii_digit()).collect();
+    let hours: i32 = digits.get(0..2)?.parse().ok()?;
+    let minutes: i32 = digits.get(2..4).and_then(|s| s.parse().ok()).unwrap_or(0);
+
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
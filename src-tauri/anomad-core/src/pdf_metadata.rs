@@ -0,0 +1,277 @@
+// Метаданные PDF: словарь /Info и XMP-пакет каталога, сведенные в одну структуру
+
+use anyhow::Context;
+use chrono::{DateTime, FixedOffset};
+use lopdf::{dictionary, encryption, Dictionary, Document, Object, ObjectId, Stream};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use xmp_toolkit::{xmp_ns, ToStringOptions, XmpMeta};
+
+use crate::error::Result;
+use super::get_file_highlights::decode_pdf_string;
+use super::pdf_date::{parse_pdf_date, parse_xmp_date};
+
+// Сколько первых страниц проверяем на наличие текстового слоя
+const TEXT_LAYER_PAGES: usize = 5;
+
+#[derive(Debug, Clone, Default)]
+pub struct PdfMetadata {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub creator: Option<String>,
+    pub details: PdfDetails,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PdfDetails {
+    pub subject: Option<String>,
+    pub keywords: Option<String>,
+    pub producer: Option<String>,
+    pub pdf_version: String,
+    pub page_count: u32,
+    pub is_encrypted: bool,
+    pub has_text_layer: bool,
+    pub is_tagged: bool,
+}
+
+// Один и тот же набор полей, прочитанный из /Info или из XMP
+#[derive(Debug, Default)]
+struct MetadataSource {
+    title: Option<String>,
+    author: Option<String>,
+    subject: Option<String>,
+    keywords: Option<String>,
+    creator: Option<String>,
+    producer: Option<String>,
+    modified: Option<DateTime<FixedOffset>>,
+}
+
+pub fn read_pdf_metadata(path: &Path) -> Result<PdfMetadata> {
+    let (doc, is_encrypted) = load_pdf(path)?;
+    Ok(document_metadata(&doc, is_encrypted))
+}
+
+// Загружает PDF и снимает шифрование, если документ открывается пустым паролем.
+// Второе значение - был ли документ зашифрован
+pub fn load_pdf(path: &Path) -> Result<(Document, bool)> {
+    let mut doc = Document::load(path).context("Failed to load PDF file")?;
+
+    let is_encrypted = doc.is_encrypted();
+    if is_encrypted {
+        // Большинство "зашифрованных" книг защищены только от печати/копирования
+        // и открываются пустым паролем пользователя
+        decrypt_with_empty_password(&mut doc);
+    }
+
+    Ok((doc, is_encrypted))
+}
+
+// lopdf расшифровывает потоки, строки-объекты и словарь /Info, но не строки внутри
+// остальных словарей и массивов (/Contents и /M аннотаций) - их расшифровываем сами
+fn decrypt_with_empty_password(doc: &mut Document) {
+    let key = match encryption::get_encryption_key(doc, "", true) {
+        Ok(key) => key,
+        Err(_) => return,
+    };
+    let reference = |key: &[u8]| doc.trailer.get(key).and_then(Object::as_reference).ok();
+    let skipped = [reference(b"Encrypt"), reference(b"Info")];
+
+    if doc.decrypt("").is_err() {
+        return;
+    }
+    for (&id, object) in doc.objects.iter_mut() {
+        if !skipped.contains(&Some(id)) {
+            decrypt_nested_strings(&key, id, object);
+        }
+    }
+}
+
+fn decrypt_nested_strings(key: &[u8], id: ObjectId, object: &mut Object) {
+    let children: Vec<&mut Object> = match object {
+        Object::Dictionary(dict) => dict.iter_mut().map(|(_, value)| value).collect(),
+        Object::Array(items) => items.iter_mut().collect(),
+        _ => return,
+    };
+
+    for child in children {
+        match child {
+            Object::String(bytes, format) => {
+                let encrypted = Object::String(bytes.clone(), *format);
+                if let Ok(decrypted) = encryption::decrypt_object(key, id, &encrypted) {
+                    *bytes = decrypted;
+                }
+            }
+            _ => decrypt_nested_strings(key, id, child),
+        }
+    }
+}
+
+pub fn document_metadata(doc: &Document, is_encrypted: bool) -> PdfMetadata {
+    let info = read_info(doc).unwrap_or_default();
+    let xmp = read_xmp_packet(doc)
+        .map(|xmp| xmp_source(&xmp))
+        .unwrap_or_default();
+
+    // Предпочитаем более свежий источник, недостающие поля берем из другого
+    let info_is_newer = match (info.modified, xmp.modified) {
+        (Some(info_date), Some(xmp_date)) => info_date > xmp_date,
+        (Some(_), None) => true,
+        _ => false,
+    };
+    let (primary, secondary) = if info_is_newer { (info, xmp) } else { (xmp, info) };
+
+    let details = PdfDetails {
+        subject: primary.subject.or(secondary.subject),
+        keywords: primary.keywords.or(secondary.keywords),
+        producer: primary.producer.or(secondary.producer),
+        pdf_version: doc.version.clone(),
+        page_count: doc.get_pages().len() as u32,
+        is_encrypted,
+        has_text_layer: has_text_layer(doc),
+        is_tagged: is_tagged(doc),
+    };
+
+    PdfMetadata {
+        title: primary.title.or(secondary.title),
+        author: primary.author.or(secondary.author),
+        creator: primary.creator.or(secondary.creator),
+        details,
+    }
+}
+
+fn read_info(doc: &Document) -> Option<MetadataSource> {
+    let info = match doc.trailer.get(b"Info").ok()? {
+        Object::Reference(id) => doc.get_dictionary(*id).ok()?,
+        Object::Dictionary(dict) => dict,
+        _ => return None,
+    };
+
+    let field = |key: &[u8]| info_string(info, key);
+
+    Some(MetadataSource {
+        title: field(b"Title"),
+        author: field(b"Author"),
+        subject: field(b"Subject"),
+        keywords: field(b"Keywords"),
+        creator: field(b"Creator"),
+        producer: field(b"Producer"),
+        modified: field(b"ModDate")
+            .or_else(|| field(b"CreationDate"))
+            .and_then(|d| parse_pdf_date(&d)),
+    })
+}
+
+fn info_string(info: &Dictionary, key: &[u8]) -> Option<String> {
+    info.get(key)
+        .ok()
+        .and_then(decode_pdf_string)
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+// XMP-пакет из потока /Metadata каталога
+pub fn read_xmp_packet(doc: &Document) -> Option<XmpMeta> {
+    let metadata_id = doc.catalog().ok()?.get(b"Metadata").ok()?.as_reference().ok()?;
+    let stream = doc.get_object(metadata_id).ok()?.as_stream().ok()?;
+    let content = stream
+        .decompressed_content()
+        .unwrap_or_else(|_| stream.content.clone());
+
+    let packet = String::from_utf8_lossy(&content);
+    packet.trim_matches(char::from(0)).parse().ok()
+}
+
+// Записывает XMP-пакет в поток /Metadata каталога: существующий поток обновляется, иначе создается новый.
+// Пакет пишется без сжатия и с запасом места, чтобы следующие изменения можно было делать на месте
+pub fn set_xmp_packet(doc: &mut Document, xmp: &XmpMeta) -> Result<()> {
+    let packet = xmp.to_string_with_options(ToStringOptions::default().set_padding(2048))?;
+
+    let existing = doc
+        .catalog()
+        .ok()
+        .and_then(|catalog| catalog.get(b"Metadata").ok())
+        .and_then(|o| o.as_reference().ok());
+
+    if let Some(Ok(stream)) = existing.map(|id| doc.get_object_mut(id).and_then(Object::as_stream_mut)) {
+        stream.set_plain_content(packet.into_bytes());
+        stream.allows_compression = false;
+        return Ok(());
+    }
+
+    let mut stream = Stream::new(
+        dictionary! { "Type" => "Metadata", "Subtype" => "XML" },
+        packet.into_bytes(),
+    );
+    stream.allows_compression = false;
+    let metadata_id = doc.add_object(stream);
+
+    doc.catalog_mut()
+        .context("PDF has no catalog")?
+        .set("Metadata", Object::Reference(metadata_id));
+    Ok(())
+}
+
+fn xmp_source(xmp: &XmpMeta) -> MetadataSource {
+    let text = |ns: &str, name: &str| {
+        xmp.property(ns, name)
+            .map(|p| p.value.trim().to_string())
+            .filter(|s| !s.is_empty())
+    };
+    let lang_alt = |ns: &str, name: &str| {
+        xmp.localized_text(ns, name, None, "x-default")
+            .map(|(p, _)| p.value.trim().to_string())
+            .filter(|s| !s.is_empty())
+    };
+
+    let authors: Vec<String> = xmp
+        .property_array(xmp_ns::DC, "creator")
+        .map(|p| p.value.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    MetadataSource {
+        title: lang_alt(xmp_ns::DC, "title"),
+        author: Some(authors.join("; ")).filter(|s| !s.is_empty()),
+        subject: lang_alt(xmp_ns::DC, "description"),
+        keywords: text(xmp_ns::PDF, "Keywords"),
+        creator: text(xmp_ns::XMP, "CreatorTool"),
+        producer: text(xmp_ns::PDF, "Producer"),
+        modified: text(xmp_ns::XMP, "MetadataDate")
+            .or_else(|| text(xmp_ns::XMP, "ModifyDate"))
+            .and_then(|d| parse_xmp_date(&d)),
+    }
+}
+
+// Текстовый слой есть, если на первых страницах встречаются операторы вывода текста
+fn has_text_layer(doc: &Document) -> bool {
+    doc.get_pages()
+        .values()
+        .take(TEXT_LAYER_PAGES)
+        .filter_map(|page_id| doc.get_and_decode_page_content(*page_id).ok())
+        .any(|content| {
+            content
+                .operations
+                .iter()
+                .any(|op| matches!(op.operator.as_str(), "Tj" | "TJ" | "'" | "\""))
+        })
+}
+
+fn is_tagged(doc: &Document) -> bool {
+    let catalog = match doc.catalog() {
+        Ok(catalog) => catalog,
+        Err(_) => return false,
+    };
+
+    let marked = catalog
+        .get(b"MarkInfo")
+        .ok()
+        .and_then(|o| match o {
+            Object::Reference(id) => doc.get_dictionary(*id).ok(),
+            Object::Dictionary(dict) => Some(dict),
+            _ => None,
+        })
+        .and_then(|mark_info| mark_info.get(b"Marked").and_then(|o| o.as_bool()).ok())
+        .unwrap_or(false);
+
+    marked || catalog.has(b"StructTreeRoot")
+}
@@ -0,0 +1,27 @@
+// Подключение библиотеки pdfium: сначала рядом с исполняемым файлом, затем системная
+
+use pdfium_render::prelude::*;
+
+use crate::error::{Error, ErrorKind, Result};
+
+pub fn load_pdfium() -> Result<Pdfium> {
+    let bundled = std::env::current_exe()
+        .ok()
+        .and_then(|exe| {
+            exe.parent()
+                .map(Pdfium::pdfium_platform_library_name_at_path)
+        })
+        .and_then(|path| Pdfium::bind_to_library(path).ok());
+
+    let bindings = match bundled {
+        Some(bindings) => bindings,
+        None => Pdfium::bind_to_system_library().map_err(|e| {
+            Error::new(
+                ErrorKind::Unavailable,
+                format!("Pdfium library not found: {}", e),
+            )
+        })?,
+    };
+
+    Ok(Pdfium::new(bindings))
+}
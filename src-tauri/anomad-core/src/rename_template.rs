@@ -0,0 +1,307 @@
+// Имена файлов по шаблону вида "{author_last} {year} {title}":
+// поля берутся из метаданных PDF и из разобранного имени файла
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use super::bibtex::{last_name, split_authors};
+use super::filename_patterns::{parse_file_name, FilenamePattern};
+use super::identifiers::xmp_year;
+use super::pdf_metadata::{document_metadata, load_pdf, read_xmp_packet};
+use crate::error::{Error, ErrorKind, Result};
+
+// Ограничения Windows: 255 байт на имя и MAX_PATH (260 с завершающим нулем) на весь путь
+const MAX_NAME_BYTES: usize = 255;
+const MAX_PATH_CHARS: usize = 259;
+
+const ILLEGAL_CHARS: [char; 9] = ['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+const RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RenameStatus {
+    // План: можно переименовать
+    Ready,
+    // Имя по шаблону совпадает с текущим
+    Unchanged,
+    // Совпадает с другим файлом пакета или с существующим файлом
+    Conflict,
+    // Не удалось построить имя (нет полей, файл не найден и т.п.)
+    Invalid,
+    // Результат применения
+    Renamed,
+    Failed,
+}
+
+// Почему пункт плана не готов. Текст для пользователя составляет приложение
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PlanReason {
+    FileNotFound,
+    // Поля шаблона, которых нет ни в метаданных, ни в имени файла
+    MissingFields { fields: Vec<String> },
+    // Имя пустое или путь не укладывается в ограничения Windows
+    InvalidName,
+    // Файл с таким именем уже есть на диске
+    NameTaken,
+    // Такое же имя получает другой файл пакета
+    BatchCollision,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenamePlanItem {
+    pub source: String,
+    pub target: Option<String>,
+    pub status: RenameStatus,
+    pub reason: Option<PlanReason>,
+    // Заполняет приложение: описание reason или ошибки применения
+    pub message: Option<String>,
+}
+
+// Поля для шаблона. Метаданные PDF важнее имени файла, имя файла заполняет пробелы
+pub fn rename_fields(path: &Path, patterns: &[FilenamePattern]) -> BTreeMap<String, String> {
+    let mut fields = BTreeMap::new();
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    if let Some(parsed) = parse_file_name(&stem, patterns) {
+        fields.extend(parsed.fields);
+        fields.extend(parsed.title.map(|v| ("title".to_string(), v)));
+        fields.extend(parsed.author.map(|v| ("author".to_string(), v)));
+        fields.extend(parsed.year.map(|v| ("year".to_string(), v)));
+    }
+
+    let is_pdf = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"));
+    if is_pdf {
+        match load_pdf(path) {
+            Ok((doc, is_encrypted)) => {
+                let metadata = document_metadata(&doc, is_encrypted);
+                let year = read_xmp_packet(&doc).as_ref().and_then(xmp_year);
+
+                let pdf_fields = [
+                    ("title", metadata.title),
+                    ("author", metadata.author),
+                    ("subject", metadata.details.subject),
+                    ("year", year),
+                ];
+                for (name, value) in pdf_fields {
+                    if let Some(value) = value.filter(|v| !v.trim().is_empty()) {
+                        fields.insert(name.to_string(), value.trim().to_string());
+                    }
+                }
+            }
+            Err(e) => log::warn!("Метаданные не прочитаны ({}): {}", path.display(), e),
+        }
+    }
+
+    if let Some(author) = fields.get("author").cloned() {
+        let authors = split_authors(&author);
+        if let Some(first) = authors.first() {
+            fields.insert("author_last".to_string(), last_name(first));
+            fields.insert("author".to_string(), first.clone());
+        }
+        fields.insert("authors".to_string(), authors.join(", "));
+    }
+    fields.insert("original".to_string(), stem);
+
+    fields
+}
+
+// Поля шаблона без значения, в порядке появления
+pub fn missing_fields(template: &str, fields: &BTreeMap<String, String>) -> Vec<String> {
+    let mut missing = Vec::new();
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' | '}' if chars.peek() == Some(&c) => {
+                chars.next();
+            }
+            '{' => {
+                let name: String = chars.by_ref().take_while(|c| *c != '}').collect();
+                if !fields.contains_key(name.trim()) {
+                    missing.push(name.trim().to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+    missing
+}
+
+// Все поля шаблона должны быть заполнены, иначе имя получится с дырами
+pub fn render_template(template: &str, fields: &BTreeMap<String, String>) -> Result<String> {
+    let missing = missing_fields(template, fields);
+    if !missing.is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("Missing fields: {}", missing.join(", ")),
+        ));
+    }
+
+    let mut rendered = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' | '}' if chars.peek() == Some(&c) => {
+                chars.next();
+                rendered.push(c);
+            }
+            '{' => {
+                let name: String = chars.by_ref().take_while(|c| *c != '}').collect();
+                rendered.push_str(&fields[name.trim()]);
+            }
+            _ => rendered.push(c),
+        }
+    }
+    Ok(rendered)
+}
+
+// Убирает символы, запрещенные в именах Windows, и лишние пробелы
+pub fn sanitize_file_name(name: &str) -> String {
+    let replaced: String = name
+        .chars()
+        .map(|c| {
+            if ILLEGAL_CHARS.contains(&c) || c.is_control() {
+                ' '
+            } else {
+                c
+            }
+        })
+        .collect();
+
+    // Windows молча отбрасывает точки и пробелы в конце имени
+    let mut cleaned = replaced.split_whitespace().collect::<Vec<_>>().join(" ");
+    cleaned = cleaned.trim_end_matches(['.', ' ']).to_string();
+
+    let base = cleaned.split('.').next().unwrap_or_default();
+    if RESERVED_NAMES.iter().any(|r| r.eq_ignore_ascii_case(base)) {
+        cleaned.push('_');
+    }
+    cleaned
+}
+
+// Укорачивает имя (но не расширение), чтобы путь поместился в ограничения Windows
+pub fn fit_file_name(dir: &Path, stem: &str, extension: Option<&str>) -> Result<PathBuf> {
+    let suffix = extension.map(|e| format!(".{}", e)).unwrap_or_default();
+    // +1 - разделитель между папкой и именем
+    let dir_chars = dir.to_string_lossy().chars().count() + 1;
+
+    let max_chars = MAX_PATH_CHARS
+        .checked_sub(dir_chars + suffix.chars().count())
+        .filter(|n| *n > 0)
+        .context("Directory path is too long")?;
+    let max_bytes = MAX_NAME_BYTES
+        .checked_sub(suffix.len())
+        .context("Extension is too long")?;
+
+    let mut fitted = String::new();
+    for c in stem.chars() {
+        if fitted.chars().count() + 1 > max_chars || fitted.len() + c.len_utf8() > max_bytes {
+            break;
+        }
+        fitted.push(c);
+    }
+
+    let fitted = fitted.trim_end_matches(['.', ' ']);
+    if fitted.is_empty() {
+        return Err(Error::new(ErrorKind::InvalidInput, "File name is empty"));
+    }
+    Ok(dir.join(format!("{}{}", fitted, suffix)))
+}
+
+// Windows и macOS по умолчанию не различают регистр, поэтому сравниваем без него
+pub fn collision_key(path: &Path) -> String {
+    path.to_string_lossy().to_lowercase()
+}
+
+// План пакетного переименования; на диске ничего не меняется
+pub fn plan_renames(
+    file_paths: &[String],
+    template: &str,
+    patterns: &[FilenamePattern],
+) -> Vec<RenamePlanItem> {
+    let mut plan: Vec<RenamePlanItem> = file_paths
+        .iter()
+        .map(|file_path| plan_rename(file_path, template, patterns))
+        .collect();
+
+    // Два файла пакета не могут получить одно имя
+    let mut targets: HashMap<String, Vec<usize>> = HashMap::new();
+    for (index, item) in plan.iter().enumerate() {
+        if let (RenameStatus::Ready, Some(target)) = (item.status, &item.target) {
+            targets
+                .entry(collision_key(Path::new(target)))
+                .or_default()
+                .push(index);
+        }
+    }
+    for indices in targets.values().filter(|i| i.len() > 1) {
+        for &index in indices {
+            plan[index].status = RenameStatus::Conflict;
+            plan[index].reason = Some(PlanReason::BatchCollision);
+        }
+    }
+
+    plan
+}
+
+fn plan_rename(file_path: &str, template: &str, patterns: &[FilenamePattern]) -> RenamePlanItem {
+    let item = |target: Option<&Path>, status, reason| RenamePlanItem {
+        source: file_path.to_string(),
+        target: target.map(|t| t.to_string_lossy().into_owned()),
+        status,
+        reason,
+        message: None,
+    };
+
+    let source = Path::new(file_path);
+    if !source.is_file() {
+        return item(None, RenameStatus::Invalid, Some(PlanReason::FileNotFound));
+    }
+
+    let fields = rename_fields(source, patterns);
+    let name = match render_template(template, &fields) {
+        Ok(name) => sanitize_file_name(&name),
+        Err(_) => {
+            let fields = missing_fields(template, &fields);
+            return item(
+                None,
+                RenameStatus::Invalid,
+                Some(PlanReason::MissingFields { fields }),
+            );
+        }
+    };
+
+    let dir = source.parent().unwrap_or(Path::new(""));
+    let extension = source.extension().map(|e| e.to_string_lossy());
+    let target = match fit_file_name(dir, &name, extension.as_deref()) {
+        Ok(target) => target,
+        Err(_) => return item(None, RenameStatus::Invalid, Some(PlanReason::InvalidName)),
+    };
+
+    if target == source {
+        return item(Some(&target), RenameStatus::Unchanged, None);
+    }
+
+    // Смена только регистра - это тот же файл, а не конфликт
+    let same_file = collision_key(&target) == collision_key(source);
+    if target.exists() && !same_file {
+        return item(
+            Some(&target),
+            RenameStatus::Conflict,
+            Some(PlanReason::NameTaken),
+        );
+    }
+
+    item(Some(&target), RenameStatus::Ready, None)
+}
@@ -0,0 +1,179 @@
+// Единственный путь записи PDF: временный файл в той же папке, проверка результата,
+// атомарная замена оригинала и ротация резервных копий
+
+use anyhow::{anyhow, Context};
+use lopdf::Document;
+use rand::{distributions::Alphanumeric, Rng};
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, ErrorKind, Result};
+use super::file_lock::ensure_not_locked;
+use super::get_file_highlights::page_annotations;
+use super::pdf_metadata::load_pdf;
+
+// Папка с резервными копиями рядом с файлом и сколько копий храним
+pub const BACKUP_DIR: &str = ".anomad-backups";
+const BACKUP_COUNT: usize = 3;
+
+// То, что не должно потеряться при записи: число страниц и аннотаций
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PdfSnapshot {
+    pub page_count: usize,
+    pub annotation_count: usize,
+}
+
+impl PdfSnapshot {
+    pub fn of(doc: &Document) -> Self {
+        let pages = doc.get_pages();
+        let annotation_count = pages
+            .values()
+            .map(|page_id| page_annotations(doc, *page_id).map_or(0, |a| a.len()))
+            .sum();
+
+        PdfSnapshot {
+            page_count: pages.len(),
+            annotation_count,
+        }
+    }
+
+    pub fn of_file(path: &Path) -> Result<Self> {
+        let (doc, _) = load_pdf(path)?;
+        Ok(Self::of(&doc))
+    }
+}
+
+// Сохраняет документ из памяти; ожидаемый результат - то, что в нем сейчас.
+// is_encrypted - флаг из load_pdf: расшифрованный документ уже без /Encrypt,
+// и lopdf молча сохранил бы его без шифрования
+pub fn save_pdf(doc: &mut Document, path: &Path, is_encrypted: bool) -> Result<()> {
+    refuse_encrypted(path, is_encrypted || doc.is_encrypted())?;
+
+    let expected = PdfSnapshot::of(doc);
+    write_pdf_with(path, expected, |temp| {
+        doc.save(temp)
+            .map(|_| ())
+            .with_context(|| format!("Failed to save PDF: {}", temp.display()))?;
+        Ok(())
+    })
+}
+
+// Изменение на месте (например, через xmp_toolkit) выполняется над копией оригинала
+pub fn update_pdf_in_place<F>(path: &Path, update: F) -> Result<()>
+where
+    F: FnOnce(&Path) -> Result<()>,
+{
+    let (doc, is_encrypted) = load_pdf(path)?;
+    refuse_encrypted(path, is_encrypted)?;
+
+    let expected = PdfSnapshot::of(&doc);
+    write_pdf_with(path, expected, |temp| {
+        fs::copy(path, temp).context("Failed to copy PDF")?;
+        update(temp)
+    })
+}
+
+// Зашифрованные PDF не пишем: новые строки и потоки пришлось бы шифровать заново
+fn refuse_encrypted(path: &Path, is_encrypted: bool) -> Result<()> {
+    if is_encrypted {
+        return Err(Error::new(
+            ErrorKind::Encrypted,
+            format!("Encrypted PDF cannot be modified: {}", path.display()),
+        ));
+    }
+    Ok(())
+}
+
+// write получает путь временного файла и должен записать туда новую версию PDF
+pub fn write_pdf_with<F>(path: &Path, expected: PdfSnapshot, write: F) -> Result<()>
+where
+    F: FnOnce(&Path) -> Result<()>,
+{
+    let exists = path.exists();
+    if exists {
+        ensure_not_locked(path)?;
+    }
+
+    let temp = temp_path(path)?;
+    let result = write(&temp)
+        .and_then(|_| verify(&temp, expected))
+        .and_then(|_| {
+            if exists {
+                // Права оригинала переносим на новую версию
+                let permissions = fs::metadata(path)?.permissions();
+                fs::set_permissions(&temp, permissions)?;
+                backup(path)?;
+            }
+            fs::rename(&temp, path)
+                .with_context(|| format!("Failed to replace {}", path.display()))?;
+            Ok(())
+        });
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+fn temp_path(path: &Path) -> Result<PathBuf> {
+    let dir = path.parent().context("File has no parent directory")?;
+    let name = path.file_name().context("Invalid file name")?.to_string_lossy();
+    let suffix: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(8)
+        .map(char::from)
+        .collect();
+
+    // Та же папка - значит та же файловая система и rename атомарен
+    Ok(dir.join(format!(".{}.{}.tmp", name, suffix)))
+}
+
+fn verify(temp: &Path, expected: PdfSnapshot) -> Result<()> {
+    // Данные должны дойти до диска до замены оригинала
+    OpenOptions::new()
+        .write(true)
+        .open(temp)
+        .and_then(|f| f.sync_all())
+        .context("Failed to flush PDF to disk")?;
+
+    let actual = PdfSnapshot::of_file(temp).context("Written PDF cannot be parsed")?;
+    if actual != expected {
+        return Err(anyhow!(
+            "Written PDF differs from expected: {} pages and {} annotations instead of {} and {}",
+            actual.page_count,
+            actual.annotation_count,
+            expected.page_count,
+            expected.annotation_count
+        )
+        .into());
+    }
+    Ok(())
+}
+
+// book.pdf.1.bak - самая свежая копия, book.pdf.3.bak - самая старая
+pub fn backup_path(path: &Path, index: usize) -> Option<PathBuf> {
+    let name = path.file_name()?.to_string_lossy();
+    Some(
+        path.parent()?
+            .join(BACKUP_DIR)
+            .join(format!("{}.{}.bak", name, index)),
+    )
+}
+
+fn backup(path: &Path) -> Result<()> {
+    let newest = backup_path(path, 1).context("Invalid file path")?;
+    if let Some(dir) = newest.parent() {
+        fs::create_dir_all(dir).context("Failed to create backup directory")?;
+    }
+
+    for index in (1..BACKUP_COUNT).rev() {
+        if let (Some(from), Some(to)) = (backup_path(path, index), backup_path(path, index + 1)) {
+            if from.exists() {
+                fs::rename(&from, &to).context("Failed to rotate backups")?;
+            }
+        }
+    }
+
+    fs::copy(path, &newest).context("Failed to create backup")?;
+    Ok(())
+}
@@ -0,0 +1,312 @@
+// Текстовый слой документа для поиска фрагментов: нормализованные символы с координатами.
+// Нормализация одна и та же для текста документа и для искомого фрагмента
+
+// Координаты символа в пространстве страницы: left, bottom, right, top
+pub type CharBounds = [f32; 4];
+
+#[derive(Debug, Clone)]
+pub struct LayerChar {
+    pub page: u32,
+    pub ch: char,
+    // None у пробелов и переносов строк, которые pdfium добавляет сам
+    pub bounds: Option<CharBounds>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TextLayer {
+    pub chars: Vec<LayerChar>,
+    pub page_count: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextMatch {
+    pub start: usize,
+    pub end: usize,
+    // 1.0 - точное совпадение, дальше доля несовпавших символов
+    pub confidence: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchedArea {
+    pub page: u32,
+    pub rect: [f32; 4],
+    pub quad_points: Vec<f32>,
+}
+
+// Страницы по обе стороны от ожидаемой, на которых ищем нечетко
+const PAGE_WINDOW: u32 = 5;
+// Предел работы нечеткого поиска по всему документу (символы документа * символы фрагмента)
+const MAX_FULL_SCAN: usize = 50_000_000;
+
+impl TextLayer {
+    // chars - символы страниц по порядку, как их отдает pdfium
+    pub fn new(chars: Vec<LayerChar>, page_count: u32) -> Self {
+        TextLayer {
+            chars: normalize(chars),
+            page_count,
+        }
+    }
+
+    fn text(&self, range: std::ops::Range<usize>) -> Vec<char> {
+        self.chars[range].iter().map(|c| c.ch).collect()
+    }
+
+    fn page_range(&self, first: u32, last: u32) -> std::ops::Range<usize> {
+        let start = self
+            .chars
+            .iter()
+            .position(|c| c.page >= first)
+            .unwrap_or(self.chars.len());
+        let end = self
+            .chars
+            .iter()
+            .rposition(|c| c.page <= last)
+            .map_or(start, |i| i + 1)
+            .max(start);
+        start..end
+    }
+
+    // Сначала точное совпадение (ближайшее к ожидаемой странице), затем нечеткий поиск
+    // рядом с ожидаемой страницей и, если документ не слишком велик, по всему документу
+    pub fn find(&self, query: &str, expected_page: u32, min_confidence: f32) -> Option<TextMatch> {
+        let pattern: Vec<char> = normalize_text(query).chars().collect();
+        if pattern.is_empty() {
+            return None;
+        }
+
+        if let Some(found) = self.find_exact(&pattern, expected_page) {
+            return Some(found);
+        }
+
+        let window = self.page_range(
+            expected_page.saturating_sub(PAGE_WINDOW),
+            expected_page + PAGE_WINDOW,
+        );
+        let mut best = self.find_fuzzy(&pattern, window);
+
+        let good_enough = best.is_some_and(|m| m.confidence >= min_confidence);
+        if !good_enough && self.chars.len() * pattern.len() <= MAX_FULL_SCAN {
+            let full = self.find_fuzzy(&pattern, 0..self.chars.len());
+            if full.map(|m| m.confidence) > best.map(|m| m.confidence) {
+                best = full;
+            }
+        }
+
+        best.filter(|m| m.confidence >= min_confidence)
+    }
+
+    fn find_exact(&self, pattern: &[char], expected_page: u32) -> Option<TextMatch> {
+        if pattern.len() > self.chars.len() {
+            return None;
+        }
+
+        (0..=self.chars.len() - pattern.len())
+            .filter(|&start| {
+                self.chars[start..start + pattern.len()]
+                    .iter()
+                    .zip(pattern)
+                    .all(|(c, p)| c.ch == *p)
+            })
+            .min_by_key(|&start| self.chars[start].page.abs_diff(expected_page))
+            .map(|start| TextMatch {
+                start,
+                end: start + pattern.len(),
+                confidence: 1.0,
+            })
+    }
+
+    // Приближенный поиск подстроки (алгоритм Селлерса): расстояние Левенштейна
+    // между фрагментом и лучшим участком текста, начало участка отслеживается попутно
+    fn find_fuzzy(&self, pattern: &[char], range: std::ops::Range<usize>) -> Option<TextMatch> {
+        let offset = range.start;
+        let text = self.text(range);
+        if text.is_empty() {
+            return None;
+        }
+
+        let m = pattern.len();
+        let mut distance: Vec<usize> = (0..=m).collect();
+        let mut start: Vec<usize> = vec![0; m + 1];
+        let mut best: Option<(usize, usize, usize)> = None;
+
+        for (j, &ch) in text.iter().enumerate() {
+            let (mut diagonal, mut diagonal_start) = (distance[0], start[0]);
+            // Совпадение может начаться с любого символа текста
+            distance[0] = 0;
+            start[0] = j + 1;
+
+            for i in 1..=m {
+                let (above, above_start) = (distance[i], start[i]);
+                let substitution = diagonal + usize::from(pattern[i - 1] != ch);
+                let deletion = distance[i - 1] + 1;
+                let insertion = above + 1;
+
+                let (value, value_start) = if substitution <= deletion && substitution <= insertion
+                {
+                    (substitution, diagonal_start)
+                } else if deletion <= insertion {
+                    (deletion, start[i - 1])
+                } else {
+                    (insertion, above_start)
+                };
+
+                diagonal = above;
+                diagonal_start = above_start;
+                distance[i] = value;
+                start[i] = value_start;
+            }
+
+            // При равном расстоянии продлеваем совпадение, а не обрываем его на середине слова
+            let better = best.map_or(true, |(d, _, end)| {
+                distance[m] < d || (distance[m] == d && end == j)
+            });
+            if better {
+                best = Some((distance[m], start[m], j + 1));
+            }
+        }
+
+        best.map(|(d, s, e)| TextMatch {
+            start: offset + s,
+            end: offset + e,
+            confidence: (1.0 - d as f32 / m as f32).max(0.0),
+        })
+    }
+
+    // Рамки найденного текста: по одной строке на четырехугольник, по одной области на страницу
+    pub fn areas(&self, found: &TextMatch) -> Vec<MatchedArea> {
+        let mut areas: Vec<MatchedArea> = Vec::new();
+        let mut lines: Vec<(u32, CharBounds)> = Vec::new();
+
+        for c in &self.chars[found.start..found.end] {
+            let bounds = match c.bounds {
+                Some(bounds) => bounds,
+                None => continue,
+            };
+
+            match lines.last_mut() {
+                Some((page, line)) if *page == c.page && same_line(line, &bounds) => {
+                    line[0] = line[0].min(bounds[0]);
+                    line[1] = line[1].min(bounds[1]);
+                    line[2] = line[2].max(bounds[2]);
+                    line[3] = line[3].max(bounds[3]);
+                }
+                _ => lines.push((c.page, bounds)),
+            }
+        }
+
+        for (page, [left, bottom, right, top]) in lines {
+            // Порядок QuadPoints, который понимают Acrobat и большинство читалок
+            let quad = [left, top, right, top, left, bottom, right, bottom];
+            match areas.last_mut() {
+                Some(area) if area.page == page => {
+                    area.rect[0] = area.rect[0].min(left);
+                    area.rect[1] = area.rect[1].min(bottom);
+                    area.rect[2] = area.rect[2].max(right);
+                    area.rect[3] = area.rect[3].max(top);
+                    area.quad_points.extend(quad);
+                }
+                _ => areas.push(MatchedArea {
+                    page,
+                    rect: [left, bottom, right, top],
+                    quad_points: quad.to_vec(),
+                }),
+            }
+        }
+
+        areas
+    }
+}
+
+// Символ на той же строке, если его середина по высоте внутри строки и он не левее ее начала
+fn same_line(line: &CharBounds, bounds: &CharBounds) -> bool {
+    let middle = (bounds[1] + bounds[3]) / 2.0;
+    middle >= line[1] && middle <= line[3] && bounds[0] >= line[0] - 1.0
+}
+
+// Нормализация для сравнения: регистр, лигатуры, кавычки и тире, переносы, пробелы
+pub fn normalize_text(text: &str) -> String {
+    let chars = text
+        .chars()
+        .map(|ch| LayerChar {
+            page: 0,
+            ch,
+            bounds: None,
+        })
+        .collect();
+    normalize(chars).into_iter().map(|c| c.ch).collect()
+}
+
+fn normalize(chars: Vec<LayerChar>) -> Vec<LayerChar> {
+    // Раскрываем лигатуры и приводим похожие символы к одному виду
+    let mut expanded: Vec<LayerChar> = Vec::with_capacity(chars.len());
+    for c in chars {
+        let replacement = match c.ch {
+            // Дефис U+2010 ведет себя как обычный; мягкий перенос разбирается ниже
+            '\u{2010}' => "-",
+            'ﬀ' => "ff",
+            'ﬁ' => "fi",
+            'ﬂ' => "fl",
+            'ﬃ' => "ffi",
+            'ﬄ' => "ffl",
+            'ﬅ' | 'ﬆ' => "st",
+            '‘' | '’' | '‚' | '‛' | '′' => "'",
+            '“' | '”' | '„' | '‟' | '″' | '«' | '»' => "\"",
+            _ => {
+                expanded.extend(c.ch.to_lowercase().map(|ch| LayerChar { ch, ..c.clone() }));
+                continue;
+            }
+        };
+        expanded.extend(replacement.chars().map(|ch| LayerChar { ch, ..c.clone() }));
+    }
+
+    let mut result: Vec<LayerChar> = Vec::with_capacity(expanded.len());
+    let mut i = 0;
+    while i < expanded.len() {
+        let c = &expanded[i];
+
+        // Перенос слова: дефис в конце строки (или страницы) вместе с разрывом убираем.
+        // Тире переносом не бывает, поэтому к дефису оно приводится только после этого
+        let is_hyphen = c.ch == '-' || c.ch == '\u{00AD}';
+        if is_hyphen && result.last().is_some_and(|l| l.ch.is_alphabetic()) {
+            let next = expanded[i + 1..]
+                .iter()
+                .position(|n| !n.ch.is_whitespace())
+                .map_or(expanded.len(), |p| i + 1 + p);
+            let breaks_line = expanded[i + 1..next]
+                .iter()
+                .any(|n| n.ch == '\n' || n.ch == '\r')
+                || expanded.get(next).is_some_and(|n| n.page != c.page);
+            if breaks_line && expanded.get(next).is_some_and(|n| n.ch.is_alphabetic()) {
+                i = next;
+                continue;
+            }
+        }
+
+        // Любой пробельный символ и граница страниц - один пробел
+        let new_page = result.last().is_some_and(|l| l.page != c.page);
+        if (c.ch.is_whitespace() || new_page) && result.last().is_some_and(|l| l.ch != ' ') {
+            result.push(LayerChar {
+                page: c.page,
+                ch: ' ',
+                bounds: None,
+            });
+        }
+        // Мягкий перенос внутри строки не виден и в тексте не нужен
+        if c.ch.is_whitespace() || c.ch == '\u{00AD}' {
+            i += 1;
+            continue;
+        }
+
+        let ch = match c.ch {
+            '‑' | '‒' | '–' | '—' | '−' => '-',
+            ch => ch,
+        };
+        result.push(LayerChar { ch, ..c.clone() });
+        i += 1;
+    }
+
+    while result.last().is_some_and(|c| c.ch == ' ') {
+        result.pop();
+    }
+    result
+}
@@ -0,0 +1,110 @@
+// Удаление в системную корзину: Recycle Bin на Windows, freedesktop Trash на Linux.
+// Восстановление из корзины trash поддерживает только на этих платформах
+
+use anyhow::{anyhow, Context};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, ErrorKind, Result};
+
+#[cfg(any(
+    target_os = "windows",
+    all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))
+))]
+mod restorable {
+    use super::*;
+    use trash::os_limited;
+
+    // Элемент корзины с этим путем, удаленный последним
+    fn find_trash_item(original: &Path) -> Result<Option<trash::TrashItem>> {
+        let parent = original.parent().context("File has no parent directory")?;
+        let name = original.file_name().context("Invalid file name")?;
+
+        let items = os_limited::list().context("Failed to list trash")?;
+        Ok(items
+            .into_iter()
+            .filter(|item| item.original_parent == parent && Path::new(&item.name) == Path::new(name))
+            .max_by_key(|item| item.time_deleted))
+    }
+
+    pub fn trash_location(original: &Path) -> Result<Option<String>> {
+        Ok(find_trash_item(original)?.map(|item| item.id.to_string_lossy().into_owned()))
+    }
+
+    pub fn restore(location: &str, original: &Path) -> Result<()> {
+        let items = os_limited::list().context("Failed to list trash")?;
+        let item = items
+            .into_iter()
+            .find(|item| item.id.to_string_lossy() == location)
+            .ok_or_else(|| anyhow!("Item is no longer in trash: {}", original.display()))?;
+
+        os_limited::restore_all([item])
+            .with_context(|| format!("Failed to restore {} from trash", original.display()))?;
+        Ok(())
+    }
+}
+
+#[cfg(not(any(
+    target_os = "windows",
+    all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))
+)))]
+mod restorable {
+    use super::*;
+
+    pub fn trash_location(_original: &Path) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    pub fn restore(_location: &str, original: &Path) -> Result<()> {
+        Err(Error::new(
+            ErrorKind::Unavailable,
+            format!(
+                "Restoring from trash is not supported on this platform: {}",
+                original.display()
+            ),
+        ))
+    }
+}
+
+// Возвращает идентификатор элемента корзины (путь внутри корзины), если его можно восстановить
+pub fn move_to_trash(path: &Path) -> Result<Option<String>> {
+    // Корзина запоминает абсолютный путь, по нему потом и ищем. canonicalize не подходит:
+    // на Windows он дает путь вида \\?\C:\..., а корзина хранит обычный
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()?.join(path)
+    };
+    trash::delete(&path).with_context(|| format!("Failed to move {} to trash", path.display()))?;
+
+    match restorable::trash_location(&path) {
+        Ok(location) => Ok(location),
+        Err(e) => {
+            log::warn!("Не удалось найти файл в корзине: {}", e);
+            Ok(None)
+        }
+    }
+}
+
+pub fn restore_from_trash(location: &str, original: &Path) -> Result<()> {
+    if original.exists() {
+        return Err(Error::new(
+            ErrorKind::AlreadyExists,
+            format!("File already exists: {}", original.display()),
+        ));
+    }
+    restorable::restore(location, original)
+}
+
+// Папку можно удалить, только если она лежит внутри одной из папок библиотеки (но не сама папка)
+pub fn is_inside_library(path: &Path, roots: &[PathBuf]) -> bool {
+    let path = match fs::canonicalize(path) {
+        Ok(path) => path,
+        Err(_) => return false,
+    };
+
+    roots
+        .iter()
+        .filter_map(|root| fs::canonicalize(root).ok())
+        .any(|root| path != root && path.starts_with(&root))
+}
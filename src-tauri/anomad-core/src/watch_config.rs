@@ -0,0 +1,207 @@
+// Сохраненные настройки отслеживания: папки с их параметрами, отдельные файлы
+// и снимок файлов (размер и время изменения), чтобы после запуска догнать изменения,
+// сделанные, пока приложение было закрыто
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use super::watch_events::{list_files, matches_extension, ChangeKind, LogicalChange};
+use crate::error::Result;
+
+pub const WATCH_CONFIG_FILE: &str = "watchers.json";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileStamp {
+    pub size: u64,
+    // Время изменения в миллисекундах от начала эпохи
+    pub modified: i64,
+}
+
+// Путь файла -> его отметка на момент снимка
+pub type Snapshot = BTreeMap<String, FileStamp>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectoryConfig {
+    pub dir_path: String,
+    pub recursive: bool,
+    pub extensions: Vec<String>,
+    pub project: Option<String>,
+    #[serde(default)]
+    pub snapshot: Snapshot,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StoredConfig {
+    directories: Vec<DirectoryConfig>,
+    // Отдельно отслеживаемые файлы (файлы активного проекта)
+    files: Snapshot,
+}
+
+pub struct WatchConfig {
+    file: PathBuf,
+    stored: StoredConfig,
+}
+
+impl WatchConfig {
+    // Поврежденный файл настроек не должен мешать запуску приложения
+    pub fn load(file: PathBuf) -> Self {
+        let stored = match fs::read_to_string(&file) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                log::warn!("Настройки отслеживания повреждены и будут сброшены: {}", e);
+                StoredConfig::default()
+            }),
+            Err(_) => StoredConfig::default(),
+        };
+        WatchConfig { file, stored }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        if let Some(dir) = self.file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.file, serde_json::to_string_pretty(&self.stored)?)
+            .with_context(|| format!("Failed to save watch config: {}", self.file.display()))?;
+        Ok(())
+    }
+
+    // Файл настроек - для сообщений об ошибках сохранения
+    pub fn file(&self) -> &Path {
+        &self.file
+    }
+
+    pub fn directories(&self) -> &[DirectoryConfig] {
+        &self.stored.directories
+    }
+
+    pub fn files(&self) -> Vec<String> {
+        self.stored.files.keys().cloned().collect()
+    }
+
+    pub fn files_snapshot(&self) -> &Snapshot {
+        &self.stored.files
+    }
+
+    // Повторный вызов для той же папки заменяет ее параметры
+    pub fn set_directory(&mut self, directory: DirectoryConfig) {
+        self.remove_directory(&directory.dir_path);
+        self.stored.directories.push(directory);
+    }
+
+    pub fn remove_directory(&mut self, dir_path: &str) {
+        self.stored.directories.retain(|d| d.dir_path != dir_path);
+    }
+
+    // Снимок, снятый без блокировки настроек; папку, убранную за это время, не возвращаем
+    pub fn set_directory_snapshot(&mut self, dir_path: &str, snapshot: Snapshot) {
+        if let Some(directory) = self
+            .stored
+            .directories
+            .iter_mut()
+            .find(|d| d.dir_path == dir_path)
+        {
+            directory.snapshot = snapshot;
+        }
+    }
+
+    // Файлы, которых сейчас нет на диске, остаются в настройках с прежней отметкой
+    // (или пустой, если файла еще не видели), пока пользователь сам не уберет их из списка
+    pub fn set_files(&mut self, paths: impl IntoIterator<Item = PathBuf>) {
+        let previous = std::mem::take(&mut self.stored.files);
+        self.stored.files = paths
+            .into_iter()
+            .map(|path| {
+                let key = path.to_string_lossy().into_owned();
+                let stamp = stamp(&path)
+                    .or_else(|| previous.get(&key).copied())
+                    .unwrap_or_default();
+                (key, stamp)
+            })
+            .collect();
+    }
+
+    // Снимки всех папок и файлов на текущий момент - перед закрытием приложения
+    pub fn refresh_snapshots(&mut self) {
+        for directory in &mut self.stored.directories {
+            if Path::new(&directory.dir_path).is_dir() {
+                directory.snapshot = directory_snapshot(directory);
+            }
+        }
+        let files: Vec<String> = self.files();
+        self.set_files(files.iter().map(PathBuf::from));
+    }
+}
+
+pub fn stamp(path: &Path) -> Option<FileStamp> {
+    let metadata = fs::metadata(path).ok()?;
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_millis() as i64);
+    Some(FileStamp {
+        size: metadata.len(),
+        modified,
+    })
+}
+
+pub fn directory_snapshot(directory: &DirectoryConfig) -> Snapshot {
+    let files = list_files(Path::new(&directory.dir_path), directory.recursive)
+        .into_iter()
+        .filter(|path| matches_extension(path, &directory.extensions));
+    files_snapshot(files)
+}
+
+// Отсутствующие файлы в снимок не попадают
+pub fn files_snapshot(paths: impl IntoIterator<Item = PathBuf>) -> Snapshot {
+    paths
+        .into_iter()
+        .filter_map(|path| Some((path.to_string_lossy().into_owned(), stamp(&path)?)))
+        .collect()
+}
+
+// Изменения между снимками. Переименование сохраняет размер и время изменения,
+// поэтому пара "исчез/появился" с одинаковой отметкой считается переименованием
+pub fn compare_snapshots(old: &Snapshot, new: &Snapshot) -> Vec<LogicalChange> {
+    let change = |kind, path: &str, old_path: Option<&str>| LogicalChange {
+        kind,
+        path: PathBuf::from(path),
+        old_path: old_path.map(PathBuf::from),
+    };
+
+    let mut deleted: Vec<(&String, &FileStamp)> = old
+        .iter()
+        .filter(|(path, _)| !new.contains_key(*path))
+        .collect();
+    let mut changes = Vec::new();
+
+    for (path, stamp) in new {
+        match old.get(path) {
+            Some(previous) if previous == stamp => {}
+            Some(_) => changes.push(change(ChangeKind::Modified, path, None)),
+            None => {
+                let same: Vec<usize> = deleted
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, (_, s))| *s == stamp)
+                    .map(|(i, _)| i)
+                    .collect();
+                // Несколько кандидатов - угадывать не беремся
+                if let [index] = same.as_slice() {
+                    let (old_path, _) = deleted.remove(*index);
+                    changes.push(change(ChangeKind::Renamed, path, Some(old_path)));
+                } else {
+                    changes.push(change(ChangeKind::Created, path, None));
+                }
+            }
+        }
+    }
+
+    for (path, _) in deleted {
+        changes.push(change(ChangeKind::Deleted, path, None));
+    }
+    changes
+}
@@ -0,0 +1,316 @@
+// Сведение сырых событий notify в логические изменения файлов.
+// Сохранение в читалке дает пачку create/modify/remove (часто через временный файл),
+// а переименование - пару событий From/To. Изменения пути выдаются, когда по нему
+// некоторое время нет событий; итог определяется по тому, был ли файл до пачки и есть ли он сейчас
+
+use notify::event::{EventKind, ModifyKind, RenameMode};
+use notify::Event;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use super::safe_write::BACKUP_DIR;
+
+// Сколько по пути не должно быть событий, чтобы пачка считалась законченной
+pub const DEBOUNCE: Duration = Duration::from_millis(500);
+// Как часто проверяем, не закончились ли пачки событий
+pub const FLUSH_INTERVAL: Duration = Duration::from_millis(100);
+
+// Окончания временных и недокачанных файлов
+const TEMP_SUFFIXES: [&str; 8] = [
+    ".tmp",
+    ".temp",
+    ".part",
+    ".partial",
+    ".crdownload",
+    ".download",
+    ".swp",
+    "~",
+];
+// Начала имен файлов-блокировок и временных копий офисных программ
+const TEMP_PREFIXES: [&str; 3] = ["~$", ".~", ".#"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Created,
+    Modified,
+    Deleted,
+    Renamed,
+}
+
+impl ChangeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeKind::Created => "created",
+            ChangeKind::Modified => "modified",
+            ChangeKind::Deleted => "deleted",
+            ChangeKind::Renamed => "renamed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogicalChange {
+    pub kind: ChangeKind,
+    pub path: PathBuf,
+    // Прежний путь для Renamed
+    pub old_path: Option<PathBuf>,
+}
+
+struct PathState {
+    existed_before: bool,
+    last_event: Instant,
+}
+
+pub struct ChangeBatcher {
+    // Файлы, которые существовали после последней выдачи изменений
+    known: HashSet<PathBuf>,
+    extensions: Vec<String>,
+    paths: HashMap<PathBuf, PathState>,
+    // Половинки переименований, ждущие пару (путь, cookie inotify)
+    pending_from: Vec<(PathBuf, Option<usize>)>,
+    renames: Vec<(PathBuf, PathBuf)>,
+}
+
+impl ChangeBatcher {
+    // known - файлы в папке на момент начала отслеживания
+    pub fn new(known: impl IntoIterator<Item = PathBuf>, extensions: Vec<String>) -> Self {
+        ChangeBatcher {
+            known: known.into_iter().collect(),
+            extensions,
+            paths: HashMap::new(),
+            pending_from: Vec::new(),
+            renames: Vec::new(),
+        }
+    }
+
+    // Файлы, добавленные к отслеживанию после создания батчера
+    pub fn add_known(&mut self, paths: impl IntoIterator<Item = PathBuf>) {
+        self.known.extend(paths);
+    }
+
+    fn touch(&mut self, path: &Path, now: Instant) {
+        let existed_before = self.known.contains(path);
+        self.paths
+            .entry(path.to_path_buf())
+            .or_insert(PathState {
+                existed_before,
+                last_event: now,
+            })
+            .last_event = now;
+    }
+
+    fn add_rename(&mut self, from: PathBuf, to: PathBuf) {
+        if from != to && !self.renames.iter().any(|(f, t)| *f == from && *t == to) {
+            self.renames.push((from, to));
+        }
+    }
+
+    pub fn push(&mut self, event: &Event, now: Instant) {
+        for path in &event.paths {
+            self.touch(path, now);
+        }
+
+        let rename = match event.kind {
+            EventKind::Modify(ModifyKind::Name(mode)) => mode,
+            _ => return,
+        };
+        let tracker = event.attrs.tracker();
+
+        // FSEvents не говорит, какая это половинка: существующий путь - новое имя
+        let rename = match (rename, event.paths.as_slice()) {
+            (RenameMode::Any, [path]) if path.exists() => RenameMode::To,
+            (RenameMode::Any, [_]) => RenameMode::From,
+            _ => rename,
+        };
+
+        match (rename, event.paths.as_slice()) {
+            (RenameMode::Both, [from, to]) => self.add_rename(from.clone(), to.clone()),
+            (RenameMode::From, [from]) => self.pending_from.push((from.clone(), tracker)),
+            (RenameMode::To, [to]) => {
+                // Пара по cookie, а без него (Windows, macOS) - последняя половинка From
+                let position = match tracker {
+                    Some(_) => self.pending_from.iter().position(|(_, t)| *t == tracker),
+                    None => self.pending_from.iter().rposition(|(_, t)| t.is_none()),
+                };
+                if let Some(position) = position {
+                    let (from, _) = self.pending_from.remove(position);
+                    self.add_rename(from, to.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Есть ли пути, изменения по которым еще не выданы
+    pub fn is_idle(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    // exists - есть ли файл сейчас (в работе - Path::is_file)
+    pub fn flush(&mut self, now: Instant, exists: impl Fn(&Path) -> bool) -> Vec<LogicalChange> {
+        let mut ready: HashSet<PathBuf> = self
+            .paths
+            .iter()
+            .filter(|(_, state)| now.duration_since(state.last_event) >= DEBOUNCE)
+            .map(|(path, _)| path.clone())
+            .collect();
+
+        // Обе стороны переименования выдаются вместе, иначе пару не собрать
+        loop {
+            let split: Vec<&(PathBuf, PathBuf)> = self
+                .renames
+                .iter()
+                .filter(|(from, to)| ready.contains(from) != ready.contains(to))
+                .collect();
+            if split.is_empty() {
+                break;
+            }
+            for (from, to) in split {
+                ready.remove(from);
+                ready.remove(to);
+            }
+        }
+        if ready.is_empty() {
+            return Vec::new();
+        }
+
+        let mut changes = Vec::new();
+        let mut handled: HashSet<PathBuf> = HashSet::new();
+        let renames: Vec<(PathBuf, PathBuf)> = self
+            .renames
+            .iter()
+            .filter(|(from, _)| ready.contains(from))
+            .cloned()
+            .collect();
+
+        // Цепочки a -> b -> c сворачиваем в одно переименование a -> c
+        for (start, _) in renames
+            .iter()
+            .filter(|(from, _)| !renames.iter().any(|(_, to)| to == from))
+        {
+            let mut chain = vec![start.clone()];
+            while let Some((_, next)) = renames.iter().find(|(from, _)| Some(from) == chain.last())
+            {
+                if chain.contains(next) {
+                    break;
+                }
+                chain.push(next.clone());
+            }
+
+            let last = chain.last().unwrap_or(start);
+            let existed_before = self.paths.get(start).is_some_and(|s| s.existed_before);
+            if existed_before && !exists(start) && exists(last) && !is_temp_file(start) {
+                if let Some(change) = self.renamed(start, last) {
+                    changes.push(change);
+                }
+                // Промежуточные (временные) имена тоже обработаны
+                handled.extend(chain);
+            }
+        }
+
+        let mut paths: Vec<&PathBuf> = ready.iter().filter(|p| !handled.contains(*p)).collect();
+        paths.sort();
+        for path in paths {
+            let existed_before = self.paths.get(path).is_some_and(|s| s.existed_before);
+            let kind = match (existed_before, exists(path)) {
+                (false, true) => ChangeKind::Created,
+                (true, true) => ChangeKind::Modified,
+                (true, false) => ChangeKind::Deleted,
+                // Файл появился и исчез внутри пачки - временный
+                (false, false) => continue,
+            };
+            if self.reported(path) {
+                changes.push(LogicalChange {
+                    kind,
+                    path: path.clone(),
+                    old_path: None,
+                });
+            }
+        }
+
+        for path in &ready {
+            self.paths.remove(path);
+            if exists(path) {
+                self.known.insert(path.clone());
+            } else {
+                self.known.remove(path);
+            }
+        }
+        self.renames
+            .retain(|(from, to)| !ready.contains(from) && !ready.contains(to));
+        self.pending_from.retain(|(from, _)| !ready.contains(from));
+
+        changes
+    }
+
+    // Переименование с фильтром расширений: сменили .pdf на другое - для нас файл удален
+    fn renamed(&self, from: &Path, to: &Path) -> Option<LogicalChange> {
+        let change = |kind, path: &Path, old_path: Option<&Path>| LogicalChange {
+            kind,
+            path: path.to_path_buf(),
+            old_path: old_path.map(Path::to_path_buf),
+        };
+
+        match (self.reported(from), self.reported(to)) {
+            (true, true) => Some(change(ChangeKind::Renamed, to, Some(from))),
+            (true, false) => Some(change(ChangeKind::Deleted, from, None)),
+            (false, true) if self.known.contains(to) => {
+                Some(change(ChangeKind::Modified, to, None))
+            }
+            (false, true) => Some(change(ChangeKind::Created, to, None)),
+            (false, false) => None,
+        }
+    }
+
+    fn reported(&self, path: &Path) -> bool {
+        matches_extension(path, &self.extensions) && !is_temp_file(path)
+    }
+}
+
+// Пустой список расширений - сообщаем обо всех файлах
+pub fn matches_extension(path: &Path, extensions: &[String]) -> bool {
+    extensions.is_empty()
+        || path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| extensions.contains(&e.to_lowercase()))
+}
+
+// Временные файлы программ, недокачанные файлы и наши резервные копии
+pub fn is_temp_file(path: &Path) -> bool {
+    if path.components().any(|c| c.as_os_str() == BACKUP_DIR) {
+        return true;
+    }
+
+    let name = match path.file_name() {
+        Some(name) => name.to_string_lossy().to_lowercase(),
+        None => return false,
+    };
+    TEMP_SUFFIXES.iter().any(|suffix| name.ends_with(suffix))
+        || TEMP_PREFIXES.iter().any(|prefix| name.starts_with(prefix))
+}
+
+// Файлы папки на момент начала отслеживания - от них считается, был ли файл до события
+pub fn list_files(dir: &Path, recursive: bool) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            match entry.file_type() {
+                Ok(file_type) if file_type.is_dir() && recursive => dirs.push(path),
+                Ok(file_type) if file_type.is_file() => files.push(path),
+                _ => {}
+            }
+        }
+    }
+
+    files
+}
@@ -0,0 +1,327 @@
+// Преобразование аннотаций в XFDF (XML Forms Data Format) и обратно
+
+use anyhow::Context;
+use quick_xml::escape::{escape, resolve_predefined_entity};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, ErrorKind, Result};
+use super::annotations::{read_annotations, Annotation};
+use super::pdf_metadata::load_pdf;
+
+// Имена элементов XFDF и соответствующие им Subtype в PDF
+const SUBTYPES: [(&str, &str); 16] = [
+    ("highlight", "Highlight"),
+    ("underline", "Underline"),
+    ("strikeout", "StrikeOut"),
+    ("squiggly", "Squiggly"),
+    ("text", "Text"),
+    ("freetext", "FreeText"),
+    ("square", "Square"),
+    ("circle", "Circle"),
+    ("line", "Line"),
+    ("polygon", "Polygon"),
+    ("polyline", "PolyLine"),
+    ("ink", "Ink"),
+    ("stamp", "Stamp"),
+    ("caret", "Caret"),
+    ("fileattachment", "FileAttachment"),
+    ("sound", "Sound"),
+];
+
+// Биты поля /F и их имена в атрибуте flags
+const FLAGS: [(i64, &str); 10] = [
+    (1, "invisible"),
+    (2, "hidden"),
+    (4, "print"),
+    (8, "nozoom"),
+    (16, "norotate"),
+    (32, "noview"),
+    (64, "readonly"),
+    (128, "locked"),
+    (256, "togglenoview"),
+    (512, "lockedcontents"),
+];
+
+pub fn annotations_to_xfdf(annotations: &[Annotation], pdf_file_name: Option<&str>) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<xfdf xmlns=\"http://ns.adobe.com/xfdf/\" xml:space=\"preserve\">\n");
+
+    if let Some(name) = pdf_file_name {
+        xml.push_str(&format!("  <f href=\"{}\"/>\n", escape(name)));
+    }
+
+    xml.push_str("  <annots>\n");
+
+    for annotation in annotations {
+        // Неизвестные подтипы XFDF не описывает - пропускаем
+        let element = match SUBTYPES.iter().find(|(_, pdf)| *pdf == annotation.subtype) {
+            Some((element, _)) => *element,
+            None => continue,
+        };
+
+        let mut attrs = vec![
+            ("page", (annotation.page.saturating_sub(1)).to_string()),
+            ("rect", join_numbers(&annotation.rect)),
+        ];
+
+        if let Some(color) = annotation.color.as_deref().and_then(color_to_hex) {
+            attrs.push(("color", color));
+        }
+        if let Some(author) = &annotation.author {
+            attrs.push(("title", author.clone()));
+        }
+        if let Some(name) = &annotation.name {
+            attrs.push(("name", name.clone()));
+        }
+        if let Some(modified) = &annotation.modified {
+            attrs.push(("date", modified.clone()));
+        }
+        if let Some(created) = &annotation.created {
+            attrs.push(("creationdate", created.clone()));
+        }
+        if let Some(opacity) = annotation.opacity {
+            attrs.push(("opacity", opacity.to_string()));
+        }
+        if let Some(flags) = annotation.flags {
+            attrs.push(("flags", flags_to_names(flags)));
+        }
+        if let Some(quad_points) = &annotation.quad_points {
+            attrs.push(("coords", join_numbers(quad_points)));
+        }
+
+        xml.push_str(&format!("    <{}", element));
+        for (key, value) in attrs {
+            xml.push_str(&format!(" {}=\"{}\"", key, escape(value.as_str())));
+        }
+
+        match &annotation.contents {
+            Some(contents) => {
+                xml.push_str(">\n");
+                xml.push_str(&format!("      <contents>{}</contents>\n", escape(contents.as_str())));
+                xml.push_str(&format!("    </{}>\n", element));
+            }
+            None => xml.push_str("/>\n"),
+        }
+    }
+
+    xml.push_str("  </annots>\n</xfdf>\n");
+    xml
+}
+
+// Без output пишем sidecar рядом с PDF: book.pdf -> book.xfdf. Возвращает путь записанного файла
+pub fn export_xfdf(pdf_path: &Path, output: Option<&Path>) -> Result<PathBuf> {
+    let (doc, _) = load_pdf(pdf_path)?;
+    let annotations = read_annotations(&doc)?;
+
+    let file_name = pdf_path.file_name().map(|s| s.to_string_lossy().into_owned());
+    let xml = annotations_to_xfdf(&annotations, file_name.as_deref());
+
+    let output = output.map_or_else(|| pdf_path.with_extension("xfdf"), Path::to_path_buf);
+    fs::write(&output, xml)
+        .with_context(|| format!("Failed to write XFDF: {}", output.display()))?;
+    Ok(output)
+}
+
+pub fn xfdf_to_annotations(xml: &str) -> Result<Vec<Annotation>> {
+    let mut reader = Reader::from_str(xml);
+
+    let mut annotations = Vec::new();
+    let mut in_annots = false;
+    let mut current: Option<Annotation> = None;
+    let mut contents: Option<String> = None;
+
+    loop {
+        match reader.read_event().context("Failed to parse XFDF")? {
+            Event::Start(e) => {
+                let tag = local_name(&e);
+                if tag == "annots" {
+                    in_annots = true;
+                } else if tag == "contents" && current.is_some() {
+                    contents = Some(String::new());
+                } else if in_annots && current.is_none() {
+                    current = parse_annotation(&e)?;
+                }
+            }
+            Event::Empty(e) if in_annots && current.is_none() => {
+                if let Some(annotation) = parse_annotation(&e)? {
+                    annotations.push(annotation);
+                }
+            }
+            Event::Text(e) => {
+                if let Some(text) = contents.as_mut() {
+                    text.push_str(&e.decode().context("Failed to decode XFDF text")?);
+                }
+            }
+            Event::GeneralRef(e) => {
+                if let Some(text) = contents.as_mut() {
+                    if let Some(ch) = e.resolve_char_ref().ok().flatten() {
+                        text.push(ch);
+                    } else if let Some(entity) =
+                        resolve_predefined_entity(&e.decode().unwrap_or_default())
+                    {
+                        text.push_str(entity);
+                    }
+                }
+            }
+            Event::End(e) => {
+                let tag = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+                if tag == "annots" {
+                    in_annots = false;
+                } else if tag == "contents" {
+                    if let (Some(annotation), Some(text)) = (current.as_mut(), contents.take()) {
+                        annotation.contents = Some(text).filter(|s| !s.is_empty());
+                    }
+                } else if current
+                    .as_ref()
+                    .is_some_and(|a| tag == element_for(&a.subtype))
+                {
+                    annotations.extend(current.take());
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(annotations)
+}
+
+fn parse_annotation(e: &BytesStart) -> Result<Option<Annotation>> {
+    let tag = local_name(e);
+    let subtype = match SUBTYPES.iter().find(|(element, _)| *element == tag) {
+        Some((_, pdf)) => pdf.to_string(),
+        None => return Ok(None),
+    };
+
+    let mut annotation = Annotation {
+        page: 1,
+        subtype,
+        rect: [0.0; 4],
+        quad_points: None,
+        color: None,
+        contents: None,
+        author: None,
+        name: None,
+        modified: None,
+        created: None,
+        opacity: None,
+        flags: None,
+    };
+
+    for attr in e.attributes().flatten() {
+        let key = String::from_utf8_lossy(attr.key.local_name().as_ref()).to_string();
+        let value = attr.unescape_value().context("Failed to read XFDF attribute")?;
+
+        match key.as_str() {
+            // В XFDF страницы с нуля; без номера аннотация не попала бы на свою страницу
+            "page" => {
+                annotation.page = value
+                    .trim()
+                    .parse::<u32>()
+                    .ok()
+                    .and_then(|page| page.checked_add(1))
+                    .ok_or_else(|| {
+                        Error::new(
+                            ErrorKind::InvalidInput,
+                            format!("Invalid page '{}' in XFDF", value),
+                        )
+                    })?
+            }
+            "rect" => {
+                let numbers = parse_numbers(&value);
+                if numbers.len() != 4 {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("Invalid rect '{}' in XFDF", value),
+                    ));
+                }
+                annotation.rect = [numbers[0], numbers[1], numbers[2], numbers[3]];
+            }
+            "coords" => annotation.quad_points = Some(parse_numbers(&value)),
+            "color" => annotation.color = hex_to_color(&value),
+            "title" => annotation.author = Some(value.to_string()),
+            "name" => annotation.name = Some(value.to_string()),
+            "date" => annotation.modified = Some(value.to_string()),
+            "creationdate" => annotation.created = Some(value.to_string()),
+            "opacity" => annotation.opacity = value.trim().parse().ok(),
+            "flags" => annotation.flags = Some(names_to_flags(&value)),
+            _ => {}
+        }
+    }
+
+    Ok(Some(annotation))
+}
+
+fn local_name(e: &BytesStart) -> String {
+    String::from_utf8_lossy(e.local_name().as_ref()).to_string()
+}
+
+fn element_for(subtype: &str) -> &'static str {
+    SUBTYPES
+        .iter()
+        .find(|(_, pdf)| *pdf == subtype)
+        .map(|(element, _)| *element)
+        .unwrap_or("")
+}
+
+fn join_numbers(values: &[f32]) -> String {
+    values
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn parse_numbers(value: &str) -> Vec<f32> {
+    value
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter_map(|part| part.trim().parse().ok())
+        .collect()
+}
+
+// XFDF хранит цвет как #RRGGBB, поэтому серый и CMYK приводим к RGB
+fn color_to_hex(color: &[f32]) -> Option<String> {
+    let (r, g, b) = match color {
+        [gray] => (*gray, *gray, *gray),
+        [r, g, b] => (*r, *g, *b),
+        [c, m, y, k] => ((1.0 - c) * (1.0 - k), (1.0 - m) * (1.0 - k), (1.0 - y) * (1.0 - k)),
+        _ => return None,
+    };
+
+    let to_byte = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+    Some(format!("#{:02X}{:02X}{:02X}", to_byte(r), to_byte(g), to_byte(b)))
+}
+
+fn hex_to_color(value: &str) -> Option<Vec<f32>> {
+    let hex = value.trim().trim_start_matches('#');
+    if hex.len() != 6 {
+        return None;
+    }
+
+    (0..3)
+        .map(|i| {
+            u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+                .ok()
+                .map(|b| b as f32 / 255.0)
+        })
+        .collect()
+}
+
+fn flags_to_names(flags: i64) -> String {
+    FLAGS
+        .iter()
+        .filter(|(bit, _)| flags & bit != 0)
+        .map(|(_, name)| *name)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn names_to_flags(value: &str) -> i64 {
+    value
+        .split(',')
+        .filter_map(|name| FLAGS.iter().find(|(_, n)| *n == name.trim()))
+        .fold(0, |acc, (bit, _)| acc | bit)
+}
@@ -0,0 +1,110 @@
+// Annotation history keyed by document across copies, moves and deletions
+
+mod common;
+
+use anomad_core::annotation_history::{AnnotationHistory, HistoryAction};
+use anomad_core::{get_file_highlights, Highlight};
+use common::*;
+use lopdf::{Object, StringFormat};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// PDF с /ID в трейлере и заметками notes на первой странице
+fn pdf_with_id(path: &Path, notes: &[&str]) {
+    let mut pdf = PdfBuilder::new();
+    let annots = notes
+        .iter()
+        .map(|note| pdf.annot(with(annotation("Text"), "Contents", literal(note))))
+        .collect();
+    pdf.page(Some(Object::Array(annots)));
+    let id = Object::String(b"anomad-history-id".to_vec(), StringFormat::Hexadecimal);
+    pdf.doc.trailer.set("ID", vec![id.clone(), id]);
+    pdf.save(path);
+}
+
+fn extract(path: &Path) -> Vec<Highlight> {
+    get_file_highlights(path.to_str().unwrap()).unwrap()
+}
+
+fn actions(history: &AnnotationHistory, path: &Path) -> Vec<HistoryAction> {
+    let document = history.document_key(path);
+    history
+        .timeline(Some(&document), None, None)
+        .into_iter()
+        .map(|event| event.action)
+        .collect()
+}
+
+fn history_in(dir: &TestDir) -> (AnnotationHistory, PathBuf) {
+    let file = dir.file("history.json");
+    (AnnotationHistory::load(file.clone()), file)
+}
+
+#[test]
+fn copies_sharing_trailer_id_have_separate_histories() {
+    let dir = TestDir::new("history-copies");
+    let original = dir.file("original.pdf");
+    let copy = dir.file("copy.pdf");
+    pdf_with_id(&original, &["first"]);
+    fs::copy(&original, &copy).unwrap();
+    let (mut history, _) = history_in(&dir);
+
+    history.record(&original, &extract(&original)).unwrap();
+    history.record(&copy, &extract(&copy)).unwrap();
+    pdf_with_id(&copy, &["first", "only in copy"]);
+    history.record(&copy, &extract(&copy)).unwrap();
+    // Исходник снова извлечен без изменений - копия не должна подменить его историю
+    assert_eq!(history.record(&original, &extract(&original)).unwrap(), 0);
+
+    assert_ne!(history.document_key(&original), history.document_key(&copy));
+    assert_eq!(actions(&history, &original), vec![HistoryAction::Added]);
+    assert_eq!(
+        actions(&history, &copy),
+        vec![HistoryAction::Added, HistoryAction::Added]
+    );
+}
+
+#[test]
+fn moved_file_keeps_its_history() {
+    let dir = TestDir::new("history-moved");
+    let before = dir.file("before.pdf");
+    let after = dir.file("after.pdf");
+    pdf_with_id(&before, &["note"]);
+    let (mut history, file) = history_in(&dir);
+    history.record(&before, &extract(&before)).unwrap();
+    let document = history.document_key(&before);
+
+    fs::rename(&before, &after).unwrap();
+    let history = AnnotationHistory::load(file);
+    assert_eq!(history.document_key(&after), document);
+
+    // На старом месте появился другой документ - у него своя история
+    let mut history = history;
+    history.record(&after, &extract(&after)).unwrap();
+    pdf_with_id(&before, &["another"]);
+    history.record(&before, &extract(&before)).unwrap();
+    assert_ne!(history.document_key(&before), document);
+    assert_eq!(actions(&history, &after), vec![HistoryAction::Added]);
+    assert_eq!(actions(&history, &before), vec![HistoryAction::Added]);
+}
+
+#[test]
+fn removing_every_highlight_is_recorded() {
+    let dir = TestDir::new("history-emptied");
+    let path = dir.file("book.pdf");
+    pdf_with_id(&path, &["one", "two"]);
+    let (mut history, _) = history_in(&dir);
+    history.record(&path, &extract(&path)).unwrap();
+
+    pdf_with_id(&path, &[]);
+    assert_eq!(history.record(&path, &extract(&path)).unwrap(), 2);
+    assert_eq!(
+        actions(&history, &path),
+        vec![
+            HistoryAction::Added,
+            HistoryAction::Added,
+            HistoryAction::Deleted,
+            HistoryAction::Deleted,
+        ]
+    );
+}
@@ -0,0 +1,111 @@
+// Merging annotations from copies: duplicates, new annotations and conflicts
+
+use anomad_core::annotation_merge::{plan_merge, TARGET_SOURCE};
+use anomad_core::annotations::Annotation;
+
+fn note(page: u32, rect: [f32; 4], contents: Option<&str>) -> Annotation {
+    Annotation {
+        page,
+        subtype: "Text".to_string(),
+        rect,
+        quad_points: None,
+        color: None,
+        contents: contents.map(str::to_string),
+        author: None,
+        name: None,
+        modified: None,
+        created: None,
+        opacity: None,
+        flags: None,
+    }
+}
+
+const RECT: [f32; 4] = [72.0, 700.0, 92.0, 720.0];
+
+fn source(name: &str, annotations: Vec<Annotation>) -> (String, Vec<Annotation>) {
+    (name.to_string(), annotations)
+}
+
+#[test]
+fn same_annotation_is_skipped_despite_rounding_and_spaces() {
+    let existing = vec![note(1, RECT, Some("idea"))];
+    let copy = note(1, [72.04, 699.96, 92.0, 720.0], Some("  idea\n"));
+
+    let plan = plan_merge(&existing, &[source("copy.pdf", vec![copy])]);
+    assert!(plan.to_add.is_empty());
+    assert_eq!(plan.duplicates, 1);
+    assert!(plan.conflicts.is_empty());
+}
+
+#[test]
+fn new_annotation_is_added_once_from_several_copies() {
+    let new = note(2, RECT, Some("new"));
+    // Пустой текст и отсутствие текста - одно и то же
+    let empty = note(1, RECT, Some(" "));
+    let plan = plan_merge(
+        &[note(1, RECT, None)],
+        &[
+            source("first.pdf", vec![new.clone(), empty]),
+            source("second.pdf", vec![new.clone()]),
+        ],
+    );
+
+    assert_eq!(plan.to_add, vec![new]);
+    assert_eq!(plan.duplicates, 2);
+    assert!(plan.conflicts.is_empty());
+}
+
+#[test]
+fn different_text_in_same_place_is_conflict() {
+    let existing = vec![note(1, RECT, Some("target"))];
+    let other_place = [72.0, 600.0, 92.0, 620.0];
+    let plan = plan_merge(
+        &existing,
+        &[
+            source(
+                "first.pdf",
+                vec![
+                    note(1, RECT, Some("first")),
+                    note(3, other_place, Some("a")),
+                ],
+            ),
+            source("second.pdf", vec![note(3, other_place, Some("b"))]),
+        ],
+    );
+
+    // Между копиями конфликт тоже есть, но первая аннотация добавляется
+    assert_eq!(plan.to_add, vec![note(3, other_place, Some("a"))]);
+    assert_eq!(plan.duplicates, 0);
+    let conflicts: Vec<String> = plan
+        .conflicts
+        .iter()
+        .map(|conflict| {
+            let variants: Vec<String> = conflict
+                .variants
+                .iter()
+                .map(|v| format!("{}={}", v.source, v.contents.as_deref().unwrap_or("")))
+                .collect();
+            format!("{}: {}", conflict.page, variants.join(", "))
+        })
+        .collect();
+    assert_eq!(
+        conflicts,
+        vec![
+            format!("1: {}=target, first.pdf=first", TARGET_SOURCE),
+            "3: first.pdf=a, second.pdf=b".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn differences_inside_target_are_not_reported() {
+    let existing = vec![note(1, RECT, Some("one")), note(1, RECT, Some("two"))];
+    let plan = plan_merge(
+        &existing,
+        &[source("copy.pdf", vec![note(1, RECT, Some("two"))])],
+    );
+
+    assert!(plan.to_add.is_empty());
+    assert_eq!(plan.duplicates, 1);
+    assert!(plan.conflicts.is_empty());
+}
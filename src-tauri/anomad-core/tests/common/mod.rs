@@ -0,0 +1,298 @@
+// Test PDFs generated with lopdf
+
+#![allow(dead_code)]
+
+use lopdf::{dictionary, Dictionary, Document, Object, ObjectId, Stream, StringFormat};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Все подтипы аннотаций из спецификации PDF 2.0
+pub const ALL_SUBTYPES: [&str; 28] = [
+    "Text",
+    "Link",
+    "FreeText",
+    "Line",
+    "Square",
+    "Circle",
+    "Polygon",
+    "PolyLine",
+    "Highlight",
+    "Underline",
+    "Squiggly",
+    "StrikeOut",
+    "Caret",
+    "Stamp",
+    "Ink",
+    "Popup",
+    "FileAttachment",
+    "Sound",
+    "Movie",
+    "Screen",
+    "Widget",
+    "PrinterMark",
+    "TrapNet",
+    "Watermark",
+    "3D",
+    "Redact",
+    "Projection",
+    "RichMedia",
+];
+
+pub const DATE: &str = "D:20240115103000+03'00'";
+
+// Временная папка теста, удаляется вместе с файлами
+pub struct TestDir(PathBuf);
+
+impl TestDir {
+    pub fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("anomad-core-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        TestDir(dir)
+    }
+
+    pub fn file(&self, name: &str) -> PathBuf {
+        self.0.join(name)
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+pub fn literal(text: &str) -> Object {
+    Object::String(text.as_bytes().to_vec(), StringFormat::Literal)
+}
+
+pub fn hex(text: &str) -> Object {
+    Object::String(text.as_bytes().to_vec(), StringFormat::Hexadecimal)
+}
+
+// UTF-16BE с маркером FE FF
+pub fn utf16(text: &str) -> Object {
+    let mut bytes = vec![0xFE, 0xFF];
+    bytes.extend(text.encode_utf16().flat_map(|unit| unit.to_be_bytes()));
+    Object::String(bytes, StringFormat::Literal)
+}
+
+// Строка в PDFDocEncoding как есть, байт на символ
+pub fn pdf_doc(bytes: &[u8]) -> Object {
+    Object::String(bytes.to_vec(), StringFormat::Literal)
+}
+
+pub fn numbers(values: &[f32]) -> Object {
+    Object::Array(values.iter().map(|v| Object::Real(*v)).collect())
+}
+
+// Аннотация с минимальным набором полей; Contents и цвет задаются в тесте
+pub fn annotation(subtype: &str) -> Dictionary {
+    dictionary! {
+        "Type" => "Annot",
+        "Subtype" => subtype,
+        "Rect" => numbers(&[72.0, 690.0, 240.0, 712.0]),
+        "M" => literal(DATE),
+    }
+}
+
+pub fn with(mut dict: Dictionary, key: &str, value: Object) -> Dictionary {
+    dict.set(key, value);
+    dict
+}
+
+pub struct PdfBuilder {
+    pub doc: Document,
+    pages_id: ObjectId,
+    kids: Vec<Object>,
+}
+
+impl PdfBuilder {
+    pub fn new() -> Self {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        PdfBuilder {
+            doc,
+            pages_id,
+            kids: Vec::new(),
+        }
+    }
+
+    // Страница с одной строкой текста; annots - значение /Annots (None - без ключа)
+    pub fn page(&mut self, annots: Option<Object>) -> ObjectId {
+        self.page_with_content(b"BT /F1 12 Tf 72 700 Td (Sample text) Tj ET", annots)
+    }
+
+    pub fn page_with_content(&mut self, content: &[u8], annots: Option<Object>) -> ObjectId {
+        let font_id = self.doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+        });
+        let content_id = self
+            .doc
+            .add_object(Stream::new(dictionary! {}, content.to_vec()));
+
+        let mut page = dictionary! {
+            "Type" => "Page",
+            "Parent" => self.pages_id,
+            "Contents" => content_id,
+            "MediaBox" => numbers(&[0.0, 0.0, 612.0, 792.0]),
+            "Resources" => dictionary! { "Font" => dictionary! { "F1" => font_id } },
+        };
+        if let Some(annots) = annots {
+            page.set("Annots", annots);
+        }
+        let page_id = self.doc.add_object(page);
+        self.kids.push(Object::Reference(page_id));
+        page_id
+    }
+
+    // Аннотация отдельным объектом, в /Annots кладется ссылка
+    pub fn annot(&mut self, dict: Dictionary) -> Object {
+        Object::Reference(self.doc.add_object(dict))
+    }
+
+    pub fn object(&mut self, object: Object) -> Object {
+        Object::Reference(self.doc.add_object(object))
+    }
+
+    pub fn info(&mut self, info: Dictionary) {
+        let info_id = self.doc.add_object(info);
+        self.doc.trailer.set("Info", info_id);
+    }
+
+    // Постоянный /ID документа в трейлере; копии и новые версии файла сохраняют его
+    pub fn document_id(&mut self, id: &str) {
+        let id = Object::String(id.as_bytes().to_vec(), StringFormat::Hexadecimal);
+        self.doc.trailer.set("ID", vec![id.clone(), id]);
+    }
+
+    fn finish(&mut self) {
+        let count = self.kids.len() as i64;
+        self.doc.objects.insert(
+            self.pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => self.kids.clone(),
+                "Count" => count,
+            }),
+        );
+        if self.doc.trailer.get(b"Root").is_err() {
+            let catalog_id = self.doc.add_object(dictionary! {
+                "Type" => "Catalog",
+                "Pages" => self.pages_id,
+            });
+            self.doc.trailer.set("Root", catalog_id);
+        }
+    }
+
+    // Каталог создается заранее, чтобы в него можно было добавить /Metadata или /MarkInfo
+    pub fn catalog(&mut self) -> &mut Dictionary {
+        self.finish();
+        self.doc.catalog_mut().unwrap()
+    }
+
+    pub fn save(mut self, path: &Path) {
+        self.finish();
+        self.doc.save(path).unwrap();
+    }
+
+    // Стандартный обработчик безопасности, RC4 40 бит, пустой пароль пользователя:
+    // так защищено большинство книг, запрещающих печать или копирование
+    pub fn save_encrypted(mut self, path: &Path) {
+        self.finish();
+
+        let file_id = b"anomad-test-id-0".to_vec();
+        let permissions: i32 = -44;
+
+        let owner_key = &md5::compute(pad_password(b"owner"))[..5];
+        let owner_entry = rc4(owner_key, &PASSWORD_PAD);
+
+        let mut key_input = PASSWORD_PAD.to_vec();
+        key_input.extend_from_slice(&owner_entry);
+        key_input.extend_from_slice(&permissions.to_le_bytes());
+        key_input.extend_from_slice(&file_id);
+        let key = md5::compute(key_input)[..5].to_vec();
+        let user_entry = rc4(&key, &PASSWORD_PAD);
+
+        for (&id, object) in self.doc.objects.iter_mut() {
+            encrypt_object(&key, id, object);
+        }
+
+        let encrypt_id = self.doc.add_object(dictionary! {
+            "Filter" => "Standard",
+            "V" => 1,
+            "R" => 2,
+            "Length" => 40,
+            "O" => Object::String(owner_entry, StringFormat::Hexadecimal),
+            "U" => Object::String(user_entry, StringFormat::Hexadecimal),
+            "P" => permissions as i64,
+        });
+        self.doc.trailer.set("Encrypt", encrypt_id);
+        self.doc.trailer.set(
+            "ID",
+            vec![
+                Object::String(file_id.clone(), StringFormat::Hexadecimal),
+                Object::String(file_id, StringFormat::Hexadecimal),
+            ],
+        );
+        self.doc.save(path).unwrap();
+    }
+}
+
+const PASSWORD_PAD: [u8; 32] = [
+    0x28, 0xBF, 0x4E, 0x5E, 0x4E, 0x75, 0x8A, 0x41, 0x64, 0x00, 0x4E, 0x56, 0xFF, 0xFA, 0x01, 0x08,
+    0x2E, 0x2E, 0x00, 0xB6, 0xD0, 0x68, 0x3E, 0x80, 0x2F, 0x0C, 0xA9, 0xFE, 0x64, 0x53, 0x69, 0x7A,
+];
+
+fn pad_password(password: &[u8]) -> Vec<u8> {
+    let mut padded = password.to_vec();
+    padded.extend_from_slice(&PASSWORD_PAD[..32 - password.len()]);
+    padded
+}
+
+fn encrypt_object(key: &[u8], id: ObjectId, object: &mut Object) {
+    let mut object_key = key.to_vec();
+    object_key.extend_from_slice(&id.0.to_le_bytes()[..3]);
+    object_key.extend_from_slice(&id.1.to_le_bytes()[..2]);
+    let object_key = md5::compute(object_key)[..key.len() + 5].to_vec();
+    encrypt_strings(&object_key, object);
+}
+
+fn encrypt_strings(object_key: &[u8], object: &mut Object) {
+    match object {
+        Object::String(bytes, _) => *bytes = rc4(object_key, bytes),
+        Object::Stream(stream) => stream.set_content(rc4(object_key, &stream.content)),
+        Object::Dictionary(dict) => {
+            for (_, value) in dict.iter_mut() {
+                encrypt_strings(object_key, value);
+            }
+        }
+        Object::Array(items) => {
+            for item in items.iter_mut() {
+                encrypt_strings(object_key, item);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn rc4(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut state: Vec<u8> = (0..=255).collect();
+    let mut j = 0usize;
+    for i in 0..256 {
+        j = (j + state[i] as usize + key[i % key.len()] as usize) % 256;
+        state.swap(i, j);
+    }
+
+    let (mut i, mut j) = (0usize, 0usize);
+    data.iter()
+        .map(|byte| {
+            i = (i + 1) % 256;
+            j = (j + state[i] as usize) % 256;
+            state.swap(i, j);
+            byte ^ state[(state[i] as usize + state[j] as usize) % 256]
+        })
+        .collect()
+}
@@ -0,0 +1,75 @@
+// Duplicate groups: byte-identical copies and versions sharing a trailer /ID
+
+mod common;
+
+use anomad_core::duplicates::{group_duplicates, DuplicateFile, DuplicateKind};
+use common::*;
+use lopdf::Object;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn book(path: &Path, document_id: &str, notes: &[&str]) {
+    let mut pdf = PdfBuilder::new();
+    let annots = notes
+        .iter()
+        .map(|note| pdf.annot(with(annotation("Text"), "Contents", literal(note))))
+        .collect();
+    pdf.page(Some(Object::Array(annots)));
+    pdf.document_id(document_id);
+    pdf.save(path);
+}
+
+fn paths(files: &[DuplicateFile]) -> Vec<PathBuf> {
+    files.iter().map(|f| PathBuf::from(&f.path)).collect()
+}
+
+#[test]
+fn copies_and_versions_are_grouped_separately() {
+    let dir = TestDir::new("duplicates");
+    let original = dir.file("original.pdf");
+    let copy = dir.file("copy.pdf");
+    let annotated = dir.file("annotated.pdf");
+    let other = dir.file("other.pdf");
+    book(&original, "shared-document", &["first"]);
+    fs::copy(&original, &copy).unwrap();
+    book(&annotated, "shared-document", &["first", "second"]);
+    book(&other, "other-document", &["first"]);
+
+    // Один и тот же путь дважды - не дубликат самого себя
+    let groups = group_duplicates(&[
+        original.clone(),
+        copy.clone(),
+        annotated.clone(),
+        other,
+        original.clone(),
+    ]);
+
+    assert_eq!(groups.len(), 2, "{:?}", groups);
+    assert_eq!(groups[0].kind, DuplicateKind::Exact);
+    assert_eq!(
+        paths(&groups[0].files),
+        vec![original.clone(), copy.clone()]
+    );
+
+    assert_eq!(groups[1].kind, DuplicateKind::SameDocument);
+    assert_eq!(
+        paths(&groups[1].files),
+        vec![original, copy, annotated.clone()]
+    );
+    assert_eq!(
+        groups[1].most_highlights.as_deref().map(PathBuf::from),
+        Some(annotated)
+    );
+    assert_eq!(groups[1].files[2].highlight_count, 2);
+}
+
+#[test]
+fn lone_files_are_not_duplicates() {
+    let dir = TestDir::new("duplicates-lone");
+    let first = dir.file("first.pdf");
+    let second = dir.file("second.pdf");
+    book(&first, "first-document", &[]);
+    book(&second, "second-document", &["note"]);
+
+    assert!(group_duplicates(&[first, second, dir.file("missing.pdf")]).is_empty());
+}
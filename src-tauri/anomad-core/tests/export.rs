@@ -0,0 +1,80 @@
+// Markdown and XFDF export of generated PDFs
+
+mod common;
+
+use anomad_core::bibtex::BibSource;
+use anomad_core::get_file_highlights;
+use anomad_core::markdown::{format_document, PLAIN_HIGHLIGHT};
+use anomad_core::xfdf::{export_xfdf, xfdf_to_annotations};
+use anomad_core::ErrorKind;
+use common::*;
+use lopdf::Object;
+use std::fs;
+
+fn book(dir: &TestDir) -> std::path::PathBuf {
+    let mut pdf = PdfBuilder::new();
+    let highlight = pdf.annot(annotation("Highlight"));
+    let note = pdf.annot(with(annotation("Text"), "Contents", literal("Заметка")));
+    pdf.page(Some(Object::Array(vec![highlight])));
+    pdf.page(Some(Object::Array(vec![note])));
+    let path = dir.file("book.pdf");
+    pdf.save(&path);
+    path
+}
+
+#[test]
+fn markdown_lists_highlights_without_notes() {
+    let dir = TestDir::new("export-markdown");
+    let path = book(&dir);
+    let highlights = get_file_highlights(path.to_str().unwrap()).unwrap();
+    let source = BibSource {
+        title: Some("Книга".to_string()),
+        author: None,
+        year: None,
+        file_path: path.to_string_lossy().into_owned(),
+        identifiers: Default::default(),
+    };
+
+    let markdown = format_document(&source, &highlights);
+    let items: Vec<&str> = markdown.lines().filter(|l| l.starts_with("- ")).collect();
+    assert_eq!(
+        items,
+        vec![
+            format!("- с. 1: {}", PLAIN_HIGHLIGHT).as_str(),
+            "- с. 2: Заметка"
+        ]
+    );
+}
+
+#[test]
+fn xfdf_is_written_next_to_the_pdf_by_default() {
+    let dir = TestDir::new("export-xfdf");
+    let path = book(&dir);
+
+    let written = export_xfdf(&path, None).unwrap();
+    assert_eq!(written, dir.file("book.xfdf"));
+    let annotations = xfdf_to_annotations(&fs::read_to_string(&written).unwrap()).unwrap();
+    let subtypes: Vec<&str> = annotations.iter().map(|a| a.subtype.as_str()).collect();
+    assert_eq!(subtypes, vec!["Highlight", "Text"]);
+
+    let output = dir.file("custom.xfdf");
+    assert_eq!(export_xfdf(&path, Some(&output)).unwrap(), output);
+    assert!(output.is_file());
+}
+
+#[test]
+fn xfdf_with_malformed_page_is_rejected() {
+    let xfdf = |page: &str| {
+        format!(
+            r#"<xfdf xmlns="http://ns.adobe.com/xfdf/"><annots><text page="{}" rect="0,0,10,10"/></annots></xfdf>"#,
+            page
+        )
+    };
+    let annotations = xfdf_to_annotations(&xfdf(" 2 ")).unwrap();
+    assert_eq!(annotations[0].page, 3);
+
+    for page in ["", "two", "-1", "4294967295"] {
+        let error = xfdf_to_annotations(&xfdf(page)).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput, "{}", error);
+    }
+}
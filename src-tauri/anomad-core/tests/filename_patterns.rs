@@ -0,0 +1,96 @@
+// Filename templates, regex patterns and the order they are tried in
+
+use anomad_core::filename_patterns::{
+    compile_patterns, parse_file_name, FilenamePattern, ParsedFileName, DEFAULT_PATTERNS,
+};
+use anomad_core::ErrorKind;
+use std::collections::BTreeMap;
+
+fn parse(pattern: &str, stem: &str) -> Option<ParsedFileName> {
+    FilenamePattern::parse(pattern)
+        .unwrap()
+        .parse_file_name(stem)
+}
+
+fn parsed(title: &str, author: Option<&str>, year: Option<&str>) -> ParsedFileName {
+    ParsedFileName {
+        title: Some(title.to_string()),
+        author: author.map(str::to_string),
+        year: year.map(str::to_string),
+        fields: BTreeMap::new(),
+    }
+}
+
+#[test]
+fn template_fields_are_trimmed_and_spaces_are_flexible() {
+    assert_eq!(
+        parse(
+            "{author} - {year} - {title}",
+            "Tolstoy  -  1869 - War and Peace "
+        ),
+        Some(parsed("War and Peace", Some("Tolstoy"), Some("1869")))
+    );
+    // {year} - ровно четыре цифры
+    assert_eq!(
+        parse("{author} - {year} - {title}", "Tolstoy - 18xx - War"),
+        None
+    );
+}
+
+#[test]
+fn template_skips_wildcards_and_keeps_custom_fields() {
+    let mut expected = parsed("Notes", None, None);
+    expected
+        .fields
+        .insert("isbn".to_string(), "9780306406157".to_string());
+    assert_eq!(
+        parse("{*}_{isbn}_{title}", "scan 01_9780306406157_Notes"),
+        Some(expected)
+    );
+    // Удвоенные скобки - сами скобки
+    assert_eq!(
+        parse("{{draft}} {title}", "{draft} Notes"),
+        Some(parsed("Notes", None, None))
+    );
+}
+
+#[test]
+fn regex_pattern_skips_empty_groups() {
+    let pattern = r"re:^(?P<year>\d{4})_(?P<author>[^_]*)_(?P<title>.+)$";
+    assert_eq!(
+        parse(pattern, "1869__War and Peace"),
+        Some(parsed("War and Peace", None, Some("1869")))
+    );
+    assert_eq!(parse(pattern, "War and Peace"), None);
+}
+
+#[test]
+fn invalid_patterns_are_rejected() {
+    for pattern in ["{author-name} {title}", "no fields", r"re:^\d+$"] {
+        let error = FilenamePattern::parse(pattern).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput, "{}", error);
+    }
+    assert!(FilenamePattern::parse("re:(?P<title>").is_err());
+}
+
+#[test]
+fn first_matching_compiled_pattern_wins() {
+    // Пустые и некорректные строки настроек пропускаются
+    let patterns = compile_patterns(&[
+        "",
+        "{author-name}",
+        "{author} - {title}",
+        DEFAULT_PATTERNS[0],
+    ]);
+    assert_eq!(patterns.len(), 2);
+
+    assert_eq!(
+        parse_file_name("Tolstoy - War and Peace", &patterns),
+        Some(parsed("War and Peace", Some("Tolstoy"), None))
+    );
+    assert_eq!(
+        parse_file_name("1869 Tolstoy Leo War and Peace", &patterns),
+        Some(parsed("War and Peace", None, None))
+    );
+    assert_eq!(parse_file_name("Untitled", &patterns), None);
+}
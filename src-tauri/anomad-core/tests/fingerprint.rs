@@ -0,0 +1,100 @@
+// File fingerprints: partial content hash, trailer /ID and matching a moved file
+
+mod common;
+
+use anomad_core::fingerprint::{compute_fingerprint, match_fingerprint, FingerprintMatch};
+use common::*;
+use lopdf::dictionary;
+use std::fs;
+use std::path::Path;
+
+fn pdf(path: &Path, title: &str, id: &str) {
+    let mut pdf = PdfBuilder::new();
+    pdf.page(None);
+    pdf.info(dictionary! { "Title" => literal(title) });
+    pdf.document_id(id);
+    pdf.save(path);
+}
+
+#[test]
+fn moved_copy_matches_exactly() {
+    let dir = TestDir::new("fingerprint-moved");
+    let (original, moved) = (dir.file("book.pdf"), dir.file("renamed.pdf"));
+    pdf(&original, "Book", "book");
+    fs::copy(&original, &moved).unwrap();
+
+    let expected = compute_fingerprint(&original).unwrap();
+    let actual = compute_fingerprint(&moved).unwrap();
+    assert_eq!(actual, expected);
+    // hex-строка "book" записывается байтами
+    assert_eq!(expected.trailer_id.as_deref(), Some("626F6F6B"));
+    assert_eq!(
+        match_fingerprint(Some(&expected.content_hash), None, &actual),
+        Some(FingerprintMatch::Exact)
+    );
+}
+
+#[test]
+fn edited_document_matches_by_trailer_id() {
+    let dir = TestDir::new("fingerprint-edited");
+    let (before, after, other) = (
+        dir.file("before.pdf"),
+        dir.file("after.pdf"),
+        dir.file("other.pdf"),
+    );
+    pdf(&before, "Book", "book");
+    pdf(&after, "Book with notes", "book");
+    pdf(&other, "Book", "other");
+
+    let expected = compute_fingerprint(&before).unwrap();
+    let after = compute_fingerprint(&after).unwrap();
+    assert_ne!(after.content_hash, expected.content_hash);
+    // Регистр шестнадцатеричных цифр не важен
+    let trailer_id = expected.trailer_id.unwrap().to_lowercase();
+    assert_eq!(
+        match_fingerprint(Some(&expected.content_hash), Some(&trailer_id), &after),
+        Some(FingerprintMatch::SameDocument)
+    );
+
+    let other = compute_fingerprint(&other).unwrap();
+    assert_eq!(
+        match_fingerprint(Some(&expected.content_hash), Some(&trailer_id), &other),
+        None
+    );
+    // Без /ID остается только точное совпадение
+    assert_eq!(
+        match_fingerprint(Some(&expected.content_hash), None, &after),
+        None
+    );
+}
+
+#[test]
+fn last_trailer_id_wins_after_incremental_save() {
+    let dir = TestDir::new("fingerprint-incremental");
+    let path = dir.file("updated.pdf");
+    let mut bytes = b"%PDF-1.4\ntrailer << /ID [<aa bb> <aabb>] >>\n".to_vec();
+    // Содержимое больше двух кусков: хеш читает только начало и конец
+    bytes.extend(vec![b' '; 200 * 1024]);
+    bytes.extend(b"trailer << /ID [ <CC DD> <EEFF> ] >>\n%%EOF\n");
+    fs::write(&path, &bytes).unwrap();
+
+    let fingerprint = compute_fingerprint(&path).unwrap();
+    assert_eq!(fingerprint.size, bytes.len() as u64);
+    assert_eq!(fingerprint.trailer_id.as_deref(), Some("CCDD"));
+
+    // Середина файла в хеш не входит, конец - входит
+    let middle = 100 * 1024;
+    bytes[middle] = b'x';
+    fs::write(&path, &bytes).unwrap();
+    assert_eq!(
+        compute_fingerprint(&path).unwrap().content_hash,
+        fingerprint.content_hash
+    );
+    let end = bytes.len() - 2;
+    bytes[end] = b'x';
+    fs::write(&path, &bytes).unwrap();
+    assert_ne!(
+        compute_fingerprint(&path).unwrap().content_hash,
+        fingerprint.content_hash
+    );
+}
@@ -0,0 +1,364 @@
+// Highlight extraction from generated PDFs
+
+mod common;
+
+use anomad_core::highlight_diff::diff_highlights;
+use anomad_core::{get_file_highlights, ErrorKind, Highlight};
+use common::*;
+use lopdf::{dictionary, Object};
+
+fn highlight(page: u32, text: Option<&str>, color: Option<Vec<f32>>) -> Highlight {
+    Highlight {
+        page,
+        highlighted_text: String::new(),
+        annotation_text: text.map(str::to_string),
+        date: Some(DATE.to_string()),
+        highlight_type: if text.is_some() {
+            "annotation"
+        } else {
+            "highlight"
+        }
+        .to_string(),
+        color,
+        annotation_id: None,
+    }
+}
+
+// Содержимое пометок без annotation_id - его проверяют отдельные тесты
+fn extract(path: &std::path::Path) -> Vec<Highlight> {
+    extract_with_ids(path)
+        .into_iter()
+        .map(|highlight| Highlight {
+            annotation_id: None,
+            ..highlight
+        })
+        .collect()
+}
+
+fn extract_with_ids(path: &std::path::Path) -> Vec<Highlight> {
+    get_file_highlights(path.to_str().unwrap()).unwrap()
+}
+
+#[test]
+fn only_text_free_text_and_highlight_subtypes_are_extracted() {
+    let dir = TestDir::new("subtypes");
+    let mut pdf = PdfBuilder::new();
+    let annots = ALL_SUBTYPES
+        .iter()
+        .map(|subtype| pdf.annot(with(annotation(subtype), "Contents", literal(subtype))))
+        .collect::<Vec<_>>();
+    pdf.page(Some(Object::Array(annots)));
+    let path = dir.file("subtypes.pdf");
+    pdf.save(&path);
+
+    assert_eq!(
+        extract(&path),
+        vec![
+            highlight(1, Some("Text"), None),
+            highlight(1, Some("FreeText"), None),
+            highlight(1, Some("Highlight"), None),
+        ]
+    );
+}
+
+#[test]
+fn contents_decide_highlight_type() {
+    let dir = TestDir::new("contents");
+    let mut pdf = PdfBuilder::new();
+    let annots = vec![
+        pdf.annot(annotation("Highlight")),
+        pdf.annot(with(
+            annotation("Highlight"),
+            "Contents",
+            literal("  note  \n"),
+        )),
+        pdf.annot(with(annotation("Highlight"), "Contents", literal(" \t\n"))),
+        pdf.annot(with(annotation("Text"), "Contents", literal(""))),
+        // Contents не строкой - как будто его нет
+        pdf.annot(with(annotation("FreeText"), "Contents", Object::Integer(5))),
+    ];
+    pdf.page(Some(Object::Array(annots)));
+    let path = dir.file("contents.pdf");
+    pdf.save(&path);
+
+    assert_eq!(
+        extract(&path),
+        vec![
+            highlight(1, None, None),
+            highlight(1, Some("note"), None),
+            highlight(1, None, None),
+            highlight(1, None, None),
+            highlight(1, None, None),
+        ]
+    );
+}
+
+#[test]
+fn strings_are_decoded_from_every_encoding() {
+    let dir = TestDir::new("encodings");
+    let mut pdf = PdfBuilder::new();
+    let mut utf8_bom = vec![0xEF, 0xBB, 0xBF];
+    utf8_bom.extend_from_slice("Цитата".as_bytes());
+    let texts = [
+        utf16("Заметка 📚"),
+        utf16("ASCII in UTF-16"),
+        Object::String(utf8_bom, lopdf::StringFormat::Literal),
+        literal("Комментарий без BOM"),
+        // "Café déjà vu" в PDFDocEncoding: é и à совпадают с Latin-1
+        pdf_doc(b"Caf\xe9 d\xe9j\xe0 vu"),
+        // Кавычки, тире, лигатура и знак евро в PDFDocEncoding расходятся с Latin-1
+        pdf_doc(b"\x8dfine\x8e \x84 \x93ne, \xa05"),
+        hex("Hex string"),
+    ];
+    let annots = texts
+        .into_iter()
+        .map(|text| pdf.annot(with(annotation("Text"), "Contents", text)))
+        .collect();
+    pdf.page(Some(Object::Array(annots)));
+    let path = dir.file("encodings.pdf");
+    pdf.save(&path);
+
+    assert_eq!(
+        extract(&path),
+        vec![
+            highlight(1, Some("Заметка 📚"), None),
+            highlight(1, Some("ASCII in UTF-16"), None),
+            highlight(1, Some("Цитата"), None),
+            highlight(1, Some("Комментарий без BOM"), None),
+            highlight(1, Some("Café déjà vu"), None),
+            highlight(1, Some("“fine” — ﬁne, €5"), None),
+            highlight(1, Some("Hex string"), None),
+        ]
+    );
+}
+
+#[test]
+fn dates_are_kept_as_written() {
+    let dir = TestDir::new("dates");
+    let mut pdf = PdfBuilder::new();
+    let mut without_date = annotation("Highlight");
+    without_date.remove(b"M");
+    let annots = vec![
+        pdf.annot(without_date),
+        pdf.annot(with(annotation("Highlight"), "M", utf16("D:20230301"))),
+        pdf.annot(with(annotation("Highlight"), "M", literal(""))),
+    ];
+    pdf.page(Some(Object::Array(annots)));
+    let path = dir.file("dates.pdf");
+    pdf.save(&path);
+
+    let dates: Vec<_> = extract(&path).into_iter().map(|h| h.date).collect();
+    assert_eq!(dates, vec![None, Some("D:20230301".to_string()), None]);
+}
+
+#[test]
+fn colors_are_read_from_c_array() {
+    let dir = TestDir::new("colors");
+    let mut pdf = PdfBuilder::new();
+    let colors = [
+        numbers(&[1.0, 0.5, 0.0]),
+        Object::Array(vec![1.into(), 1.into(), 0.into()]),
+        numbers(&[0.0, 0.25, 0.75, 0.1]),
+        numbers(&[0.5]),
+        Object::Array(vec![]),
+        Object::Array(vec![1.into(), "red".into(), 0.into()]),
+        literal("yellow"),
+    ];
+    let annots = colors
+        .into_iter()
+        .map(|color| pdf.annot(with(annotation("Highlight"), "C", color)))
+        .collect();
+    pdf.page(Some(Object::Array(annots)));
+    let path = dir.file("colors.pdf");
+    pdf.save(&path);
+
+    let colors: Vec<_> = extract(&path).into_iter().map(|h| h.color).collect();
+    assert_eq!(
+        colors,
+        vec![
+            Some(vec![1.0, 0.5, 0.0]),
+            Some(vec![1.0, 1.0, 0.0]),
+            Some(vec![0.0, 0.25, 0.75, 0.1]),
+            Some(vec![0.5]),
+            None,
+            None,
+            None,
+        ]
+    );
+}
+
+#[test]
+fn annots_can_be_indirect_and_annotations_direct() {
+    let dir = TestDir::new("indirect");
+    let mut pdf = PdfBuilder::new();
+    pdf.page(None);
+    let first = pdf.annot(with(
+        annotation("Text"),
+        "Contents",
+        literal("by reference"),
+    ));
+    let array = pdf.object(Object::Array(vec![
+        first,
+        Object::Dictionary(with(
+            annotation("Highlight"),
+            "C",
+            numbers(&[0.0, 1.0, 0.0]),
+        )),
+    ]));
+    pdf.page(Some(array));
+    let nested = pdf.object(Object::Array(vec![Object::Dictionary(annotation(
+        "FreeText",
+    ))]));
+    pdf.page(Some(nested));
+    let path = dir.file("indirect.pdf");
+    pdf.save(&path);
+
+    assert_eq!(
+        extract(&path),
+        vec![
+            highlight(2, Some("by reference"), None),
+            highlight(2, None, Some(vec![0.0, 1.0, 0.0])),
+            highlight(3, None, None),
+        ]
+    );
+}
+
+#[test]
+fn broken_annotation_entries_are_skipped() {
+    let dir = TestDir::new("broken-entries");
+    let mut pdf = PdfBuilder::new();
+    let valid = pdf.annot(with(annotation("Text"), "Contents", literal("kept")));
+    let not_a_dictionary = pdf.object(Object::Integer(42));
+    let annots = vec![
+        Object::Reference((9999, 0)),
+        Object::Null,
+        Object::Integer(7),
+        not_a_dictionary,
+        Object::Dictionary(dictionary! { "Type" => "Annot" }),
+        Object::Dictionary(with(annotation("Highlight"), "Subtype", Object::Integer(1))),
+        valid,
+    ];
+    pdf.page(Some(Object::Array(annots)));
+    pdf.page(Some(Object::Null));
+    pdf.page(Some(literal("not an array")));
+    let path = dir.file("broken-entries.pdf");
+    pdf.save(&path);
+
+    assert_eq!(extract(&path), vec![highlight(1, Some("kept"), None)]);
+}
+
+#[test]
+fn broken_annots_reference_fails_the_file() {
+    let dir = TestDir::new("broken-annots");
+
+    let mut pdf = PdfBuilder::new();
+    pdf.page(Some(Object::Reference((9999, 0))));
+    let missing = dir.file("missing-array.pdf");
+    pdf.save(&missing);
+
+    let mut pdf = PdfBuilder::new();
+    let not_an_array = pdf.object(Object::Integer(1));
+    pdf.page(Some(not_an_array));
+    let wrong_type = dir.file("wrong-type.pdf");
+    pdf.save(&wrong_type);
+
+    for path in [missing, wrong_type] {
+        let error = get_file_highlights(path.to_str().unwrap()).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidPdf, "{}", error);
+    }
+}
+
+#[test]
+fn unreadable_files_report_error_kind() {
+    let dir = TestDir::new("unreadable");
+    let garbage = dir.file("garbage.pdf");
+    std::fs::write(&garbage, b"%PDF-1.4\nthis is not a pdf").unwrap();
+
+    let error = get_file_highlights(garbage.to_str().unwrap()).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidPdf, "{}", error);
+
+    let error = get_file_highlights(dir.file("missing.pdf").to_str().unwrap()).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::NotFound, "{}", error);
+}
+
+#[test]
+fn encrypted_file_with_empty_password_is_decrypted() {
+    let dir = TestDir::new("encrypted");
+    let mut pdf = PdfBuilder::new();
+    let annots = vec![
+        pdf.annot(with(annotation("Text"), "Contents", utf16("Зашифровано"))),
+        Object::Dictionary(with(annotation("Highlight"), "Contents", literal("direct"))),
+        pdf.annot(with(
+            annotation("Highlight"),
+            "C",
+            numbers(&[1.0, 1.0, 0.0]),
+        )),
+    ];
+    pdf.page(Some(Object::Array(annots)));
+    let path = dir.file("encrypted.pdf");
+    pdf.save_encrypted(&path);
+
+    assert_eq!(
+        extract(&path),
+        vec![
+            highlight(1, Some("Зашифровано"), None),
+            highlight(1, Some("direct"), None),
+            highlight(1, None, Some(vec![1.0, 1.0, 0.0])),
+        ]
+    );
+}
+
+#[test]
+fn annotation_id_prefers_nm_then_object_number_then_rect() {
+    let dir = TestDir::new("annotation-ids");
+    let mut pdf = PdfBuilder::new();
+    let named = pdf.annot(with(annotation("Highlight"), "NM", literal("uuid-1")));
+    let by_object = pdf.annot(annotation("Highlight"));
+    let object_number = match by_object {
+        Object::Reference((number, generation)) => format!("obj:{} {}", number, generation),
+        _ => unreachable!(),
+    };
+    pdf.page(Some(Object::Array(vec![
+        named,
+        by_object,
+        Object::Dictionary(annotation("Highlight")),
+    ])));
+    let path = dir.file("ids.pdf");
+    pdf.save(&path);
+
+    let ids: Vec<_> = extract_with_ids(&path)
+        .into_iter()
+        .map(|h| h.annotation_id)
+        .collect();
+    assert_eq!(
+        ids,
+        vec![
+            Some("nm:uuid-1".to_string()),
+            Some(object_number),
+            Some("rect:72.00 690.00 240.00 712.00".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn diff_tells_identical_highlights_apart_by_annotation_id() {
+    let identified = |id: &str, text: Option<&str>| Highlight {
+        annotation_id: Some(id.to_string()),
+        ..highlight(1, text, None)
+    };
+    let previous = vec![
+        identified("obj:5 0", None),
+        identified("obj:6 0", None),
+        identified("obj:7 0", None),
+    ];
+    // Из трех одинаковых выделений первое получило заметку, второе удалено
+    let current = vec![
+        identified("obj:5 0", Some("note")),
+        identified("obj:7 0", None),
+    ];
+
+    let diff = diff_highlights(&previous, &current);
+    assert!(diff.added.is_empty(), "{:?}", diff.added);
+    assert_eq!(diff.changed, vec![identified("obj:5 0", Some("note"))]);
+    assert_eq!(diff.removed, vec![identified("obj:6 0", None)]);
+}
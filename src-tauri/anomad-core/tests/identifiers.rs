@@ -0,0 +1,65 @@
+// Identifiers found in free text and BibTeX citation keys
+
+use anomad_core::bibtex::{citation_key, BibSource};
+use anomad_core::identifiers::find_isbns;
+use std::collections::HashSet;
+
+// ISBN-13 находят оба выражения - с префиксом "ISBN" и без него
+fn isbns(text: &str) -> Vec<String> {
+    let mut found = find_isbns(text);
+    found.dedup();
+    found
+}
+
+#[test]
+fn isbn_stops_before_trailing_numbers() {
+    assert_eq!(isbns("ISBN 0-306-40615-2 12"), vec!["0306406152"]);
+    // "0306406152108" сходится по контрольной сумме ISBN-13, но без префикса 978/979
+    assert_eq!(isbns("ISBN 0-306-40615-2 108"), vec!["0306406152"]);
+    assert_eq!(isbns("ISBN: 0306406152 2021"), vec!["0306406152"]);
+    assert_eq!(
+        isbns("ISBN-13 978-0-306-40615-7 12 p."),
+        vec!["9780306406157"]
+    );
+    // Контрольная сумма не сходится ни для одной длины
+    assert!(isbns("ISBN 0-306-40615-3 12").is_empty());
+}
+
+#[test]
+fn citation_keys_stay_unique_past_z() {
+    let source = BibSource {
+        title: Some("The Art of Computer Programming".to_string()),
+        author: Some("Donald Knuth".to_string()),
+        year: Some("1968".to_string()),
+        ..Default::default()
+    };
+
+    let mut used_keys = HashSet::new();
+    let keys: Vec<String> = (0..30)
+        .map(|_| citation_key(&source, &mut used_keys))
+        .collect();
+
+    assert_eq!(used_keys.len(), keys.len());
+    assert_eq!(keys[0], "knuth1968art");
+    assert_eq!(keys[1], "knuth1968arta");
+    assert_eq!(keys[26], "knuth1968artz");
+    assert_eq!(keys[27], "knuth1968artaa");
+    assert_eq!(keys[29], "knuth1968artac");
+}
+
+#[test]
+fn citation_keys_transliterate_cyrillic_and_diacritics() {
+    let key = |author: &str, title: &str| {
+        let source = BibSource {
+            title: Some(title.to_string()),
+            author: Some(author.to_string()),
+            year: Some("1869".to_string()),
+            ..Default::default()
+        };
+        citation_key(&source, &mut HashSet::new())
+    };
+
+    assert_eq!(key("Толстой, Лев", "Война и мир"), "tolstoy1869voyna");
+    assert_eq!(key("Лев Щербаков", "О природе вещей"), "shcherbakov1869prirode");
+    assert_eq!(key("Kurt Gödel", "Über formal unentscheidbare Sätze"), "godel1869uber");
+}
@@ -0,0 +1,44 @@
+// Document identity written without rewriting the PDF
+
+mod common;
+
+use anomad_core::identity::{ensure_identity, read_identity, sidecar_path, IdentityStrategy};
+use anomad_core::safe_write::BACKUP_DIR;
+use common::*;
+use std::fs;
+
+fn plain_pdf(dir: &TestDir) -> std::path::PathBuf {
+    let mut pdf = PdfBuilder::new();
+    pdf.page(None);
+    let path = dir.file("book.pdf");
+    pdf.save(&path);
+    path
+}
+
+#[test]
+fn default_strategy_leaves_pdf_untouched() {
+    let dir = TestDir::new("identity-default");
+    let path = plain_pdf(&dir);
+    let original = fs::read(&path).unwrap();
+
+    let strategy = IdentityStrategy::default();
+    let id = ensure_identity(&path, strategy).unwrap();
+
+    assert_eq!(strategy, IdentityStrategy::Sidecar);
+    assert!(sidecar_path(&path).is_file());
+    assert_eq!(read_identity(&path, strategy).unwrap(), Some(id));
+    assert_eq!(fs::read(&path).unwrap(), original);
+}
+
+#[test]
+fn xmp_without_room_fails_instead_of_rewriting() {
+    let dir = TestDir::new("identity-xmp-no-room");
+    // Без XMP-пакета дописать идентификатор на месте некуда
+    let path = plain_pdf(&dir);
+    let original = fs::read(&path).unwrap();
+
+    let error = ensure_identity(&path, IdentityStrategy::Xmp).unwrap_err();
+    assert!(error.to_string().contains("in place"), "{}", error);
+    assert_eq!(fs::read(&path).unwrap(), original);
+    assert!(!dir.file(BACKUP_DIR).exists());
+}
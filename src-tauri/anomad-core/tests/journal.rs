@@ -0,0 +1,171 @@
+// Undoing journal entries: renames, trash, backups and partially undone batches
+
+mod common;
+
+use anomad_core::journal::{Journal, JournalEntry, Operation, UndoBackup, JOURNAL_FILE};
+use anomad_core::organize::move_file;
+use anomad_core::pdf_metadata::load_pdf;
+use anomad_core::safe_write::save_pdf;
+use anomad_core::trash::move_to_trash;
+use anomad_core::ErrorKind;
+use common::*;
+use lopdf::dictionary;
+use std::fs;
+use std::path::Path;
+
+fn pdf(path: &Path, title: &str) {
+    let mut pdf = PdfBuilder::new();
+    pdf.page(None);
+    pdf.info(dictionary! { "Title" => literal(title) });
+    pdf.save(path);
+}
+
+fn rename(from: &Path, to: &Path) -> Operation {
+    move_file(from, to).unwrap();
+    Operation::Rename {
+        from: from.to_string_lossy().into_owned(),
+        to: to.to_string_lossy().into_owned(),
+    }
+}
+
+fn entry(journal: &Journal, id: &str) -> JournalEntry {
+    journal
+        .recent(usize::MAX)
+        .into_iter()
+        .find(|e| e.id == id)
+        .unwrap()
+}
+
+// Запись в PDF так, как ее делают команды: копия до записи становится операцией
+fn set_title(path: &Path, title: &str) -> Operation {
+    let backup = UndoBackup::create(path).unwrap();
+    let (mut doc, is_encrypted) = load_pdf(path).unwrap();
+    let info = doc.add_object(dictionary! { "Title" => literal(title) });
+    doc.trailer.set("Info", info);
+    save_pdf(&mut doc, path, is_encrypted).unwrap();
+    backup.into_operation().unwrap()
+}
+
+#[test]
+fn rename_is_undone_once() {
+    let dir = TestDir::new("journal-rename");
+    let (before, after) = (dir.file("draft.pdf"), dir.file("final.pdf"));
+    pdf(&before, "Draft");
+    let mut journal = Journal::load(dir.file(JOURNAL_FILE));
+    let id = journal
+        .record("Переименование", vec![rename(&before, &after)])
+        .unwrap()
+        .unwrap();
+
+    assert!(journal.undo(&id).unwrap().is_empty());
+    assert!(before.is_file());
+    assert!(!after.exists());
+
+    // Отметка об отмене сохраняется в файле журнала
+    let mut journal = Journal::load(dir.file(JOURNAL_FILE));
+    assert!(entry(&journal, &id).undone);
+    let error = journal.undo(&id).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidInput, "{}", error);
+    let error = journal.undo("unknown").unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::NotFound, "{}", error);
+}
+
+#[test]
+fn deleted_file_is_restored_from_trash() {
+    let dir = TestDir::new("journal-trash");
+    let path = dir.file("deleted.pdf");
+    pdf(&path, "Deleted");
+    let original = fs::read(&path).unwrap();
+    let trash_location = move_to_trash(&path).unwrap().unwrap();
+    assert!(!path.exists());
+
+    let mut journal = Journal::load(dir.file(JOURNAL_FILE));
+    let delete = Operation::Delete {
+        path: path.to_string_lossy().into_owned(),
+        trash_location,
+    };
+    let id = journal.record("Удаление", vec![delete]).unwrap().unwrap();
+
+    assert!(journal.undo(&id).unwrap().is_empty());
+    assert_eq!(fs::read(&path).unwrap(), original);
+}
+
+fn backup_path(operation: &Operation) -> std::path::PathBuf {
+    match operation {
+        Operation::RestoreBackup { backup_path, .. } => backup_path.into(),
+        _ => panic!("{:?}", operation),
+    }
+}
+
+#[test]
+fn backup_is_restored_only_over_unchanged_file() {
+    let dir = TestDir::new("journal-backup");
+    let path = dir.file("book.pdf");
+    pdf(&path, "Original");
+    let original = fs::read(&path).unwrap();
+    let mut journal = Journal::load(dir.file(JOURNAL_FILE));
+
+    let first = set_title(&path, "Edited");
+    let first_backup = backup_path(&first);
+    let first_id = journal.record("Метаданные", vec![first]).unwrap().unwrap();
+    let edited = fs::read(&path).unwrap();
+    let second = set_title(&path, "Edited again");
+    let second_backup = backup_path(&second);
+    let second_id = journal.record("Метаданные", vec![second]).unwrap().unwrap();
+    let edited_again = fs::read(&path).unwrap();
+
+    // Файл изменили после первой записи: копия не восстанавливается, чтобы не потерять правку
+    let errors = journal.undo(&first_id).unwrap();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].0, path.to_string_lossy());
+    assert_eq!(fs::read(&path).unwrap(), edited_again);
+    assert!(first_backup.is_file());
+    assert!(!entry(&journal, &first_id).undone);
+
+    // После отмены второй записи отпечаток снова совпадает с записанным
+    assert!(journal.undo(&second_id).unwrap().is_empty());
+    assert_eq!(fs::read(&path).unwrap(), edited);
+    assert!(!second_backup.exists());
+    assert!(journal.undo(&first_id).unwrap().is_empty());
+    assert_eq!(fs::read(&path).unwrap(), original);
+    assert!(!first_backup.exists());
+}
+
+#[test]
+fn failed_part_of_batch_is_retried_alone() {
+    let dir = TestDir::new("journal-batch");
+    let (first, first_renamed) = (dir.file("first.pdf"), dir.file("first-renamed.pdf"));
+    let (second, second_renamed) = (dir.file("second.pdf"), dir.file("second-renamed.pdf"));
+    pdf(&first, "First");
+    pdf(&second, "Second");
+    let mut journal = Journal::load(dir.file(JOURNAL_FILE));
+    let operations = vec![
+        rename(&first, &first_renamed),
+        rename(&second, &second_renamed),
+    ];
+    let id = journal
+        .record("Переименование", operations)
+        .unwrap()
+        .unwrap();
+
+    // Второй файл куда-то унесли
+    let away = dir.file("away.pdf");
+    fs::rename(&second_renamed, &away).unwrap();
+    let errors = journal.undo(&id).unwrap();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].0, second_renamed.to_string_lossy());
+    assert_eq!(errors[0].1.kind(), ErrorKind::NotFound, "{}", errors[0].1);
+    assert!(first.is_file());
+
+    let batch = entry(&journal, &id);
+    assert!(!batch.undone);
+    assert!(batch.operations[0].undone);
+    assert!(!batch.operations[1].undone);
+
+    // Повторная отмена не трогает уже отмененное переименование
+    fs::rename(&away, &second_renamed).unwrap();
+    assert!(journal.undo(&id).unwrap().is_empty());
+    assert!(first.is_file());
+    assert!(second.is_file());
+    assert!(entry(&journal, &id).undone);
+}
@@ -0,0 +1,231 @@
+// Metadata output of get_file_info_all_meta for generated PDFs and validation of metadata edits
+
+mod common;
+
+use anomad_core::get_file_info_all_meta;
+use anomad_core::metadata_edit::MetadataChanges;
+use anomad_core::pdf_metadata::{set_xmp_packet, PdfDetails};
+use anomad_core::ErrorKind;
+use common::*;
+use lopdf::{dictionary, Dictionary};
+use std::path::Path;
+use xmp_toolkit::{xmp_ns, XmpMeta, XmpValue};
+
+fn info_dictionary() -> Dictionary {
+    dictionary! {
+        "Title" => utf16("Война и мир"),
+        "Author" => literal("Tolstoy, Leo"),
+        "Subject" => pdf_doc(b"Roman \xe9pique"),
+        "Keywords" => hex("war; peace"),
+        "Creator" => literal("  LaTeX with hyperref  "),
+        "Producer" => literal("pdfTeX-1.40.25"),
+        "ModDate" => literal("D:20200101000000Z"),
+    }
+}
+
+fn xmp_packet(title: &str, modified: &str) -> XmpMeta {
+    let mut xmp = XmpMeta::new().unwrap();
+    xmp.set_localized_text(xmp_ns::DC, "title", None, "x-default", title)
+        .unwrap();
+    let creators = XmpValue::new("creator".to_string())
+        .set_is_array(true)
+        .set_is_ordered(true);
+    for author in ["First Author", "Second Author"] {
+        xmp.append_array_item(xmp_ns::DC, &creators, &XmpValue::new(author.to_string()))
+            .unwrap();
+    }
+    xmp.set_property(
+        xmp_ns::XMP,
+        "CreatorTool",
+        &XmpValue::new("XMP Tool".to_string()),
+    )
+    .unwrap();
+    xmp.set_property(
+        xmp_ns::XMP,
+        "MetadataDate",
+        &XmpValue::new(modified.to_string()),
+    )
+    .unwrap();
+    xmp
+}
+
+fn details(page_count: u32) -> PdfDetails {
+    PdfDetails {
+        subject: Some("Roman épique".to_string()),
+        keywords: Some("war; peace".to_string()),
+        producer: Some("pdfTeX-1.40.25".to_string()),
+        pdf_version: "1.5".to_string(),
+        page_count,
+        is_encrypted: false,
+        has_text_layer: true,
+        is_tagged: false,
+    }
+}
+
+fn assert_details(actual: Option<PdfDetails>, expected: PdfDetails) {
+    // PdfDetails не сравнивается целиком, поэтому через JSON
+    assert_eq!(
+        serde_json::to_value(actual).unwrap(),
+        serde_json::to_value(Some(expected)).unwrap()
+    );
+}
+
+fn meta(path: &Path) -> anomad_core::FileInfo {
+    get_file_info_all_meta(path.to_str().unwrap())
+}
+
+#[test]
+fn info_dictionary_fields_are_decoded() {
+    let dir = TestDir::new("info");
+    let mut pdf = PdfBuilder::new();
+    pdf.page(None);
+    pdf.page(None);
+    pdf.info(info_dictionary());
+    let path = dir.file("info.pdf");
+    pdf.save(&path);
+
+    let info = meta(&path);
+    assert_eq!(info.pdf_title.as_deref(), Some("Война и мир"));
+    assert_eq!(info.pdf_author.as_deref(), Some("Tolstoy, Leo"));
+    assert_eq!(info.pdf_creator.as_deref(), Some("LaTeX with hyperref"));
+    assert_details(info.pdf_details, details(2));
+    assert!(!info.is_locked);
+
+    let fingerprint = info.fingerprint.expect("fingerprint of a PDF");
+    assert_eq!(fingerprint.size, std::fs::metadata(&path).unwrap().len());
+}
+
+#[test]
+fn newer_xmp_overrides_info_dictionary() {
+    let dir = TestDir::new("xmp-newer");
+    let mut pdf = PdfBuilder::new();
+    pdf.page(None);
+    pdf.info(info_dictionary());
+    pdf.catalog();
+    set_xmp_packet(
+        &mut pdf.doc,
+        &xmp_packet("XMP Title", "2024-05-01T10:00:00Z"),
+    )
+    .unwrap();
+    let path = dir.file("xmp-newer.pdf");
+    pdf.save(&path);
+
+    let info = meta(&path);
+    assert_eq!(info.pdf_title.as_deref(), Some("XMP Title"));
+    assert_eq!(
+        info.pdf_author.as_deref(),
+        Some("First Author; Second Author")
+    );
+    assert_eq!(info.pdf_creator.as_deref(), Some("XMP Tool"));
+    // Поля, которых нет в XMP, берутся из /Info
+    assert_details(info.pdf_details, details(1));
+}
+
+#[test]
+fn newer_info_dictionary_overrides_xmp() {
+    let dir = TestDir::new("info-newer");
+    let mut pdf = PdfBuilder::new();
+    pdf.page(None);
+    let mut info = info_dictionary();
+    info.set("ModDate", literal("D:20250101000000Z"));
+    info.remove(b"Author");
+    pdf.info(info);
+    pdf.catalog();
+    set_xmp_packet(
+        &mut pdf.doc,
+        &xmp_packet("XMP Title", "2024-05-01T10:00:00Z"),
+    )
+    .unwrap();
+    let path = dir.file("info-newer.pdf");
+    pdf.save(&path);
+
+    let info = meta(&path);
+    assert_eq!(info.pdf_title.as_deref(), Some("Война и мир"));
+    assert_eq!(
+        info.pdf_author.as_deref(),
+        Some("First Author; Second Author")
+    );
+    assert_eq!(info.pdf_creator.as_deref(), Some("LaTeX with hyperref"));
+    assert_details(info.pdf_details, details(1));
+}
+
+#[test]
+fn tagged_document_without_text_layer() {
+    let dir = TestDir::new("tagged");
+    let mut pdf = PdfBuilder::new();
+    pdf.page_with_content(b"0 0 m 100 100 l S", None);
+    pdf.catalog()
+        .set("MarkInfo", dictionary! { "Marked" => true });
+    let path = dir.file("tagged.pdf");
+    pdf.save(&path);
+
+    let info = meta(&path);
+    assert_eq!(info.pdf_title, None);
+    assert_eq!(info.pdf_author, None);
+    assert_eq!(info.pdf_creator, None);
+    assert_details(
+        info.pdf_details,
+        PdfDetails {
+            pdf_version: "1.5".to_string(),
+            page_count: 1,
+            has_text_layer: false,
+            is_tagged: true,
+            ..PdfDetails::default()
+        },
+    );
+}
+
+#[test]
+fn encrypted_document_metadata_is_decrypted() {
+    let dir = TestDir::new("encrypted-meta");
+    let mut pdf = PdfBuilder::new();
+    pdf.page(None);
+    pdf.info(info_dictionary());
+    let path = dir.file("encrypted.pdf");
+    pdf.save_encrypted(&path);
+
+    let info = meta(&path);
+    assert_eq!(info.pdf_title.as_deref(), Some("Война и мир"));
+    assert_eq!(info.pdf_author.as_deref(), Some("Tolstoy, Leo"));
+    assert_details(
+        info.pdf_details,
+        PdfDetails {
+            is_encrypted: true,
+            ..details(1)
+        },
+    );
+}
+
+#[test]
+fn broken_and_non_pdf_files_have_no_pdf_metadata() {
+    let dir = TestDir::new("broken-meta");
+    let broken = dir.file("broken.pdf");
+    std::fs::write(&broken, b"%PDF-1.4\n1 0 obj << /Title (x) >>\n%%EOF").unwrap();
+
+    let info = meta(&broken);
+    assert_eq!(info.pdf_title, None);
+    assert_eq!(info.pdf_author, None);
+    assert_eq!(info.pdf_creator, None);
+    assert!(info.pdf_details.is_none());
+    assert!(info.fingerprint.is_some());
+
+    let text = dir.file("notes.txt");
+    std::fs::write(&text, "not a pdf").unwrap();
+    let info = meta(&text);
+    assert!(info.pdf_details.is_none());
+    assert!(info.fingerprint.is_none());
+}
+
+#[test]
+fn custom_fields_cannot_shadow_standard_fields() {
+    let changes = |name: &str| MetadataChanges {
+        custom: [(name.to_string(), "value".to_string())].into(),
+        ..Default::default()
+    };
+
+    for name in ["title", "TITLE", "Authors", "author", "keywords", "moddate"] {
+        let error = changes(name).validate().unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput, "{}", name);
+    }
+    changes("Edition").validate().unwrap();
+}
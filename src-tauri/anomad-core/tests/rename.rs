@@ -0,0 +1,144 @@
+// Rename and organise plans built from PDF metadata, and moving files into place
+
+mod common;
+
+use anomad_core::organize::{move_file, plan_organize, OrganizeFile, OrganizeStatus};
+use anomad_core::rename_template::{plan_renames, PlanReason, RenameStatus};
+use anomad_core::ErrorKind;
+use common::*;
+use lopdf::dictionary;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn book(path: &Path, title: &str, author: &str) {
+    let mut pdf = PdfBuilder::new();
+    pdf.page(None);
+    pdf.info(dictionary! {
+        "Title" => literal(title),
+        "Author" => literal(author),
+    });
+    pdf.save(path);
+}
+
+fn key(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+#[test]
+fn rename_plan_marks_batch_collisions_and_unchanged_names() {
+    let dir = TestDir::new("rename-plan");
+    let first = dir.file("a.pdf");
+    let second = dir.file("b.pdf");
+    let named = dir.file("Knuth Art.pdf");
+    book(&first, "Same", "Ann Lee");
+    book(&second, "Same", "Ann Lee");
+    book(&named, "Art", "Donald Knuth");
+
+    let plan = plan_renames(
+        &[
+            key(&first),
+            key(&second),
+            key(&named),
+            key(&dir.file("gone.pdf")),
+        ],
+        "{author_last} {title}",
+        &[],
+    );
+
+    let statuses: Vec<(RenameStatus, Option<PlanReason>)> = plan
+        .iter()
+        .map(|item| (item.status, item.reason.clone()))
+        .collect();
+    assert_eq!(
+        statuses,
+        vec![
+            (RenameStatus::Conflict, Some(PlanReason::BatchCollision)),
+            (RenameStatus::Conflict, Some(PlanReason::BatchCollision)),
+            (RenameStatus::Unchanged, None),
+            (RenameStatus::Invalid, Some(PlanReason::FileNotFound)),
+        ]
+    );
+    assert_eq!(plan[0].target, Some(key(&dir.file("Lee Same.pdf"))));
+    // Текст причины составляет приложение
+    assert!(plan.iter().all(|item| item.message.is_none()));
+}
+
+#[test]
+fn plans_name_missing_template_fields() {
+    let dir = TestDir::new("rename-missing");
+    let path = dir.file("book.pdf");
+    book(&path, "Book", "Ann Lee");
+    let missing = Some(PlanReason::MissingFields {
+        fields: vec!["year".to_string(), "isbn".to_string()],
+    });
+
+    let plan = plan_renames(&[key(&path)], "{author_last} {year} {{{isbn}}}", &[]);
+    assert_eq!(plan[0].status, RenameStatus::Invalid);
+    assert_eq!(plan[0].reason, missing);
+
+    let file = OrganizeFile {
+        path: key(&path),
+        project: None,
+    };
+    let item = plan_organize(
+        &file,
+        &dir.file("library"),
+        "{year}/{isbn}",
+        &[],
+        &mut HashSet::new(),
+    );
+    assert_eq!(item.status, OrganizeStatus::Invalid);
+    assert_eq!(item.reason, missing);
+}
+
+#[test]
+fn organize_plan_gives_batch_duplicates_a_suffix() {
+    let dir = TestDir::new("organize-plan");
+    let root = dir.file("library");
+    let first = dir.file("one").join("book.pdf");
+    let second = dir.file("two").join("book.pdf");
+    for path in [&first, &second] {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        book(path, "Book", "Ann Lee");
+    }
+
+    let mut taken = HashSet::new();
+    let targets: Vec<(OrganizeStatus, Option<PathBuf>)> = [&first, &second]
+        .into_iter()
+        .map(|path| {
+            let file = OrganizeFile {
+                path: key(path),
+                project: Some("Thesis".to_string()),
+            };
+            let item = plan_organize(&file, &root, "{project}/{author_last}", &[], &mut taken);
+            (item.status, item.target.map(PathBuf::from))
+        })
+        .collect();
+
+    let folder = root.join("Thesis").join("Lee");
+    assert_eq!(
+        targets,
+        vec![
+            (OrganizeStatus::Ready, Some(folder.join("book.pdf"))),
+            (OrganizeStatus::Ready, Some(folder.join("book (2).pdf"))),
+        ]
+    );
+}
+
+#[test]
+fn failed_rename_is_not_retried_as_copy() {
+    let dir = TestDir::new("organize-move");
+    let source = dir.file("book.pdf");
+    let target = dir.file("sorted").join("book.pdf");
+
+    // Переименование не удалось не из-за другого тома - копировать нечего и незачем
+    let error = move_file(&source, &target).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::NotFound, "{}", error);
+    assert!(!target.exists());
+
+    book(&source, "Book", "Ann Lee");
+    move_file(&source, &target).unwrap();
+    assert!(!source.exists());
+    assert!(target.is_file());
+}
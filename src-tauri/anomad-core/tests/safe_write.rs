@@ -0,0 +1,103 @@
+// Refusal to rewrite encrypted or locked PDFs
+
+mod common;
+
+use anomad_core::file_lock::{ensure_not_locked, is_file_locked};
+use anomad_core::identity::{ensure_identity, IdentityStrategy};
+use anomad_core::pdf_metadata::load_pdf;
+use anomad_core::safe_write::{save_pdf, BACKUP_DIR};
+use anomad_core::ErrorKind;
+use common::*;
+use fs2::FileExt;
+use lopdf::{dictionary, Object};
+use std::fs;
+
+fn encrypted_pdf(dir: &TestDir) -> std::path::PathBuf {
+    let mut pdf = PdfBuilder::new();
+    let note = pdf.annot(with(annotation("Text"), "Contents", literal("note")));
+    pdf.page(Some(Object::Array(vec![note])));
+    pdf.info(dictionary! { "Title" => literal("Encrypted") });
+    let path = dir.file("encrypted.pdf");
+    pdf.save_encrypted(&path);
+    path
+}
+
+#[test]
+fn decrypted_document_is_not_saved_back() {
+    let dir = TestDir::new("save-encrypted");
+    let path = encrypted_pdf(&dir);
+    let original = fs::read(&path).unwrap();
+
+    let (mut doc, is_encrypted) = load_pdf(&path).unwrap();
+    assert!(is_encrypted);
+    // После расшифровки в памяти /Encrypt в трейлере уже нет
+    assert!(!doc.is_encrypted());
+
+    let error = save_pdf(&mut doc, &path, is_encrypted).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Encrypted, "{}", error);
+    assert_eq!(fs::read(&path).unwrap(), original);
+    assert!(!dir.file(BACKUP_DIR).exists());
+}
+
+#[test]
+fn xmp_identity_is_not_written_into_encrypted_file() {
+    let dir = TestDir::new("identity-encrypted");
+    let path = encrypted_pdf(&dir);
+    let original = fs::read(&path).unwrap();
+
+    let error = ensure_identity(&path, IdentityStrategy::Xmp).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Encrypted, "{}", error);
+    assert_eq!(fs::read(&path).unwrap(), original);
+}
+
+#[test]
+fn unencrypted_document_is_saved() {
+    let dir = TestDir::new("save-plain");
+    let mut pdf = PdfBuilder::new();
+    pdf.page(None);
+    let path = dir.file("plain.pdf");
+    pdf.save(&path);
+
+    let (mut doc, is_encrypted) = load_pdf(&path).unwrap();
+    assert!(!is_encrypted);
+    let info = doc.add_object(dictionary! { "Title" => literal("Saved") });
+    doc.trailer.set("Info", info);
+    save_pdf(&mut doc, &path, is_encrypted).unwrap();
+
+    assert!(dir.file(BACKUP_DIR).join("plain.pdf.1.bak").is_file());
+    let (saved, _) = load_pdf(&path).unwrap();
+    let info = saved
+        .trailer
+        .get(b"Info")
+        .and_then(Object::as_reference)
+        .unwrap();
+    assert!(saved.get_dictionary(info).unwrap().has(b"Title"));
+}
+
+#[test]
+fn only_lock_conflicts_count_as_locked() {
+    let dir = TestDir::new("file-lock");
+    let mut pdf = PdfBuilder::new();
+    pdf.page(None);
+    let path = dir.file("open.pdf");
+    pdf.save(&path);
+    let original = fs::read(&path).unwrap();
+
+    // Другая программа держит эксклюзивную блокировку
+    let reader = fs::File::open(&path).unwrap();
+    reader.lock_exclusive().unwrap();
+    assert!(is_file_locked(&path));
+    let (mut doc, is_encrypted) = load_pdf(&path).unwrap();
+    let error = save_pdf(&mut doc, &path, is_encrypted).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Locked, "{}", error);
+    assert_eq!(fs::read(&path).unwrap(), original);
+    reader.unlock().unwrap();
+    assert!(!is_file_locked(&path));
+
+    // Папку на запись не открыть, но занятой она от этого не становится
+    let folder = dir.file("folder.pdf");
+    fs::create_dir(&folder).unwrap();
+    assert!(!is_file_locked(&folder));
+    let error = ensure_not_locked(&folder).unwrap_err();
+    assert_ne!(error.kind(), ErrorKind::Locked, "{}", error);
+}
@@ -0,0 +1,134 @@
+// Text layer normalisation, fuzzy search and highlight areas
+
+use anomad_core::text_layer::{normalize_text, LayerChar, MatchedArea, TextLayer};
+
+const CHAR_WIDTH: f32 = 5.0;
+const LINE_HEIGHT: f32 = 10.0;
+
+// Символы страниц построчно, как их отдает pdfium: строки разделены '\n' без координат
+fn layer(pages: &[&[&str]]) -> TextLayer {
+    let mut chars = Vec::new();
+    for (page_index, lines) in pages.iter().enumerate() {
+        let page = page_index as u32 + 1;
+        for (line_index, line) in lines.iter().enumerate() {
+            let bottom = 700.0 - line_index as f32 * 2.0 * LINE_HEIGHT;
+            for (column, ch) in line.chars().enumerate() {
+                let left = 72.0 + column as f32 * CHAR_WIDTH;
+                chars.push(LayerChar {
+                    page,
+                    ch,
+                    bounds: Some([left, bottom, left + CHAR_WIDTH, bottom + LINE_HEIGHT]),
+                });
+            }
+            chars.push(LayerChar {
+                page,
+                ch: '\n',
+                bounds: None,
+            });
+        }
+    }
+    TextLayer::new(chars, pages.len() as u32)
+}
+
+fn matched_text(layer: &TextLayer, start: usize, end: usize) -> String {
+    layer.chars[start..end].iter().map(|c| c.ch).collect()
+}
+
+#[test]
+fn normalization_folds_case_ligatures_quotes_and_spaces() {
+    assert_eq!(
+        normalize_text("  “Eﬃcient”\t‘ﬂows’\n\nAND  more "),
+        "\"efficient\" 'flows' and more"
+    );
+}
+
+#[test]
+fn only_hyphens_at_line_end_join_words() {
+    assert_eq!(normalize_text("hyphen-\nation"), "hyphenation");
+    assert_eq!(normalize_text("hyphen\u{2010}\nation"), "hyphenation");
+    assert_eq!(normalize_text("soft\u{00AD}\nhyphen"), "softhyphen");
+    assert_eq!(normalize_text("in\u{00AD}visible"), "invisible");
+    assert_eq!(normalize_text("well-known"), "well-known");
+    // Тире в конце строки - знак препинания, слова не склеиваются
+    assert_eq!(normalize_text("word—\nnext"), "word- next");
+    assert_eq!(normalize_text("from – to"), "from - to");
+}
+
+#[test]
+fn exact_match_closest_to_expected_page_wins() {
+    let layer = layer(&[&["the same phrase"], &["filler"], &["the same phrase"]]);
+
+    let found = layer.find("Same phrase", 3, 0.8).unwrap();
+    assert_eq!(found.confidence, 1.0);
+    assert_eq!(layer.chars[found.start].page, 3);
+    assert_eq!(matched_text(&layer, found.start, found.end), "same phrase");
+}
+
+#[test]
+fn fuzzy_match_tolerates_typos_and_line_breaks() {
+    let layer = layer(&[&["a quick brown fox jum-", "ped over the lazy dog"]]);
+
+    let found = layer.find("quick brwn fox jumped", 1, 0.8).unwrap();
+    assert!(
+        found.confidence < 1.0 && found.confidence >= 0.9,
+        "{:?}",
+        found
+    );
+    assert_eq!(
+        matched_text(&layer, found.start, found.end),
+        "quick brown fox jumped"
+    );
+
+    assert!(layer.find("completely different", 1, 0.8).is_none());
+}
+
+#[test]
+fn areas_have_one_quad_per_line_and_one_area_per_page() {
+    let layer = layer(&[&["first line", "second"], &["third"]]);
+    let found = layer.find("first line second third", 1, 1.0).unwrap();
+
+    let top = 700.0 + LINE_HEIGHT;
+    let second_bottom = 700.0 - 2.0 * LINE_HEIGHT;
+    let first_right = 72.0 + 10.0 * CHAR_WIDTH;
+    assert_eq!(
+        layer.areas(&found),
+        vec![
+            MatchedArea {
+                page: 1,
+                rect: [72.0, second_bottom, first_right, top],
+                quad_points: vec![
+                    72.0,
+                    top,
+                    first_right,
+                    top,
+                    72.0,
+                    700.0,
+                    first_right,
+                    700.0,
+                    72.0,
+                    second_bottom + LINE_HEIGHT,
+                    72.0 + 6.0 * CHAR_WIDTH,
+                    second_bottom + LINE_HEIGHT,
+                    72.0,
+                    second_bottom,
+                    72.0 + 6.0 * CHAR_WIDTH,
+                    second_bottom,
+                ],
+            },
+            MatchedArea {
+                page: 2,
+                rect: [72.0, 700.0, 72.0 + 5.0 * CHAR_WIDTH, top],
+                quad_points: vec![
+                    72.0,
+                    top,
+                    72.0 + 5.0 * CHAR_WIDTH,
+                    top,
+                    72.0,
+                    700.0,
+                    72.0 + 5.0 * CHAR_WIDTH,
+                    700.0,
+                ],
+            },
+        ]
+    );
+}
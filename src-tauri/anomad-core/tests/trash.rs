@@ -0,0 +1,44 @@
+// Which folders may be deleted: only those inside a library folder
+
+mod common;
+
+use anomad_core::trash::is_inside_library;
+use common::*;
+use std::fs;
+
+#[test]
+fn only_folders_below_library_root_are_inside() {
+    let dir = TestDir::new("library-bounds");
+    let library = dir.file("library");
+    let nested = library.join("papers").join("2024");
+    fs::create_dir_all(&nested).unwrap();
+    // Общий префикс имени - еще не вложенность
+    let sibling = dir.file("library-old");
+    fs::create_dir_all(&sibling).unwrap();
+    let roots = vec![library.clone()];
+
+    assert!(is_inside_library(&nested, &roots));
+    // Путь сравнивается после разрешения "..", а не по записи
+    assert!(!is_inside_library(
+        &library.join("papers").join(".."),
+        &roots
+    ));
+    assert!(!is_inside_library(&library, &roots));
+    assert!(!is_inside_library(&sibling, &roots));
+    assert!(!is_inside_library(&library.join("missing"), &roots));
+    assert!(!is_inside_library(&nested, &[]));
+}
+
+#[cfg(unix)]
+#[test]
+fn symlink_out_of_library_is_outside() {
+    let dir = TestDir::new("library-symlink");
+    let library = dir.file("library");
+    let outside = dir.file("outside");
+    fs::create_dir_all(&library).unwrap();
+    fs::create_dir_all(&outside).unwrap();
+    let link = library.join("link");
+    std::os::unix::fs::symlink(&outside, &link).unwrap();
+
+    assert!(!is_inside_library(&link, &[library]));
+}
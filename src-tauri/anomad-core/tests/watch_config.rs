@@ -0,0 +1,51 @@
+// Persisted watch settings
+
+mod common;
+
+use anomad_core::watch_config::{stamp, FileStamp, WatchConfig};
+use common::*;
+use std::fs;
+
+#[test]
+fn missing_files_stay_in_config_until_removed() {
+    let dir = TestDir::new("watch-config-missing");
+    let kept = dir.file("kept.pdf");
+    let moved_away = dir.file("moved-away.pdf");
+    let never_seen = dir.file("never-seen.pdf");
+    fs::write(&kept, b"kept").unwrap();
+    fs::write(&moved_away, b"moved away").unwrap();
+
+    let config_file = dir.file("watchers.json");
+    let mut config = WatchConfig::load(config_file.clone());
+    config.set_files([kept.clone(), moved_away.clone(), never_seen.clone()]);
+    let moved_stamp = stamp(&moved_away).unwrap();
+
+    fs::remove_file(&moved_away).unwrap();
+    config.refresh_snapshots();
+    config.save().unwrap();
+
+    let config = WatchConfig::load(config_file);
+    let snapshot = config.files_snapshot();
+    assert_eq!(snapshot.len(), 3, "{:?}", snapshot);
+    assert_eq!(snapshot[&key(&kept)], stamp(&kept).unwrap());
+    assert_eq!(snapshot[&key(&moved_away)], moved_stamp);
+    assert_eq!(snapshot[&key(&never_seen)], FileStamp::default());
+}
+
+#[test]
+fn files_removed_from_the_list_are_dropped() {
+    let dir = TestDir::new("watch-config-removed");
+    let first = dir.file("first.pdf");
+    let second = dir.file("second.pdf");
+    fs::write(&first, b"first").unwrap();
+
+    let mut config = WatchConfig::load(dir.file("watchers.json"));
+    config.set_files([first.clone(), second]);
+    config.set_files([first.clone()]);
+
+    assert_eq!(config.files(), vec![key(&first)]);
+}
+
+fn key(path: &std::path::Path) -> String {
+    path.to_string_lossy().into_owned()
+}
@@ -0,0 +1,114 @@
+// Raw notify events folded into logical file changes
+
+use anomad_core::watch_events::{ChangeBatcher, ChangeKind, LogicalChange, DEBOUNCE};
+use notify::event::{CreateKind, DataChange, EventKind, ModifyKind, RemoveKind, RenameMode};
+use notify::Event;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+fn dir() -> PathBuf {
+    PathBuf::from("/watched")
+}
+
+fn event(kind: EventKind, path: &Path) -> Event {
+    Event::new(kind).add_path(path.to_path_buf())
+}
+
+fn modified(path: &Path) -> Event {
+    event(
+        EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+        path,
+    )
+}
+
+fn rename(mode: RenameMode, path: &Path) -> Event {
+    event(EventKind::Modify(ModifyKind::Name(mode)), path)
+}
+
+fn batcher(known: &[&Path]) -> ChangeBatcher {
+    ChangeBatcher::new(
+        known.iter().map(|path| path.to_path_buf()),
+        vec!["pdf".to_string()],
+    )
+}
+
+// Выдает изменения после паузы; existing - файлы, которые есть на диске к этому моменту
+fn flush(batcher: &mut ChangeBatcher, start: Instant, existing: &[&Path]) -> Vec<LogicalChange> {
+    let existing: HashSet<&Path> = existing.iter().copied().collect();
+    // До конца паузы пачка не считается законченной
+    assert!(batcher
+        .flush(start, |path| existing.contains(path))
+        .is_empty());
+    let changes = batcher.flush(start + DEBOUNCE, |path| existing.contains(path));
+    assert!(batcher.is_idle());
+    changes
+}
+
+fn change(kind: ChangeKind, path: &Path, old_path: Option<&Path>) -> LogicalChange {
+    LogicalChange {
+        kind,
+        path: path.to_path_buf(),
+        old_path: old_path.map(Path::to_path_buf),
+    }
+}
+
+#[test]
+fn atomic_save_through_temp_file_is_one_modification() {
+    let book = dir().join("book.pdf");
+    let temp = dir().join("book.pdf.tmp");
+    let mut batcher = batcher(&[&book]);
+    let now = Instant::now();
+
+    batcher.push(&event(EventKind::Create(CreateKind::File), &temp), now);
+    batcher.push(&modified(&temp), now);
+    batcher.push(&event(EventKind::Remove(RemoveKind::File), &book), now);
+    batcher.push(&rename(RenameMode::From, &temp), now);
+    batcher.push(&rename(RenameMode::To, &book), now);
+
+    assert_eq!(
+        flush(&mut batcher, now, &[&book]),
+        vec![change(ChangeKind::Modified, &book, None)]
+    );
+}
+
+#[test]
+fn rename_halves_without_cookie_are_paired() {
+    let before = dir().join("draft.pdf");
+    let after = dir().join("final.pdf");
+    let mut batcher = batcher(&[&before]);
+    let now = Instant::now();
+
+    // Windows и macOS не дают cookie: половинки связываются по порядку
+    batcher.push(&rename(RenameMode::From, &before), now);
+    batcher.push(&rename(RenameMode::To, &after), now);
+
+    assert_eq!(
+        flush(&mut batcher, now, &[&after]),
+        vec![change(ChangeKind::Renamed, &after, Some(&before))]
+    );
+
+    // Новое имя теперь известно: его правка - изменение, а не создание
+    let later = now + DEBOUNCE;
+    batcher.push(&modified(&after), later);
+    assert_eq!(
+        flush(&mut batcher, later, &[&after]),
+        vec![change(ChangeKind::Modified, &after, None)]
+    );
+}
+
+#[test]
+fn move_out_of_watched_folder_is_deletion() {
+    let book = dir().join("book.pdf");
+    let other = dir().join("other.pdf");
+    let mut batcher = batcher(&[&book, &other]);
+    let now = Instant::now();
+
+    // Вторая половинка приходит из другой папки, а значит не приходит вовсе
+    batcher.push(&rename(RenameMode::From, &book), now);
+
+    assert_eq!(
+        flush(&mut batcher, now, &[&other]),
+        vec![change(ChangeKind::Deleted, &book, None)]
+    );
+}
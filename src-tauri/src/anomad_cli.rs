@@ -0,0 +1,6 @@
+// Command-line entry point: the same features without a window, for scripts and cron.
+// No windows_subsystem here - the binary needs a console
+
+fn main() {
+    std::process::exit(app_lib::cli::run(std::env::args().skip(1).collect()))
+}
@@ -0,0 +1,564 @@
+// Headless command-line interface: search, highlights, metadata, export and identity
+// stamping without starting Tauri. Entry point is anomad_cli.rs
+
+use chrono::{Duration, Local, NaiveDate};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, BufRead, IsTerminal};
+use std::path::{Path, PathBuf};
+
+use crate::commands::error::{AppError, CommandResult};
+use crate::commands::types::{SearchParams, SearchResult};
+use anomad_core::annotation_history::parse_day;
+use anomad_core::bibtex::{citation_key, format_entry, read_bib_source};
+use anomad_core::everything_search::everything_search;
+use anomad_core::get_file_highlights::{get_file_highlights, Highlight};
+use anomad_core::get_file_info_all_meta::get_file_info_all_meta;
+use anomad_core::identifiers::DocumentIdentifiers;
+use anomad_core::identity::{ensure_identity, IdentityStrategy};
+use anomad_core::markdown::format_document;
+use anomad_core::pdf_date::parse_pdf_date;
+use anomad_core::pdf_metadata::PdfDetails;
+use anomad_core::xfdf::export_xfdf;
+
+// Коды выхода
+const EXIT_OK: i32 = 0;
+const EXIT_FAILED: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+// Часть файлов обработана, по остальным ошибки выведены в stderr
+const EXIT_PARTIAL: i32 = 3;
+const EXIT_SEARCH_UNAVAILABLE: i32 = 4;
+
+#[derive(Parser)]
+#[command(
+    name = "anomad-cli",
+    version,
+    about = "AcademyNomad без окна: поиск, пометки, метаданные, экспорт и идентификаторы"
+)]
+struct Cli {
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = OutputFormat::Text,
+        global = true,
+        help = "Формат вывода"
+    )]
+    format: OutputFormat,
+
+    #[command(subcommand)]
+    command: CliCommand,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    Markdown,
+    Bibtex,
+    Xfdf,
+}
+
+#[derive(Subcommand)]
+enum CliCommand {
+    #[command(about = "Поиск файлов через Everything; в текстовом виде - по пути на строку")]
+    Search {
+        query: String,
+        #[arg(long, default_value_t = 20, help = "Сколько результатов выдать")]
+        count: u32,
+        #[arg(long, help = "Извлечь пометки найденных файлов")]
+        highlights: bool,
+        #[arg(long, help = "Прочитать все метаданные PDF")]
+        meta: bool,
+    },
+    #[command(about = "Пометки PDF, при необходимости только за период")]
+    Highlights {
+        #[command(flatten)]
+        files: FileArgs,
+        #[arg(long, help = "Пометки, измененные не раньше даты ГГГГ-ММ-ДД")]
+        since: Option<String>,
+        #[arg(long, help = "Пометки, измененные не позже даты ГГГГ-ММ-ДД")]
+        until: Option<String>,
+        #[arg(long, conflicts_with = "since", help = "Пометки за последние N дней")]
+        days: Option<u32>,
+    },
+    #[command(about = "Метаданные, отпечаток и идентификаторы (DOI, ISBN, arXiv) PDF")]
+    Metadata {
+        #[command(flatten)]
+        files: FileArgs,
+    },
+    #[command(about = "Экспорт пометок в Markdown, библиографии в BibTeX или аннотаций в XFDF")]
+    Export {
+        #[command(flatten)]
+        files: FileArgs,
+        #[arg(long, value_enum, help = "Во что экспортировать")]
+        to: ExportFormat,
+        #[arg(
+            short,
+            long,
+            help = "Файл результата (без него - stdout; для XFDF - файл рядом с PDF)"
+        )]
+        output: Option<PathBuf>,
+    },
+    #[command(about = "Записать идентификатор документа (или прочитать уже записанный)")]
+    Identity {
+        #[command(flatten)]
+        files: FileArgs,
+        #[arg(
+            long,
+            default_value = "sidecar",
+            value_parser = parse_strategy,
+            help = "Способ хранения: xmp, sidecar, xattr или content_hash"
+        )]
+        strategy: IdentityStrategy,
+    },
+}
+
+#[derive(Args)]
+struct FileArgs {
+    #[arg(help = "Пути к файлам; без них пути читаются из stdin, по одному на строку")]
+    files: Vec<PathBuf>,
+}
+
+impl FileArgs {
+    // Так результат search можно передать другой подкоманде через конвейер
+    fn resolve(self) -> CommandResult<Vec<PathBuf>> {
+        if !self.files.is_empty() {
+            return Ok(self.files);
+        }
+        if io::stdin().is_terminal() {
+            return Err(AppError::invalid("files", "Не указаны файлы"));
+        }
+        let files: Vec<PathBuf> = io::stdin()
+            .lock()
+            .lines()
+            .map_while(Result::ok)
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
+            .map(PathBuf::from)
+            .collect();
+        if files.is_empty() {
+            return Err(AppError::invalid("files", "Не указаны файлы"));
+        }
+        Ok(files)
+    }
+}
+
+fn parse_strategy(value: &str) -> Result<IdentityStrategy, String> {
+    serde_json::from_value(serde_json::Value::String(value.replace('-', "_")))
+        .map_err(|_| format!("Неизвестный способ хранения: {}", value))
+}
+
+#[derive(Serialize)]
+struct FileHighlights {
+    path: String,
+    highlights: Vec<Highlight>,
+}
+
+#[derive(Serialize)]
+struct FileMetadataOutput {
+    path: String,
+    id: Option<String>,
+    title: Option<String>,
+    author: Option<String>,
+    creator: Option<String>,
+    details: Option<PdfDetails>,
+    content_hash: Option<String>,
+    trailer_id: Option<String>,
+    is_locked: bool,
+    identifiers: Option<DocumentIdentifiers>,
+}
+
+#[derive(Serialize)]
+struct IdentityOutput {
+    path: String,
+    id: String,
+    strategy: IdentityStrategy,
+}
+
+#[derive(Serialize)]
+struct ExportOutput {
+    written: Vec<String>,
+}
+
+// Аргументы без имени программы; возвращает код выхода
+pub fn run(args: Vec<String>) -> i32 {
+    // Предупреждения ядра (пропущенный шаблон, сбой запроса) - в stderr
+    crate::logging::init_cli_logger();
+    let cli = match Cli::try_parse_from(std::iter::once("anomad-cli".to_string()).chain(args)) {
+        Ok(cli) => cli,
+        Err(e) => {
+            let _ = e.print();
+            return if e.use_stderr() { EXIT_USAGE } else { EXIT_OK };
+        }
+    };
+
+    let format = cli.format;
+    match execute(cli.command, format) {
+        Ok(errors) if errors.is_empty() => EXIT_OK,
+        Ok(errors) => {
+            errors.iter().for_each(|e| report_error(format, e));
+            EXIT_PARTIAL
+        }
+        Err(e) => {
+            report_error(format, &e);
+            exit_code(&e)
+        }
+    }
+}
+
+fn exit_code(error: &AppError) -> i32 {
+    match error {
+        AppError::InvalidArgument { .. } => EXIT_USAGE,
+        AppError::SearchUnavailable => EXIT_SEARCH_UNAVAILABLE,
+        _ => EXIT_FAILED,
+    }
+}
+
+// В JSON-режиме ошибки - тоже JSON (по объекту на строку), чтобы их можно было разобрать
+fn report_error(format: OutputFormat, error: &AppError) {
+    match format {
+        OutputFormat::Json => match serde_json::to_string(error) {
+            Ok(json) => eprintln!("{}", json),
+            Err(_) => eprintln!("{}", error),
+        },
+        OutputFormat::Text => eprintln!("ошибка: {}", error),
+    }
+}
+
+fn print_json<T: Serialize>(value: &T) -> CommandResult<()> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| AppError::failed("Не удалось сформировать JSON", e))?;
+    println!("{}", json);
+    Ok(())
+}
+
+// Ok со списком ошибок - команда выполнена, но часть файлов обработать не удалось
+fn execute(command: CliCommand, format: OutputFormat) -> CommandResult<Vec<AppError>> {
+    match command {
+        CliCommand::Search {
+            query,
+            count,
+            highlights,
+            meta,
+        } => search(query, count, highlights, meta, format).map(|_| Vec::new()),
+        CliCommand::Highlights {
+            files,
+            since,
+            until,
+            days,
+        } => {
+            let since = match days {
+                Some(days) => Some(Local::now().date_naive() - Duration::days(i64::from(days))),
+                None => day_arg("since", since)?,
+            };
+            let until = day_arg("until", until)?;
+            highlights(files.resolve()?, since, until, format)
+        }
+        CliCommand::Metadata { files } => metadata(files.resolve()?, format),
+        CliCommand::Export { files, to, output } => export(files.resolve()?, to, output, format),
+        CliCommand::Identity { files, strategy } => identity(files.resolve()?, strategy, format),
+    }
+}
+
+fn day_arg(field: &'static str, value: Option<String>) -> CommandResult<Option<NaiveDate>> {
+    match value {
+        Some(value) => parse_day(&value).map(Some).ok_or_else(|| {
+            AppError::invalid(
+                field,
+                format!("Неверная дата: {}, ожидается ГГГГ-ММ-ДД", value),
+            )
+        }),
+        None => Ok(None),
+    }
+}
+
+// Обрабатывает файлы по одному: ошибка в одном не останавливает остальные
+fn each_file<T>(
+    files: &[PathBuf],
+    f: impl Fn(&Path) -> CommandResult<T>,
+) -> (Vec<(String, T)>, Vec<AppError>) {
+    let mut done = Vec::new();
+    let mut errors = Vec::new();
+    for path in files {
+        if !path.is_file() {
+            errors.push(AppError::not_found(path));
+            continue;
+        }
+        match f(path) {
+            Ok(value) => done.push((path.to_string_lossy().into_owned(), value)),
+            Err(e) => errors.push(e),
+        }
+    }
+    (done, errors)
+}
+
+fn read_highlights(path: &Path) -> CommandResult<Vec<Highlight>> {
+    get_file_highlights(&path.to_string_lossy())
+        .map_err(|e| AppError::from_core("Не удалось прочитать пометки", path, &e))
+}
+
+fn search(
+    query: String,
+    count: u32,
+    include_highlights: bool,
+    meta: bool,
+    format: OutputFormat,
+) -> CommandResult<()> {
+    let params = SearchParams {
+        query,
+        path: None,
+        count: Some(count),
+        include_highlights: Some(include_highlights),
+    };
+    let (items, has_more) = tauri::async_runtime::block_on(everything_search(&params, meta))
+        .ok_or(AppError::SearchUnavailable)?;
+
+    match format {
+        OutputFormat::Json => print_json(&SearchResult { items, has_more }),
+        OutputFormat::Text => {
+            items.iter().for_each(|item| println!("{}", item.full_path));
+            Ok(())
+        }
+    }
+}
+
+fn highlights(
+    files: Vec<PathBuf>,
+    since: Option<NaiveDate>,
+    until: Option<NaiveDate>,
+    format: OutputFormat,
+) -> CommandResult<Vec<AppError>> {
+    let filtered = since.is_some() || until.is_some();
+    let in_range = |highlight: &Highlight| {
+        if !filtered {
+            return true;
+        }
+        // Пометка без даты (/M) в период попасть не может
+        let Some(day) = highlight
+            .date
+            .as_deref()
+            .and_then(parse_pdf_date)
+            .map(|date| date.date_naive())
+        else {
+            return false;
+        };
+        since.map_or(true, |since| day >= since) && until.map_or(true, |until| day <= until)
+    };
+
+    let (done, errors) = each_file(&files, read_highlights);
+    let results: Vec<FileHighlights> = done
+        .into_iter()
+        .map(|(path, highlights)| FileHighlights {
+            path,
+            highlights: highlights.into_iter().filter(|h| in_range(h)).collect(),
+        })
+        // С фильтром по дате файлы без подходящих пометок не выводим
+        .filter(|file| !filtered || !file.highlights.is_empty())
+        .collect();
+
+    match format {
+        OutputFormat::Json => print_json(&results)?,
+        OutputFormat::Text => {
+            for file in &results {
+                println!("{}", file.path);
+                for highlight in &file.highlights {
+                    // У выделения без извлеченного текста выводим только страницу и тип
+                    let text = highlight.highlighted_text.trim();
+                    if text.is_empty() {
+                        println!("  с. {} [{}]", highlight.page, highlight.highlight_type);
+                    } else {
+                        println!(
+                            "  с. {} [{}] {}",
+                            highlight.page, highlight.highlight_type, text
+                        );
+                    }
+                    if let Some(note) = &highlight.annotation_text {
+                        println!("    > {}", note);
+                    }
+                }
+            }
+        }
+    }
+    Ok(errors)
+}
+
+fn metadata(files: Vec<PathBuf>, format: OutputFormat) -> CommandResult<Vec<AppError>> {
+    let (done, errors) = each_file(&files, |path| {
+        let info = get_file_info_all_meta(&path.to_string_lossy());
+        let identifiers = read_bib_source(path).ok().map(|source| source.identifiers);
+        Ok(FileMetadataOutput {
+            path: path.to_string_lossy().into_owned(),
+            id: info.file_id,
+            title: info.pdf_title,
+            author: info.pdf_author,
+            creator: info.pdf_creator,
+            details: info.pdf_details,
+            content_hash: info.fingerprint.as_ref().map(|f| f.content_hash.clone()),
+            trailer_id: info.fingerprint.and_then(|f| f.trailer_id),
+            is_locked: info.is_locked,
+            identifiers,
+        })
+    });
+    let results: Vec<FileMetadataOutput> = done.into_iter().map(|(_, meta)| meta).collect();
+
+    match format {
+        OutputFormat::Json => print_json(&results)?,
+        OutputFormat::Text => {
+            for meta in &results {
+                println!("{}", meta.path);
+                let fields = [
+                    ("Идентификатор", meta.id.as_deref()),
+                    ("Название", meta.title.as_deref()),
+                    ("Автор", meta.author.as_deref()),
+                    ("Программа", meta.creator.as_deref()),
+                    ("Хеш", meta.content_hash.as_deref()),
+                    ("/ID", meta.trailer_id.as_deref()),
+                ];
+                for (name, value) in fields {
+                    if let Some(value) = value {
+                        println!("  {}: {}", name, value);
+                    }
+                }
+                if let Some(details) = &meta.details {
+                    println!("  Страниц: {}", details.page_count);
+                }
+                if let Some(identifiers) = &meta.identifiers {
+                    let lists = [
+                        ("DOI", &identifiers.dois),
+                        ("ISBN", &identifiers.isbns),
+                        ("arXiv", &identifiers.arxiv_ids),
+                    ];
+                    for (name, values) in lists {
+                        if !values.is_empty() {
+                            println!("  {}: {}", name, values.join(", "));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    Ok(errors)
+}
+
+fn export(
+    files: Vec<PathBuf>,
+    to: ExportFormat,
+    output: Option<PathBuf>,
+    format: OutputFormat,
+) -> CommandResult<Vec<AppError>> {
+    let (content, errors) = match to {
+        ExportFormat::Markdown => {
+            let (done, errors) = each_file(&files, |path| {
+                let source = read_bib_source(path)
+                    .map_err(|e| AppError::from_core("Не удалось прочитать PDF", path, &e))?;
+                Ok(format_document(&source, &read_highlights(path)?))
+            });
+            let documents: Vec<String> = done.into_iter().map(|(_, doc)| doc).collect();
+            (documents.join("\n"), errors)
+        }
+        ExportFormat::Bibtex => {
+            let mut used_keys = HashSet::new();
+            let (done, errors) = each_file(&files, |path| {
+                read_bib_source(path)
+                    .map_err(|e| AppError::from_core("Не удалось прочитать PDF", path, &e))
+            });
+            let entries: Vec<String> = done
+                .iter()
+                .map(|(_, source)| format_entry(&citation_key(source, &mut used_keys), source))
+                .collect();
+            (entries.join("\n"), errors)
+        }
+        ExportFormat::Xfdf => return export_xfdf_files(files, output, format),
+    };
+
+    match output {
+        Some(output) => {
+            fs::write(&output, content)
+                .map_err(|e| AppError::io("Не удалось записать файл", &output, &e))?;
+            report_written(vec![output.to_string_lossy().into_owned()], format)?;
+        }
+        None => print!("{}", content),
+    }
+    Ok(errors)
+}
+
+// XFDF у каждого PDF свой: output допустим только для одного файла
+fn export_xfdf_files(
+    files: Vec<PathBuf>,
+    output: Option<PathBuf>,
+    format: OutputFormat,
+) -> CommandResult<Vec<AppError>> {
+    if output.is_some() && files.len() > 1 {
+        return Err(AppError::invalid(
+            "output",
+            "Для XFDF файл результата можно указать только при экспорте одного PDF",
+        ));
+    }
+
+    let (done, errors) = each_file(&files, |path| {
+        export_xfdf(path, output.as_deref())
+            .map(|written| written.to_string_lossy().into_owned())
+            .map_err(|e| AppError::from_core("Не удалось экспортировать XFDF", path, &e))
+    });
+    report_written(
+        done.into_iter().map(|(_, written)| written).collect(),
+        format,
+    )?;
+    Ok(errors)
+}
+
+fn report_written(written: Vec<String>, format: OutputFormat) -> CommandResult<()> {
+    match format {
+        OutputFormat::Json => print_json(&ExportOutput { written }),
+        OutputFormat::Text => {
+            written.iter().for_each(|path| println!("{}", path));
+            Ok(())
+        }
+    }
+}
+
+// Как set_metadata: если выбранный способ не сработал, идентифицируем по хешу содержимого
+// и предупреждаем об этом в stderr.
+// Журнал операций приложения не ведется - он доступен только из окна
+fn identity(
+    files: Vec<PathBuf>,
+    strategy: IdentityStrategy,
+    format: OutputFormat,
+) -> CommandResult<Vec<AppError>> {
+    let (done, errors) = each_file(&files, |path| {
+        // Использованный способ выводится вместе с идентификатором
+        let stamped = match ensure_identity(path, strategy) {
+            Err(e) if strategy != IdentityStrategy::ContentHash => {
+                log::warn!(
+                    "{}: идентификатор не записан ({}), использован хеш содержимого",
+                    path.display(),
+                    e
+                );
+                ensure_identity(path, IdentityStrategy::ContentHash)
+                    .map(|id| (id, IdentityStrategy::ContentHash))
+            }
+            stamped => stamped.map(|id| (id, strategy)),
+        };
+        stamped.map_err(|e| AppError::from_core("Не удалось прочитать файл", path, &e))
+    });
+    let results: Vec<IdentityOutput> = done
+        .into_iter()
+        .map(|(path, (id, strategy))| IdentityOutput { path, id, strategy })
+        .collect();
+
+    match format {
+        OutputFormat::Json => print_json(&results)?,
+        OutputFormat::Text => {
+            for result in &results {
+                println!("{}\t{}", result.id, result.path);
+            }
+        }
+    }
+    Ok(errors)
+}
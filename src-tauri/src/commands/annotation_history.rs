@@ -0,0 +1,69 @@
+// Annotation history commands: record extracted highlights and return a timeline
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::{command, State};
+
+use super::error::{AppError, CommandResult};
+use super::types::FileResult;
+use anomad_core::annotation_history::{parse_day, AnnotationHistory, HistoryEvent};
+use anomad_core::get_file_highlights::Highlight;
+
+// История создается в setup, когда известна папка данных приложения
+pub type HistoryState = Arc<Mutex<AnnotationHistory>>;
+
+// Ошибка истории не должна мешать извлечению пометок, поэтому только логируем
+pub fn record_highlights(history: &HistoryState, path: &Path, highlights: &[Highlight]) {
+    if let Err(e) = history.lock().unwrap().record(path, highlights) {
+        log::error!(
+            "Не удалось записать историю пометок {}: {}",
+            path.display(),
+            e
+        );
+    }
+}
+
+pub fn record_extracted(history: &HistoryState, extracted: &[(PathBuf, Vec<Highlight>)]) {
+    let extracted = extracted
+        .iter()
+        .map(|(path, highlights)| (path.as_path(), highlights.as_slice()));
+    if let Err(e) = history.lock().unwrap().record_all(extracted) {
+        log::error!("Не удалось записать историю пометок: {}", e);
+    }
+}
+
+// Пустой список тоже записываем - все пометки файла удалены. Непрочитанный файл
+// и файл, в котором пометки не искали, поиск возвращает с None
+pub fn record_search_results(history: &HistoryState, items: &[FileResult]) {
+    let extracted = items.iter().filter_map(|item| {
+        let highlights = item.highlights.as_ref()?;
+        Some((Path::new(&item.full_path), highlights.as_slice()))
+    });
+    if let Err(e) = history.lock().unwrap().record_all(extracted) {
+        log::error!("Не удалось записать историю пометок: {}", e);
+    }
+}
+
+// file_path - один документ (None - вся библиотека); from и to - даты "ГГГГ-ММ-ДД" включительно
+#[command]
+pub async fn get_annotation_timeline(
+    file_path: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    history: State<'_, HistoryState>,
+) -> CommandResult<Vec<HistoryEvent>> {
+    let day = |field, value: Option<String>| match value.filter(|v| !v.trim().is_empty()) {
+        Some(value) => parse_day(&value).map(Some).ok_or_else(|| {
+            AppError::invalid(
+                field,
+                format!("Неверная дата: {}, ожидается ГГГГ-ММ-ДД", value),
+            )
+        }),
+        None => Ok(None),
+    };
+    let (from, to) = (day("from", from)?, day("to", to)?);
+
+    let history = history.lock().unwrap();
+    let document = file_path.map(|path| history.document_key(Path::new(&path)));
+    Ok(history.timeline(document.as_deref(), from, to))
+}
@@ -0,0 +1,103 @@
+// Template-based batch rename command
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{command, AppHandle, State};
+
+use super::error::{AppError, CommandResult};
+use super::filename_patterns::load_filename_patterns;
+use super::journal::{record_operation, JournalState};
+use anomad_core::identity::move_sidecar;
+use anomad_core::journal::Operation;
+use anomad_core::rename_template::{
+    collision_key, plan_renames, PlanReason, RenamePlanItem, RenameStatus,
+};
+
+// С dry_run ничего не переименовывает и возвращает план. Без него план строится заново
+// (файлы могли измениться) и выполняются только пункты со статусом Ready
+#[command]
+pub async fn batch_rename(
+    app: AppHandle,
+    file_paths: Vec<String>,
+    template: String,
+    dry_run: bool,
+    journal: State<'_, JournalState>,
+) -> CommandResult<Vec<RenamePlanItem>> {
+    if template.trim().is_empty() {
+        return Err(AppError::invalid("template", "Шаблон имени пуст"));
+    }
+
+    let patterns = load_filename_patterns(&app);
+    let plan: Vec<RenamePlanItem> = plan_renames(&file_paths, &template, &patterns)
+        .into_iter()
+        .map(|mut item| {
+            item.message = item.reason.as_ref().map(plan_message);
+            item
+        })
+        .collect();
+
+    if dry_run {
+        return Ok(plan);
+    }
+
+    let results: Vec<RenamePlanItem> = plan.into_iter().map(apply_item).collect();
+
+    // Весь пакет отменяется одной записью журнала
+    let operations = results
+        .iter()
+        .filter(|item| item.status == RenameStatus::Renamed)
+        .filter_map(|item| {
+            item.target.as_ref().map(|target| Operation::Rename {
+                from: item.source.clone(),
+                to: target.clone(),
+            })
+        })
+        .collect();
+    record_operation(&journal, "Пакетное переименование", operations);
+
+    Ok(results)
+}
+
+// Причины из плана ядра - текстом для пользователя
+pub fn plan_message(reason: &PlanReason) -> String {
+    match reason {
+        PlanReason::FileNotFound => "Файл не найден".to_string(),
+        PlanReason::MissingFields { fields } => {
+            let fields: Vec<String> = fields.iter().map(|f| format!("{{{}}}", f)).collect();
+            format!("Не заполнены поля шаблона: {}", fields.join(", "))
+        }
+        PlanReason::InvalidName => {
+            "Недопустимое имя файла: имя пустое или путь слишком длинный".to_string()
+        }
+        PlanReason::NameTaken => "Файл с таким именем уже существует".to_string(),
+        PlanReason::BatchCollision => "Такое же имя получает другой файл пакета".to_string(),
+    }
+}
+
+fn apply_item(mut item: RenamePlanItem) -> RenamePlanItem {
+    let target = match (&item.status, &item.target) {
+        (RenameStatus::Ready, Some(target)) => PathBuf::from(target),
+        _ => return item,
+    };
+    let source = Path::new(&item.source);
+
+    // Файл мог появиться после построения плана
+    if target.exists() && collision_key(&target) != collision_key(source) {
+        item.status = RenameStatus::Conflict;
+        item.reason = Some(PlanReason::NameTaken);
+        item.message = item.reason.as_ref().map(plan_message);
+        return item;
+    }
+
+    match fs::rename(source, &target) {
+        Ok(_) => {
+            let _ = move_sidecar(source, &target);
+            item.status = RenameStatus::Renamed;
+        }
+        Err(e) => {
+            item.status = RenameStatus::Failed;
+            item.message = Some(format!("Не удалось переименовать файл: {}", e));
+        }
+    }
+    item
+}
@@ -2,21 +2,82 @@
 
 use std::fs;
 use std::path::Path;
-use tauri::command;
+use tauri::{command, AppHandle, State};
 
+use super::error::{AppError, CommandResult};
+use super::journal::{record_operation, JournalState};
+use super::settings::library_roots;
+use super::types::DeleteResult;
+use anomad_core::identity::sidecar_path;
+use anomad_core::journal::Operation;
+use anomad_core::trash::{is_inside_library, move_to_trash};
+
+// По умолчанию файл уходит в системную корзину; безвозвратно - только с permanent.
+// Папки удаляются, только если лежат внутри одной из папок библиотеки
 #[command]
-pub async fn delete_file(path: String) -> Result<(), String> {
+pub async fn delete_file(
+    app: AppHandle,
+    path: String,
+    permanent: Option<bool>,
+    journal: State<'_, JournalState>,
+) -> CommandResult<DeleteResult> {
     let file_path = Path::new(&path);
 
     if !file_path.exists() {
-        return Err("Файл не существует".to_string());
+        return Err(AppError::not_found(file_path));
+    }
+
+    let is_dir = file_path.is_dir();
+    if is_dir && !is_inside_library(file_path, &library_roots(&app)) {
+        return Err(AppError::OutsideLibrary { path });
     }
 
-    if file_path.is_file() {
-        fs::remove_file(&path).map_err(|e| format!("Не удалось удалить файл: {}", e))?;
-    } else if file_path.is_dir() {
-        fs::remove_dir_all(&path).map_err(|e| format!("Не удалось удалить папку: {}", e))?;
+    let sidecar = sidecar_path(file_path);
+    let sidecar = (!is_dir && sidecar.is_file()).then_some(sidecar);
+
+    if permanent.unwrap_or(false) {
+        if is_dir {
+            fs::remove_dir_all(&path)
+                .map_err(|e| AppError::io("Не удалось удалить папку", &path, &e))?;
+        } else {
+            fs::remove_file(&path)
+                .map_err(|e| AppError::io("Не удалось удалить файл", &path, &e))?;
+            if let Some(sidecar) = &sidecar {
+                let _ = fs::remove_file(sidecar);
+            }
+        }
+
+        return Ok(DeleteResult {
+            path,
+            permanent: true,
+            trash_location: None,
+        });
+    }
+
+    let trash_location = move_to_trash(file_path)
+        .map_err(|e| AppError::from_core("Не удалось переместить в корзину", file_path, &e))?;
+
+    let mut operations = Vec::new();
+    if let Some(location) = &trash_location {
+        operations.push(Operation::Delete {
+            path: path.clone(),
+            trash_location: location.clone(),
+        });
+    }
+    // Sidecar с идентификатором уходит в корзину вместе с файлом
+    if let Some(sidecar) = sidecar {
+        if let Ok(Some(location)) = move_to_trash(&sidecar) {
+            operations.push(Operation::Delete {
+                path: sidecar.to_string_lossy().into_owned(),
+                trash_location: location,
+            });
+        }
     }
+    record_operation(&journal, "Удаление", operations);
 
-    Ok(())
+    Ok(DeleteResult {
+        path,
+        permanent: false,
+        trash_location,
+    })
 }
@@ -0,0 +1,188 @@
+// Bundle recent logs, app version and environment checks into a zip for bug reports
+
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tauri::{command, AppHandle, Manager};
+use zip::result::ZipError;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use super::error::{AppError, CommandResult};
+use crate::logging::LOG_DIR;
+use anomad_core::annotation_history::HISTORY_FILE;
+use anomad_core::everything_search::everything_version;
+use anomad_core::journal::JOURNAL_FILE;
+use anomad_core::pdfium::load_pdfium;
+use anomad_core::watch_config::WATCH_CONFIG_FILE;
+
+// Текущий файл и два последних переименованных - обычно этого хватает,
+// чтобы увидеть ошибку и то, что ей предшествовало
+const MAX_LOG_FILES: usize = 3;
+
+#[derive(Debug, Serialize)]
+pub struct EnvironmentCheck {
+    pub name: String,
+    pub ok: bool,
+    pub detail: String,
+}
+
+// Содержимое environment.json в архиве
+#[derive(Debug, Serialize)]
+pub struct DiagnosticsReport {
+    pub app_version: String,
+    pub os: &'static str,
+    pub arch: &'static str,
+    pub debug_build: bool,
+    pub created_at: String,
+    pub data_dir: String,
+    pub checks: Vec<EnvironmentCheck>,
+    pub log_files: Vec<String>,
+}
+
+fn check(name: &str, result: Result<String, String>) -> EnvironmentCheck {
+    let (ok, detail) = match result {
+        Ok(detail) => (true, detail),
+        Err(detail) => (false, detail),
+    };
+    EnvironmentCheck {
+        name: name.to_string(),
+        ok,
+        detail,
+    }
+}
+
+// Поврежденные файлы ядро молча сбрасывает при запуске, поэтому проверяем их отдельно
+fn data_file_check(data_dir: &Path, file_name: &str) -> EnvironmentCheck {
+    let result = match fs::read(data_dir.join(file_name)) {
+        Ok(content) => match serde_json::from_slice::<serde_json::Value>(&content) {
+            Ok(_) => Ok(format!("{} байт", content.len())),
+            Err(e) => Err(format!("Файл поврежден: {}", e)),
+        },
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok("Файла нет".to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    check(file_name, result)
+}
+
+fn data_dir_check(data_dir: &Path) -> EnvironmentCheck {
+    let probe = data_dir.join(".diagnostics-probe");
+    let result = fs::write(&probe, b"probe")
+        .and_then(|_| fs::remove_file(&probe))
+        .map(|_| "Запись разрешена".to_string())
+        .map_err(|e| e.to_string());
+    check("data_dir", result)
+}
+
+fn environment_checks(data_dir: &Path) -> Vec<EnvironmentCheck> {
+    vec![
+        check(
+            "everything",
+            everything_version()
+                .map(|version| format!("Everything {}", version))
+                .ok_or_else(|| "Everything не запущен или не отвечает".to_string()),
+        ),
+        check(
+            "pdfium",
+            load_pdfium()
+                .map(|_| "Библиотека загружена".to_string())
+                .map_err(|e| e.to_string()),
+        ),
+        data_dir_check(data_dir),
+        data_file_check(data_dir, JOURNAL_FILE),
+        data_file_check(data_dir, HISTORY_FILE),
+        data_file_check(data_dir, WATCH_CONFIG_FILE),
+    ]
+}
+
+// Последние файлы лога, новые первыми
+fn recent_log_files(log_dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(log_dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<(SystemTime, PathBuf)> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "log"))
+        .map(|path| {
+            let modified = fs::metadata(&path)
+                .and_then(|meta| meta.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            (modified, path)
+        })
+        .collect();
+    files.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    files
+        .into_iter()
+        .take(MAX_LOG_FILES)
+        .map(|(_, path)| path)
+        .collect()
+}
+
+fn write_bundle(
+    output_path: &Path,
+    report: &DiagnosticsReport,
+    log_files: &[PathBuf],
+) -> Result<(), ZipError> {
+    let mut zip = ZipWriter::new(File::create(output_path)?);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    zip.start_file("environment.json", options)?;
+    let json = serde_json::to_vec_pretty(report).map_err(io::Error::from)?;
+    zip.write_all(&json)?;
+
+    // Нечитаемый файл лога не повод отказываться от остального архива
+    for path in log_files {
+        let (Some(name), Ok(content)) = (path.file_name(), fs::read(path)) else {
+            log::warn!("Файл лога не добавлен в архив: {}", path.display());
+            continue;
+        };
+        zip.start_file(format!("logs/{}", name.to_string_lossy()), options)?;
+        zip.write_all(&content)?;
+    }
+
+    zip.finish()?;
+    Ok(())
+}
+
+// Архив для сообщения об ошибке: environment.json и logs/*.log
+#[command]
+pub async fn create_diagnostics_bundle(
+    app: AppHandle,
+    output_path: String,
+) -> CommandResult<String> {
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| AppError::failed("Не удалось определить папку данных", e))?;
+
+    // Записи из буфера логгера должны попасть в файл до копирования
+    log::logger().flush();
+    let log_files = recent_log_files(&data_dir.join(LOG_DIR));
+
+    let report = DiagnosticsReport {
+        app_version: app.package_info().version.to_string(),
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        debug_build: cfg!(debug_assertions),
+        created_at: chrono::Local::now().to_rfc3339(),
+        data_dir: data_dir.to_string_lossy().into_owned(),
+        checks: environment_checks(&data_dir),
+        log_files: log_files
+            .iter()
+            .filter_map(|path| path.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .collect(),
+    };
+
+    let action = "Не удалось создать диагностический архив";
+    write_bundle(Path::new(&output_path), &report, &log_files).map_err(|e| match e {
+        ZipError::Io(io_error) => AppError::io(action, &output_path, &io_error),
+        other => AppError::failed(action, other),
+    })?;
+
+    log::info!("Диагностический архив создан: {}", output_path);
+    Ok(output_path)
+}
@@ -1,49 +1,64 @@
 // Edit filename command
 
 use std::fs;
-use std::io;
 use std::path::PathBuf;
-use tauri::command;
+use tauri::{command, State};
+
+use super::error::{AppError, CommandResult};
+use super::journal::{record_operation, JournalState};
+use anomad_core::identity::move_sidecar;
+use anomad_core::journal::Operation;
 
 #[command]
 pub async fn edit_filename(
     original_full_path: String,
     new_filename: String,
-) -> Result<String, String> {
+    journal: State<'_, JournalState>,
+) -> CommandResult<String> {
     let original_path = PathBuf::from(&original_full_path);
 
     // Проверяем, существует ли оригинальный файл
     if !original_path.exists() {
-        return Err(format!(
-            "Оригинальный файл не найден: {}",
-            original_full_path
-        ));
+        return Err(AppError::not_found(&original_path));
     }
 
     // Создаем полный путь к новому файлу в той же директории
-    let parent_dir = original_path
-        .parent()
-        .ok_or_else(|| "Не удалось получить родительскую директорию файла".to_string())?;
+    let parent_dir = original_path.parent().ok_or_else(|| {
+        AppError::invalid(
+            "original_full_path",
+            "Не удалось получить родительскую директорию файла",
+        )
+    })?;
     let new_full_path = parent_dir.join(&new_filename);
 
     // Проверяем, существует ли файл с новым именем (чтобы избежать перезаписи)
     if new_full_path.exists() {
-        return Err(format!(
-            "Файл с именем '{}' уже существует в этой директории.",
-            new_filename
-        ));
+        return Err(AppError::AlreadyExists {
+            path: new_full_path.to_string_lossy().into_owned(),
+        });
     }
 
     // Переименовываем файл
     match fs::rename(&original_path, &new_full_path) {
-        Ok(_) => Ok(new_full_path.to_string_lossy().into_owned()),
-        Err(e) => {
-            // Обработка конкретных ошибок для более информативных сообщений
-            if e.kind() == io::ErrorKind::PermissionDenied {
-                Err(format!("Ошибка доступа при переименовании файла: {}. Убедитесь, что у приложения есть необходимые разрешения.", e))
-            } else {
-                Err(format!("Не удалось переименовать файл: {}", e))
-            }
+        Ok(_) => {
+            let _ = move_sidecar(&original_path, &new_full_path);
+            let new_full_path = new_full_path.to_string_lossy().into_owned();
+
+            record_operation(
+                &journal,
+                "Переименование",
+                vec![Operation::Rename {
+                    from: original_full_path,
+                    to: new_full_path.clone(),
+                }],
+            );
+            Ok(new_full_path)
         }
+        // Вид ошибки (нет доступа, файл открыт) передается в kind
+        Err(e) => Err(AppError::io(
+            "Не удалось переименовать файл",
+            &original_path,
+            &e,
+        )),
     }
 }
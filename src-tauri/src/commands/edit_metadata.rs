@@ -0,0 +1,113 @@
+// Batch metadata editing commands: preview and apply
+
+use std::path::Path;
+use tauri::{command, State};
+
+use super::error::{AppError, CommandResult};
+use super::journal::{record_operation, JournalState};
+use super::types::MetadataEditResult;
+use anomad_core::journal::{Operation, UndoBackup};
+use anomad_core::metadata_edit::{apply_changes, preview_changes, MetadataChanges};
+use anomad_core::pdf_metadata::load_pdf;
+use anomad_core::safe_write::save_pdf;
+
+// Ничего не записывает: показывает старые и новые значения для каждого файла
+#[command]
+pub async fn preview_metadata_edit(
+    file_paths: Vec<String>,
+    changes: MetadataChanges,
+) -> CommandResult<Vec<MetadataEditResult>> {
+    changes
+        .validate()
+        .map_err(|e| AppError::invalid("changes", format!("Некорректные изменения: {}", e)))?;
+
+    Ok(file_paths
+        .into_iter()
+        .map(|file_path| match load_pdf(Path::new(&file_path)) {
+            Ok((doc, _)) => MetadataEditResult {
+                changes: preview_changes(&doc, &changes),
+                path: file_path,
+                written: false,
+                error: None,
+            },
+            Err(e) => MetadataEditResult::failed(file_path, format!("Не удалось открыть PDF: {}", e)),
+        })
+        .collect())
+}
+
+// Ошибка в одном файле не останавливает остальные; файлы без изменений не перезаписываются.
+// Весь пакет попадает в журнал одной записью
+#[command]
+pub async fn apply_metadata_edit(
+    file_paths: Vec<String>,
+    changes: MetadataChanges,
+    journal: State<'_, JournalState>,
+) -> CommandResult<Vec<MetadataEditResult>> {
+    changes
+        .validate()
+        .map_err(|e| AppError::invalid("changes", format!("Некорректные изменения: {}", e)))?;
+
+    let mut operations = Vec::new();
+    let results = file_paths
+        .into_iter()
+        .map(|file_path| {
+            let (result, operation) = edit_file(file_path, &changes);
+            operations.extend(operation);
+            result
+        })
+        .collect();
+
+    record_operation(&journal, "Правка метаданных", operations);
+    Ok(results)
+}
+
+fn edit_file(file_path: String, changes: &MetadataChanges) -> (MetadataEditResult, Option<Operation>) {
+    let path = Path::new(&file_path);
+
+    let (mut doc, is_encrypted) = match load_pdf(path) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            let error = format!("Не удалось открыть PDF: {}", e);
+            return (MetadataEditResult::failed(file_path, error), None);
+        }
+    };
+
+    let preview = preview_changes(&doc, changes);
+    if !preview.iter().any(|c| c.changed) {
+        let result = MetadataEditResult {
+            path: file_path,
+            changes: preview,
+            written: false,
+            error: None,
+        };
+        return (result, None);
+    }
+
+    let backup = match UndoBackup::create(path) {
+        Ok(backup) => backup,
+        Err(e) => {
+            let error = format!("Не удалось создать резервную копию: {}", e);
+            return (MetadataEditResult::failed(file_path, error), None);
+        }
+    };
+
+    let result =
+        apply_changes(&mut doc, changes).and_then(|_| save_pdf(&mut doc, path, is_encrypted));
+    let operation = match result {
+        Ok(_) => backup.into_operation().ok(),
+        Err(_) => {
+            backup.discard();
+            None
+        }
+    };
+
+    let result = MetadataEditResult {
+        path: file_path,
+        changes: preview,
+        written: result.is_ok(),
+        error: result
+            .err()
+            .map(|e| format!("Не удалось записать метаданные: {}", e)),
+    };
+    (result, operation)
+}
@@ -0,0 +1,378 @@
+// Typed command error serialized for the frontend
+
+use anomad_core::ErrorKind;
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+// Ошибка команды. На фронтенд уходит объект { code, message, ...поля }:
+// code не меняется и подходит для логики, message - готовый текст для пользователя.
+// action - локализованное описание действия ("Не удалось удалить файл"), входит в message
+#[derive(Debug)]
+pub enum AppError {
+    // Файл или папка не существует
+    NotFound {
+        path: String,
+    },
+    // Путь уже занят (переименование, перенос)
+    AlreadyExists {
+        path: String,
+    },
+    NotADirectory {
+        path: String,
+    },
+    // Файл открыт в другой программе и не может быть записан или перемещен
+    Locked {
+        action: String,
+        path: String,
+        detail: String,
+    },
+    // Ошибка файловой системы; kind - вид ошибки ОС (см. io_kind)
+    Io {
+        action: String,
+        path: String,
+        kind: &'static str,
+        detail: String,
+    },
+    // PDF не удалось разобрать, прочитать аннотации или сохранить
+    Pdf {
+        action: String,
+        path: String,
+        detail: String,
+    },
+    // Зашифрованный PDF: его нельзя расшифровать или записать обратно
+    Encrypted {
+        action: String,
+        path: String,
+        detail: String,
+    },
+    // Everything не запущен или не ответил
+    SearchUnavailable,
+    // Недоступен другой внешний компонент: pdfium, системная корзина
+    Unavailable {
+        action: String,
+        path: String,
+        detail: String,
+    },
+    InvalidArgument {
+        field: &'static str,
+        detail: String,
+    },
+    AlreadyWatching {
+        path: String,
+    },
+    NotWatching {
+        path: String,
+    },
+    // Папки удаляются только внутри папок библиотеки
+    OutsideLibrary {
+        path: String,
+    },
+    // Прочее: внешние программы, pdfium, журнал, настройки
+    Failed {
+        action: String,
+        path: Option<String>,
+        detail: String,
+    },
+}
+
+pub type CommandResult<T> = Result<T, AppError>;
+
+fn path_string(path: impl AsRef<Path>) -> String {
+    path.as_ref().to_string_lossy().into_owned()
+}
+
+// Стабильное имя вида ошибки ОС. Файл, открытый в другой программе,
+// в Windows дает ERROR_SHARING_VIOLATION / ERROR_LOCK_VIOLATION - выделяем его отдельно
+pub fn io_kind(error: &io::Error) -> &'static str {
+    if cfg!(windows) && matches!(error.raw_os_error(), Some(32 | 33)) {
+        return "locked";
+    }
+    match error.kind() {
+        io::ErrorKind::NotFound => "not_found",
+        io::ErrorKind::PermissionDenied => "permission_denied",
+        io::ErrorKind::AlreadyExists => "already_exists",
+        io::ErrorKind::InvalidInput => "invalid_input",
+        io::ErrorKind::InvalidData => "invalid_data",
+        io::ErrorKind::UnexpectedEof => "unexpected_eof",
+        io::ErrorKind::TimedOut => "timed_out",
+        io::ErrorKind::Interrupted => "interrupted",
+        io::ErrorKind::WriteZero => "write_zero",
+        io::ErrorKind::OutOfMemory => "out_of_memory",
+        io::ErrorKind::Unsupported => "unsupported",
+        _ => "other",
+    }
+}
+
+impl AppError {
+    pub fn not_found(path: impl AsRef<Path>) -> Self {
+        AppError::NotFound {
+            path: path_string(path),
+        }
+    }
+
+    pub fn io(action: &str, path: impl AsRef<Path>, error: &io::Error) -> Self {
+        match io_kind(error) {
+            "locked" => AppError::Locked {
+                action: action.to_string(),
+                path: path_string(path),
+                detail: error.to_string(),
+            },
+            kind => AppError::Io {
+                action: action.to_string(),
+                path: path_string(path),
+                kind,
+                detail: error.to_string(),
+            },
+        }
+    }
+
+    pub fn invalid(field: &'static str, detail: impl fmt::Display) -> Self {
+        AppError::InvalidArgument {
+            field,
+            detail: detail.to_string(),
+        }
+    }
+
+    pub fn failed(action: &str, error: impl fmt::Display) -> Self {
+        AppError::Failed {
+            action: action.to_string(),
+            path: None,
+            detail: error.to_string(),
+        }
+    }
+
+    // Ошибки ядра: каждому виду ErrorKind соответствует свой code, поэтому фронтенд
+    // отличает открытый в читалке файл или занятый путь от прочих сбоев
+    pub fn from_core(action: &str, path: impl AsRef<Path>, error: &anomad_core::Error) -> Self {
+        let (action, path, detail) = (action.to_string(), path_string(path), error.to_string());
+        match error.kind() {
+            ErrorKind::NotFound => AppError::NotFound { path },
+            ErrorKind::AlreadyExists => AppError::AlreadyExists { path },
+            ErrorKind::Locked => AppError::Locked {
+                action,
+                path,
+                detail,
+            },
+            ErrorKind::PermissionDenied | ErrorKind::Io => AppError::Io {
+                action,
+                path,
+                kind: match error.io_error() {
+                    Some(io_error) => io_kind(io_error),
+                    None if error.kind() == ErrorKind::PermissionDenied => "permission_denied",
+                    None => "other",
+                },
+                detail,
+            },
+            ErrorKind::InvalidPdf => AppError::Pdf {
+                action,
+                path,
+                detail,
+            },
+            ErrorKind::Encrypted => AppError::Encrypted {
+                action,
+                path,
+                detail,
+            },
+            ErrorKind::InvalidInput => AppError::InvalidArgument {
+                field: "input",
+                detail: format!("{}: {}", action, detail),
+            },
+            ErrorKind::Unavailable => AppError::Unavailable {
+                action,
+                path,
+                detail,
+            },
+            ErrorKind::Other => AppError::Failed {
+                action,
+                path: Some(path),
+                detail,
+            },
+        }
+    }
+
+    // Ошибки notify: io-ошибку (нет доступа, исчерпан лимит inotify) передаем как Io
+    pub fn from_notify(action: &str, path: impl AsRef<Path>, error: &notify::Error) -> Self {
+        match &error.kind {
+            notify::ErrorKind::Io(io_error) => AppError::io(action, path, io_error),
+            _ => AppError::Failed {
+                action: action.to_string(),
+                path: Some(path_string(path)),
+                detail: error.to_string(),
+            },
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::NotFound { .. } => "not_found",
+            AppError::AlreadyExists { .. } => "already_exists",
+            AppError::NotADirectory { .. } => "not_a_directory",
+            AppError::Locked { .. } => "locked",
+            AppError::Io { .. } => "io",
+            AppError::Pdf { .. } => "pdf",
+            AppError::Encrypted { .. } => "encrypted",
+            AppError::SearchUnavailable => "search_unavailable",
+            AppError::Unavailable { .. } => "unavailable",
+            AppError::InvalidArgument { .. } => "invalid_argument",
+            AppError::AlreadyWatching { .. } => "already_watching",
+            AppError::NotWatching { .. } => "not_watching",
+            AppError::OutsideLibrary { .. } => "outside_library",
+            AppError::Failed { .. } => "failed",
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::NotFound { path } => write!(f, "Файл не найден: {}", path),
+            AppError::AlreadyExists { path } => write!(f, "Файл уже существует: {}", path),
+            AppError::NotADirectory { path } => write!(f, "Папка не найдена: {}", path),
+            AppError::Io {
+                action,
+                kind,
+                detail,
+                ..
+            } => match *kind {
+                "permission_denied" => write!(
+                    f,
+                    "{}: нет доступа. Убедитесь, что у приложения есть необходимые разрешения ({})",
+                    action, detail
+                ),
+                _ => write!(f, "{}: {}", action, detail),
+            },
+            AppError::Locked { action, detail, .. } => {
+                write!(f, "{}: файл открыт в другой программе ({})", action, detail)
+            }
+            AppError::Pdf { action, detail, .. } => write!(f, "{}: {}", action, detail),
+            AppError::Encrypted { action, detail, .. } => {
+                write!(f, "{}: PDF зашифрован ({})", action, detail)
+            }
+            AppError::SearchUnavailable => {
+                write!(
+                    f,
+                    "Ошибка выполнения поиска: Everything не запущен или не отвечает"
+                )
+            }
+            AppError::Unavailable { action, detail, .. } => write!(f, "{}: {}", action, detail),
+            AppError::InvalidArgument { detail, .. } => write!(f, "{}", detail),
+            AppError::AlreadyWatching { path } => {
+                write!(f, "Директория '{}' уже отслеживается.", path)
+            }
+            AppError::NotWatching { path } => {
+                write!(f, "Директория '{}' не отслеживается", path)
+            }
+            AppError::OutsideLibrary { path } => write!(
+                f,
+                "Папку можно удалить, только если она находится внутри папки библиотеки: {}",
+                path
+            ),
+            AppError::Failed { action, detail, .. } => write!(f, "{}: {}", action, detail),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("code", self.code())?;
+        map.serialize_entry("message", &self.to_string())?;
+        match self {
+            AppError::NotFound { path }
+            | AppError::AlreadyExists { path }
+            | AppError::NotADirectory { path }
+            | AppError::AlreadyWatching { path }
+            | AppError::NotWatching { path }
+            | AppError::OutsideLibrary { path } => {
+                map.serialize_entry("path", path)?;
+            }
+            AppError::Io {
+                path, kind, detail, ..
+            } => {
+                map.serialize_entry("path", path)?;
+                map.serialize_entry("kind", kind)?;
+                map.serialize_entry("detail", detail)?;
+            }
+            AppError::Locked { path, detail, .. }
+            | AppError::Pdf { path, detail, .. }
+            | AppError::Encrypted { path, detail, .. }
+            | AppError::Unavailable { path, detail, .. } => {
+                map.serialize_entry("path", path)?;
+                map.serialize_entry("detail", detail)?;
+            }
+            AppError::SearchUnavailable => {}
+            AppError::InvalidArgument { field, detail } => {
+                map.serialize_entry("field", field)?;
+                map.serialize_entry("detail", detail)?;
+            }
+            AppError::Failed { path, detail, .. } => {
+                if let Some(path) = path {
+                    map.serialize_entry("path", path)?;
+                }
+                map.serialize_entry("detail", detail)?;
+            }
+        }
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn serialized(kind: ErrorKind) -> Value {
+        let error = anomad_core::Error::new(kind, "core detail");
+        serde_json::to_value(AppError::from_core("Действие", "/library/book.pdf", &error)).unwrap()
+    }
+
+    #[test]
+    fn every_core_error_kind_has_its_own_code() {
+        let expected = [
+            (ErrorKind::NotFound, "not_found"),
+            (ErrorKind::AlreadyExists, "already_exists"),
+            (ErrorKind::PermissionDenied, "io"),
+            (ErrorKind::Locked, "locked"),
+            (ErrorKind::Io, "io"),
+            (ErrorKind::InvalidPdf, "pdf"),
+            (ErrorKind::Encrypted, "encrypted"),
+            (ErrorKind::InvalidInput, "invalid_argument"),
+            (ErrorKind::Unavailable, "unavailable"),
+            (ErrorKind::Other, "failed"),
+        ];
+        for (kind, code) in expected {
+            let value = serialized(kind);
+            assert_eq!(value["code"], code, "{:?}: {}", kind, value);
+            assert!(value["message"].as_str().is_some_and(|m| !m.is_empty()));
+        }
+    }
+
+    #[test]
+    fn core_errors_keep_path_and_os_kind() {
+        assert_eq!(
+            serialized(ErrorKind::Locked),
+            json!({
+                "code": "locked",
+                "message": "Действие: файл открыт в другой программе (core detail)",
+                "path": "/library/book.pdf",
+                "detail": "core detail",
+            })
+        );
+        assert_eq!(
+            serialized(ErrorKind::PermissionDenied)["kind"],
+            "permission_denied"
+        );
+        assert_eq!(serialized(ErrorKind::Io)["kind"], "other");
+
+        let denied = io::Error::from(io::ErrorKind::PermissionDenied);
+        let error = anomad_core::Error::from(denied);
+        let value =
+            serde_json::to_value(AppError::from_core("Действие", "/book.pdf", &error)).unwrap();
+        assert_eq!(value["code"], "io");
+        assert_eq!(value["kind"], "permission_denied");
+    }
+}
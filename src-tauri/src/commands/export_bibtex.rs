@@ -0,0 +1,49 @@
+// Export BibTeX file for a list of files command
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use tauri::command;
+
+use super::error::{AppError, CommandResult};
+use super::types::ExportBibtexResult;
+use anomad_core::bibtex::{citation_key, format_entry, read_bib_source};
+
+// Файлы, которые не удалось прочитать, не прерывают экспорт, а попадают в failed
+#[command]
+pub async fn export_bibtex(
+    file_paths: Vec<String>,
+    output_path: String,
+) -> CommandResult<ExportBibtexResult> {
+    let mut used_keys = HashSet::new();
+    let mut entries = Vec::new();
+    let mut failed = Vec::new();
+
+    for file_path in file_paths {
+        let path = Path::new(&file_path);
+        if !path.is_file() {
+            failed.push(file_path);
+            continue;
+        }
+
+        match read_bib_source(path) {
+            Ok(source) => {
+                let key = citation_key(&source, &mut used_keys);
+                entries.push(format_entry(&key, &source));
+            }
+            Err(e) => {
+                log::warn!("Не удалось прочитать {}: {}", file_path, e);
+                failed.push(file_path);
+            }
+        }
+    }
+
+    fs::write(&output_path, entries.join("\n"))
+        .map_err(|e| AppError::io("Не удалось записать BibTeX", &output_path, &e))?;
+
+    Ok(ExportBibtexResult {
+        path: output_path,
+        exported: entries.len(),
+        failed,
+    })
+}
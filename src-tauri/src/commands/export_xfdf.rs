@@ -0,0 +1,20 @@
+// Export annotations to XFDF command
+
+use std::path::Path;
+use tauri::command;
+
+use super::error::{AppError, CommandResult};
+
+// Без output_path пишем sidecar рядом с PDF: book.pdf -> book.xfdf
+#[command]
+pub async fn export_xfdf(file_path: String, output_path: Option<String>) -> CommandResult<String> {
+    let path = Path::new(&file_path);
+    if !path.is_file() {
+        return Err(AppError::not_found(path));
+    }
+
+    let output = anomad_core::xfdf::export_xfdf(path, output_path.as_deref().map(Path::new))
+        .map_err(|e| AppError::from_core("Не удалось экспортировать XFDF", path, &e))?;
+
+    Ok(output.to_string_lossy().into_owned())
+}
@@ -0,0 +1,338 @@
+// Watch individual files command: highlights are re-extracted after every save
+
+use notify::{Event, RecommendedWatcher, RecursiveMode, Result as NotifyResult, Watcher};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{command, AppHandle, Emitter, Manager, State};
+use tokio::sync::mpsc;
+
+use super::annotation_history::{record_extracted, record_highlights, HistoryState};
+use super::error::{AppError, CommandResult};
+use super::filename_patterns::load_filename_patterns;
+use super::watch_restore::WatchConfigState;
+use super::watcher_commands::FileChangeEvent;
+use anomad_core::file_lock::is_file_locked;
+use anomad_core::filename_patterns::FilenamePattern;
+use anomad_core::get_file_highlights::{get_file_highlights, Highlight};
+use anomad_core::highlight_diff::{diff_highlights, HighlightDiff};
+use anomad_core::watch_events::{ChangeBatcher, ChangeKind, LogicalChange, FLUSH_INTERVAL};
+
+// Сколько ждем, пока читалка отпустит файл после сохранения
+const LOCK_TIMEOUT: Duration = Duration::from_secs(30);
+const LOCK_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+// Отслеживаемый файл -> пометки из последнего извлечения
+type WatchedHighlights = Arc<Mutex<HashMap<PathBuf, Vec<Highlight>>>>;
+
+#[derive(Default)]
+pub struct WatchedFiles {
+    // Создается при первом вызове watch_files
+    watcher: Option<RecommendedWatcher>,
+    // Отслеживаем папки файлов, а не сами файлы: сохранение через временный файл
+    // заменяет файл новым, и наблюдение за старым терялось бы
+    dirs: HashSet<PathBuf>,
+    files: WatchedHighlights,
+}
+
+pub type FileWatcherState = Mutex<WatchedFiles>;
+
+#[derive(Debug, Serialize, Clone)]
+pub struct WatchFilesResult {
+    pub watched: Vec<String>,
+    // Файлы, которых нет на диске; они не отслеживаются
+    pub missing: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct HighlightsChangedEvent {
+    pub file_path: String,
+    #[serde(flatten)]
+    pub diff: HighlightDiff,
+}
+
+// Заменяет набор отслеживаемых файлов (например, все файлы активного проекта).
+// Пустой список прекращает отслеживание
+#[command]
+pub async fn watch_files(
+    app_handle: AppHandle,
+    file_paths: Vec<String>,
+    config: State<'_, WatchConfigState>,
+) -> CommandResult<WatchFilesResult> {
+    // Извлечение пометок новых файлов читает PDF - не держим этим поток асинхронных команд
+    let app = app_handle.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        set_watched_files(&app, &app.state::<FileWatcherState>(), file_paths)
+    })
+    .await
+    .map_err(|e| AppError::failed("Не удалось обновить отслеживаемые файлы", e))??;
+
+    let mut config = config.lock().unwrap();
+    config.set_files(
+        result
+            .watched
+            .iter()
+            .chain(&result.missing)
+            .map(PathBuf::from),
+    );
+    config.save().map_err(|e| {
+        AppError::from_core(
+            "Не удалось сохранить настройки отслеживания",
+            config.file(),
+            &e,
+        )
+    })?;
+    Ok(result)
+}
+
+// Замена набора файлов - и из команды, и при восстановлении после запуска.
+// Пометки новых файлов извлекаются без блокировок: чтение большого PDF не должно
+// задерживать обработку событий и другие команды
+pub fn set_watched_files(
+    app_handle: &AppHandle,
+    state: &FileWatcherState,
+    file_paths: Vec<String>,
+) -> CommandResult<WatchFilesResult> {
+    let (existing, missing): (Vec<PathBuf>, Vec<PathBuf>) = file_paths
+        .iter()
+        .map(PathBuf::from)
+        .partition(|path| path.is_file());
+
+    let known: HashSet<PathBuf> = {
+        let watched = state.lock().unwrap();
+        let files = watched.files.lock().unwrap();
+        files.keys().cloned().collect()
+    };
+
+    // Для новых файлов запоминаем текущие пометки - от них считается разница после сохранения
+    let mut extracted = Vec::new();
+    for path in existing.iter().filter(|path| !known.contains(*path)) {
+        if let Ok(highlights) = get_file_highlights(&path.to_string_lossy()) {
+            extracted.push((path.clone(), highlights));
+        }
+    }
+    record_extracted(&app_handle.state::<HistoryState>(), &extracted);
+
+    let mut guard = state.lock().unwrap();
+    let watched = &mut *guard;
+    if watched.watcher.is_none() && !existing.is_empty() {
+        let watcher = spawn_watcher(app_handle, watched.files.clone()).map_err(|e| {
+            AppError::from_notify("Не удалось запустить отслеживание", &existing[0], &e)
+        })?;
+        watched.watcher = Some(watcher);
+    }
+
+    let dirs: HashSet<PathBuf> = existing
+        .iter()
+        .filter_map(|path| path.parent().map(Path::to_path_buf))
+        .collect();
+    if let Some(watcher) = watched.watcher.as_mut() {
+        for dir in watched.dirs.difference(&dirs) {
+            let _ = watcher.unwatch(dir);
+        }
+        for dir in dirs.difference(&watched.dirs) {
+            watcher
+                .watch(dir, RecursiveMode::NonRecursive)
+                .map_err(|e| AppError::from_notify("Не удалось отслеживать папку", dir, &e))?;
+        }
+    }
+    watched.dirs = dirs;
+
+    {
+        let mut files = watched.files.lock().unwrap();
+        let mut previous = std::mem::take(&mut *files);
+        let mut extracted: HashMap<PathBuf, Vec<Highlight>> = extracted.into_iter().collect();
+        for path in &existing {
+            // Пока шло извлечение, обработчик событий мог обновить пометки - они новее
+            let highlights = previous
+                .remove(path)
+                .or_else(|| extracted.remove(path))
+                .unwrap_or_default();
+            files.insert(path.clone(), highlights);
+        }
+    }
+
+    if existing.is_empty() {
+        // Удаление watcher'а закрывает канал, и задача обработки событий завершается
+        watched.watcher = None;
+    }
+
+    let to_strings = |paths: Vec<PathBuf>| -> Vec<String> {
+        paths
+            .into_iter()
+            .map(|p| p.to_string_lossy().into_owned())
+            .collect()
+    };
+    Ok(WatchFilesResult {
+        watched: to_strings(existing),
+        missing: to_strings(missing),
+    })
+}
+
+#[command]
+pub async fn list_watched_files(state: State<'_, FileWatcherState>) -> CommandResult<Vec<String>> {
+    let watched = state.lock().unwrap();
+    let files = watched.files.lock().unwrap();
+    let mut paths: Vec<String> = files
+        .keys()
+        .map(|p| p.to_string_lossy().into_owned())
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+// Останавливает отслеживание файлов при закрытии окна
+pub fn stop_file_watcher(state: &FileWatcherState) {
+    if let Ok(mut watched) = state.lock() {
+        watched.watcher = None;
+        watched.dirs.clear();
+    }
+}
+
+fn spawn_watcher(
+    app_handle: &AppHandle,
+    files: WatchedHighlights,
+) -> NotifyResult<RecommendedWatcher> {
+    let (tx, mut rx) = mpsc::channel(100);
+    let watcher = notify::recommended_watcher(move |res: NotifyResult<Event>| {
+        if let Ok(event) = res {
+            let _ = tx.blocking_send(event);
+        }
+    })?;
+
+    let app_handle = app_handle.clone();
+    let patterns = load_filename_patterns(&app_handle);
+
+    tauri::async_runtime::spawn(async move {
+        // В папках бывают и другие файлы - сообщаем только об отслеживаемых
+        let mut batcher = ChangeBatcher::new(Vec::new(), Vec::new());
+        loop {
+            let event = if batcher.is_idle() {
+                rx.recv().await
+            } else {
+                match tokio::time::timeout(FLUSH_INTERVAL, rx.recv()).await {
+                    Ok(event) => event,
+                    Err(_) => {
+                        handle_changes(&app_handle, &mut batcher, &files, &patterns);
+                        continue;
+                    }
+                }
+            };
+
+            match event {
+                Some(event) => {
+                    let known: Vec<PathBuf> = files.lock().unwrap().keys().cloned().collect();
+                    batcher.add_known(known);
+                    batcher.push(&event, Instant::now());
+                }
+                None => break,
+            }
+            handle_changes(&app_handle, &mut batcher, &files, &patterns);
+        }
+    });
+
+    Ok(watcher)
+}
+
+// Пометки обновляются в отдельной задаче: ожидание занятого файла не должно
+// останавливать разбор событий, иначе очередь notify переполняется
+fn handle_changes(
+    app_handle: &AppHandle,
+    batcher: &mut ChangeBatcher,
+    files: &WatchedHighlights,
+    patterns: &[FilenamePattern],
+) {
+    let changes: Vec<LogicalChange> = {
+        let files = files.lock().unwrap();
+        batcher
+            .flush(Instant::now(), |p| p.is_file())
+            .into_iter()
+            .filter(|change| files.contains_key(change.old_path.as_ref().unwrap_or(&change.path)))
+            .collect()
+    };
+    if changes.is_empty() {
+        return;
+    }
+
+    let mut events = Vec::new();
+    let mut refreshing = Vec::new();
+    for change in changes {
+        match change.kind {
+            ChangeKind::Renamed => {
+                // Файл переименован - отслеживаем его под новым именем
+                let mut files = files.lock().unwrap();
+                if let Some(old_path) = &change.old_path {
+                    if let Some(highlights) = files.remove(old_path) {
+                        files.insert(change.path.clone(), highlights);
+                    }
+                }
+            }
+            ChangeKind::Created | ChangeKind::Modified => {
+                // Каждый файл ждет, пока читалка его отпустит, - ждем все файлы сразу
+                let path = change.path.clone();
+                let app_handle = app_handle.clone();
+                let files = files.clone();
+                refreshing.push(tauri::async_runtime::spawn(async move {
+                    let diff = refresh_highlights(&app_handle, &path, &files).await;
+                    diff.map(|diff| HighlightsChangedEvent {
+                        file_path: path.to_string_lossy().to_string(),
+                        diff,
+                    })
+                }));
+            }
+            ChangeKind::Deleted => {}
+        }
+
+        events.push(FileChangeEvent::from_change(change, patterns));
+    }
+
+    let _ = app_handle.emit("files-changed", &events);
+    if refreshing.is_empty() {
+        return;
+    }
+
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut refreshed = Vec::new();
+        for task in refreshing {
+            if let Ok(Some(event)) = task.await {
+                refreshed.push(event);
+            }
+        }
+        if !refreshed.is_empty() {
+            let _ = app_handle.emit("highlights-changed", &refreshed);
+        }
+    });
+}
+
+// Извлекает пометки заново, когда читалка отпустит файл; None - пометки не изменились
+async fn refresh_highlights(
+    app_handle: &AppHandle,
+    path: &Path,
+    files: &WatchedHighlights,
+) -> Option<HighlightDiff> {
+    let started = Instant::now();
+    while is_file_locked(path) && started.elapsed() < LOCK_TIMEOUT {
+        tokio::time::sleep(LOCK_POLL_INTERVAL).await;
+    }
+
+    // Файл мог так и остаться занятым - читать его это обычно не мешает
+    let path_str = path.to_string_lossy().into_owned();
+    let current = tauri::async_runtime::spawn_blocking(move || get_file_highlights(&path_str))
+        .await
+        .ok()?
+        .ok()?;
+    record_highlights(&app_handle.state::<HistoryState>(), path, &current);
+    let mut files = files.lock().unwrap();
+    let previous = files.get(path)?;
+    let diff = diff_highlights(previous, &current);
+    files.insert(path.to_path_buf(), current);
+
+    if diff.is_empty() {
+        None
+    } else {
+        Some(diff)
+    }
+}
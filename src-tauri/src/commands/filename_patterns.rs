@@ -0,0 +1,50 @@
+// Filename patterns: loading from settings and testing command
+
+use tauri::{command, AppHandle, Runtime};
+
+use super::error::{AppError, CommandResult};
+use super::settings::read_setting;
+use super::types::PatternTestResult;
+use anomad_core::filename_patterns::{compile_patterns, FilenamePattern, DEFAULT_PATTERNS};
+
+const PATTERNS_KEY: &str = "filenamePatterns";
+
+// Шаблоны из настроек; если их нет или ни один не разобрался - прежнее правило
+pub fn load_filename_patterns<R: Runtime>(app: &AppHandle<R>) -> Vec<FilenamePattern> {
+    let configured: Vec<String> = read_setting(app, PATTERNS_KEY).unwrap_or_default();
+
+    let patterns = compile_patterns(&configured);
+    if patterns.is_empty() {
+        compile_patterns(&DEFAULT_PATTERNS)
+    } else {
+        patterns
+    }
+}
+
+// Проверка шаблона на примерах имен до сохранения в настройках
+#[command]
+pub async fn test_filename_pattern(
+    pattern: String,
+    file_names: Vec<String>,
+) -> CommandResult<Vec<PatternTestResult>> {
+    let pattern = FilenamePattern::parse(pattern.trim())
+        .map_err(|e| AppError::invalid("pattern", format!("Некорректный шаблон: {:#}", e)))?;
+
+    Ok(file_names
+        .into_iter()
+        .map(|file_name| {
+            // Расширение отбрасываем так же, как при разборе настоящих файлов
+            let stem = std::path::Path::new(&file_name)
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let parsed = pattern.parse_file_name(&stem);
+
+            PatternTestResult {
+                file_name,
+                matched: parsed.is_some(),
+                parsed,
+            }
+        })
+        .collect())
+}
@@ -0,0 +1,14 @@
+// Find duplicate files command
+
+use std::path::PathBuf;
+use tauri::command;
+
+use super::error::CommandResult;
+use anomad_core::duplicates::{group_duplicates, DuplicateGroup};
+
+// Файлы обычно берутся из результатов поиска Everything
+#[command]
+pub async fn find_duplicates(file_paths: Vec<String>) -> CommandResult<Vec<DuplicateGroup>> {
+    let paths: Vec<PathBuf> = file_paths.into_iter().map(PathBuf::from).collect();
+    Ok(group_duplicates(&paths))
+}
@@ -0,0 +1,28 @@
+// Get BibTeX entry for a single file command
+
+use std::collections::HashSet;
+use std::path::Path;
+use tauri::command;
+
+use super::error::{AppError, CommandResult};
+use super::types::BibtexEntry;
+use anomad_core::bibtex::{citation_key, format_entry, read_bib_source};
+
+#[command]
+pub async fn get_bibtex(file_path: String) -> CommandResult<BibtexEntry> {
+    let path = Path::new(&file_path);
+    if !path.is_file() {
+        return Err(AppError::not_found(path));
+    }
+
+    let source = read_bib_source(path)
+        .map_err(|e| AppError::from_core("Не удалось прочитать PDF", path, &e))?;
+    let key = citation_key(&source, &mut HashSet::new());
+
+    Ok(BibtexEntry {
+        path: file_path,
+        bibtex: format_entry(&key, &source),
+        key,
+        identifiers: source.identifiers,
+    })
+}
@@ -1,12 +1,17 @@
 // Everything search command
 
-use tauri::command;
+use tauri::{command, State};
 
+use super::annotation_history::{record_search_results, HistoryState};
+use super::error::{AppError, CommandResult};
 use super::types::{SearchParams, SearchResult};
-use super::utils::everything_search::everything_search;
+use anomad_core::everything_search::everything_search;
 
 #[command]
-pub async fn get_everything(params: SearchParams) -> Result<SearchResult, String> {
+pub async fn get_everything(
+    params: SearchParams,
+    history: State<'_, HistoryState>,
+) -> CommandResult<SearchResult> {
     if params.query.trim().is_empty() {
         return Ok(SearchResult {
             items: Vec::new(),
@@ -15,7 +20,10 @@ pub async fn get_everything(params: SearchParams) -> Result<SearchResult, String
     }
 
     match everything_search(&params, false).await {
-        Some((items, has_more)) => Ok(SearchResult { items, has_more }),
-        None => Err("Ошибка выполнения поиска".to_string()),
+        Some((items, has_more)) => {
+            record_search_results(&history, &items);
+            Ok(SearchResult { items, has_more })
+        }
+        None => Err(AppError::SearchUnavailable),
     }
 }
@@ -1,19 +1,26 @@
 // Get highlights for files command
 
-use tauri::command;
+use tauri::{command, State};
 
+use super::annotation_history::{record_extracted, HistoryState};
+use super::error::CommandResult;
 use super::types::FileResult;
-use super::utils::get_file_highlights::get_file_highlights;
-use super::utils::get_file_info::get_file_info;
+use anomad_core::get_file_highlights::get_file_highlights;
+use anomad_core::get_file_info_all_meta::get_file_info_all_meta;
 
 // Эта функция всегда выдает все файлы в таком же порядке, даже если хайлайтов нет !
 #[command]
-pub async fn get_highlights_for_files(paths: Vec<String>) -> Result<Vec<FileResult>, String> {
+pub async fn get_highlights_for_files(
+    paths: Vec<String>,
+    history: State<'_, HistoryState>,
+) -> CommandResult<Vec<FileResult>> {
     if paths.is_empty() {
         return Ok(Vec::new());
     }
 
     let mut results = Vec::with_capacity(paths.len());
+    // Успешно извлеченные пометки для истории; файлы с ошибкой чтения в нее не попадают
+    let mut extracted = Vec::new();
 
     for path in paths {
         let path_ref = std::path::Path::new(&path);
@@ -35,10 +42,13 @@ pub async fn get_highlights_for_files(paths: Vec<String>) -> Result<Vec<FileResu
             .and_then(|s| s.to_str())
             .map(|s| s.to_lowercase());
 
-        let info = get_file_info(&path);
+        let info = get_file_info_all_meta(&path);
         
         let highlights = match get_file_highlights(&path) {
-            Ok(hl) => Some(hl),
+            Ok(hl) => {
+                extracted.push((path_ref.to_path_buf(), hl.clone()));
+                Some(hl)
+            }
             Err(_) => Some(Vec::new()),
         };
 
@@ -55,9 +65,13 @@ pub async fn get_highlights_for_files(paths: Vec<String>) -> Result<Vec<FileResu
             pdf_title: info.pdf_title,
             pdf_author: info.pdf_author,
             pdf_creator: info.pdf_creator,
+            pdf_details: info.pdf_details,
+            content_hash: info.fingerprint.as_ref().map(|f| f.content_hash.clone()),
+            trailer_id: info.fingerprint.and_then(|f| f.trailer_id),
             highlights,
         });
     }
 
+    record_extracted(&history, &extracted);
     Ok(results)
 }
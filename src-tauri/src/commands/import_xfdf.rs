@@ -0,0 +1,89 @@
+// Import annotations from XFDF command
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use tauri::{command, State};
+
+use super::error::{AppError, CommandResult};
+use super::journal::{record_operation, JournalState};
+use super::types::ImportXfdfResult;
+use anomad_core::annotations::{add_annotations, read_annotations};
+use anomad_core::journal::{Operation, UndoBackup};
+use anomad_core::pdf_metadata::load_pdf;
+use anomad_core::safe_write::save_pdf;
+use anomad_core::xfdf::xfdf_to_annotations;
+
+// Если output_path указан, аннотации пишутся в копию, а исходный PDF не меняется
+#[command]
+pub async fn import_xfdf(
+    file_path: String,
+    xfdf_path: String,
+    output_path: Option<String>,
+    journal: State<'_, JournalState>,
+) -> CommandResult<ImportXfdfResult> {
+    let path = Path::new(&file_path);
+    if !path.is_file() {
+        return Err(AppError::not_found(path));
+    }
+
+    let xml = fs::read_to_string(&xfdf_path)
+        .map_err(|e| AppError::io("Не удалось прочитать XFDF", &xfdf_path, &e))?;
+    let incoming = xfdf_to_annotations(&xml)
+        .map_err(|e| AppError::invalid("xfdf_path", format!("Некорректный XFDF: {}", e)))?;
+
+    let (mut doc, is_encrypted) =
+        load_pdf(path).map_err(|e| AppError::from_core("Не удалось открыть PDF", path, &e))?;
+
+    // Аннотации с уже существующим /NM не дублируем, чтобы повторный импорт ничего не менял
+    let existing_names: HashSet<String> = read_annotations(&doc)
+        .map_err(|e| AppError::from_core("Не удалось прочитать аннотации", path, &e))?
+        .into_iter()
+        .filter_map(|a| a.name)
+        .collect();
+
+    let (to_add, duplicates): (Vec<_>, Vec<_>) = incoming.into_iter().partition(|a| {
+        a.name
+            .as_ref()
+            .map_or(true, |name| !existing_names.contains(name))
+    });
+
+    let imported = add_annotations(&mut doc, &to_add)
+        .map_err(|e| AppError::from_core("Не удалось добавить аннотации", path, &e))?;
+    let skipped = duplicates.len() + (to_add.len() - imported);
+
+    let output = output_path.unwrap_or_else(|| file_path.clone());
+    if imported > 0 || output != file_path {
+        let output_file = Path::new(&output);
+        // Существующий файл отменяется восстановлением копии, новый - отправкой в корзину
+        let backup = if output_file.exists() {
+            Some(UndoBackup::create(output_file).map_err(|e| {
+                AppError::from_core("Не удалось создать резервную копию", output_file, &e)
+            })?)
+        } else {
+            None
+        };
+
+        if let Err(e) = save_pdf(&mut doc, output_file, is_encrypted) {
+            if let Some(backup) = backup {
+                backup.discard();
+            }
+            return Err(AppError::from_core("Не удалось сохранить PDF", &output, &e));
+        }
+
+        let operation = match backup {
+            Some(backup) => backup.into_operation().ok(),
+            None => Some(Operation::Copy {
+                from: file_path.clone(),
+                to: output.clone(),
+            }),
+        };
+        record_operation(&journal, "Импорт XFDF", operation.into_iter().collect());
+    }
+
+    Ok(ImportXfdfResult {
+        path: output,
+        imported,
+        skipped,
+    })
+}
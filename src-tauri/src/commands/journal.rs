@@ -0,0 +1,54 @@
+// Operation journal commands: list and undo
+
+use std::sync::{Arc, Mutex};
+use tauri::{command, State};
+
+use super::error::{AppError, CommandResult};
+use super::types::UndoResult;
+use anomad_core::journal::{Journal, JournalEntry, Operation};
+use anomad_core::ErrorKind;
+
+// Журнал создается в setup, когда известна папка данных приложения
+pub type JournalState = Arc<Mutex<Journal>>;
+
+const DEFAULT_LIMIT: usize = 50;
+
+// Ошибка журнала не отменяет уже выполненную операцию, поэтому только логируем
+pub fn record_operation(journal: &JournalState, description: &str, operations: Vec<Operation>) {
+    if let Err(e) = journal.lock().unwrap().record(description, operations) {
+        log::error!("Не удалось записать операцию в журнал: {}", e);
+    }
+}
+
+#[command]
+pub async fn list_operations(
+    limit: Option<usize>,
+    journal: State<'_, JournalState>,
+) -> CommandResult<Vec<JournalEntry>> {
+    Ok(journal.lock().unwrap().recent(limit.unwrap_or(DEFAULT_LIMIT)))
+}
+
+#[command]
+pub async fn undo_operation(
+    id: String,
+    journal: State<'_, JournalState>,
+) -> CommandResult<UndoResult> {
+    let errors: Vec<AppError> = journal
+        .lock()
+        .unwrap()
+        .undo(&id)
+        .map_err(|e| match e.kind() {
+            // Неизвестная или уже отмененная операция
+            ErrorKind::NotFound | ErrorKind::InvalidInput => AppError::invalid("id", e),
+            _ => AppError::failed("Не удалось отменить операцию", e),
+        })?
+        .into_iter()
+        .map(|(path, e)| AppError::from_core("Не удалось отменить операцию", path, &e))
+        .collect();
+
+    Ok(UndoResult {
+        id,
+        undone: errors.is_empty(),
+        errors,
+    })
+}
@@ -0,0 +1,87 @@
+// Merge annotations from several copies of the same PDF command
+
+use std::path::Path;
+use tauri::{command, State};
+
+use super::error::{AppError, CommandResult};
+use super::journal::{record_operation, JournalState};
+use super::types::MergeAnnotationsResult;
+use anomad_core::annotation_merge::plan_merge;
+use anomad_core::annotations::{add_annotations, read_annotations};
+use anomad_core::journal::UndoBackup;
+use anomad_core::pdf_metadata::load_pdf;
+use anomad_core::safe_write::save_pdf;
+
+// Объединение аннотаций копий source_paths в target_path. С dry_run только отчет
+#[command]
+pub async fn merge_annotations(
+    source_paths: Vec<String>,
+    target_path: String,
+    dry_run: bool,
+    journal: State<'_, JournalState>,
+) -> CommandResult<MergeAnnotationsResult> {
+    let target = Path::new(&target_path);
+    if !target.is_file() {
+        return Err(AppError::not_found(target));
+    }
+
+    let (mut doc, is_encrypted) =
+        load_pdf(target).map_err(|e| AppError::from_core("Не удалось открыть PDF", target, &e))?;
+    let existing = read_annotations(&doc)
+        .map_err(|e| AppError::from_core("Не удалось прочитать аннотации", target, &e))?;
+    let page_count = doc.get_pages().len();
+
+    let mut sources = Vec::new();
+    let mut failed = Vec::new();
+    for source_path in source_paths.into_iter().filter(|p| *p != target_path) {
+        let annotations = load_pdf(Path::new(&source_path))
+            .map_err(|e| format!("Не удалось открыть PDF: {}", e))
+            .and_then(|(source, _)| {
+                // Координаты имеют смысл только на тех же страницах
+                if source.get_pages().len() != page_count {
+                    return Err("число страниц отличается от целевого файла".to_string());
+                }
+                read_annotations(&source)
+                    .map_err(|e| format!("Не удалось прочитать аннотации: {}", e))
+            });
+
+        match annotations {
+            Ok(annotations) => sources.push((source_path, annotations)),
+            Err(e) => failed.push(format!("{}: {}", source_path, e)),
+        }
+    }
+
+    let plan = plan_merge(&existing, &sources);
+    let mut added = 0;
+
+    if !dry_run && !plan.to_add.is_empty() {
+        let backup = UndoBackup::create(target)
+            .map_err(|e| AppError::from_core("Не удалось создать резервную копию", target, &e))?;
+
+        let result = add_annotations(&mut doc, &plan.to_add)
+            .and_then(|count| save_pdf(&mut doc, target, is_encrypted).map(|_| count));
+        match result {
+            Ok(count) => {
+                added = count;
+                let operation = backup.into_operation().ok();
+                record_operation(&journal, "Объединение аннотаций", operation.into_iter().collect());
+            }
+            Err(e) => {
+                backup.discard();
+                return Err(AppError::from_core(
+                    "Не удалось сохранить PDF",
+                    target,
+                    &e,
+                ));
+            }
+        }
+    }
+
+    Ok(MergeAnnotationsResult {
+        target: target_path,
+        added: if dry_run { plan.to_add.len() } else { added },
+        duplicates: plan.duplicates,
+        conflicts: plan.conflicts,
+        failed,
+    })
+}
@@ -10,6 +10,25 @@ pub mod show_in_explorer;
 pub mod open_file_with;
 pub mod set_metadata;
 pub mod delete_file;
+pub mod export_xfdf;
+pub mod import_xfdf;
+pub mod get_bibtex;
+pub mod export_bibtex;
+pub mod relink_files;
+pub mod edit_metadata;
+pub mod filename_patterns;
+pub mod batch_rename;
+pub mod journal;
+pub mod organize_files;
+pub mod find_duplicates;
+pub mod merge_annotations;
+pub mod transfer_highlights;
+pub mod file_watcher;
+pub mod watch_restore;
+pub mod annotation_history;
+pub mod diagnostics;
+pub mod error;
+pub mod settings;
 pub mod types;
 
 pub use watcher_commands::*;
@@ -22,5 +41,20 @@ pub use show_in_explorer::*;
 pub use open_file_with::*;
 pub use set_metadata::*;
 pub use delete_file::*;
-
-mod utils;
+pub use export_xfdf::*;
+pub use import_xfdf::*;
+pub use get_bibtex::*;
+pub use export_bibtex::*;
+pub use relink_files::*;
+pub use edit_metadata::*;
+pub use filename_patterns::*;
+pub use batch_rename::*;
+pub use journal::*;
+pub use organize_files::*;
+pub use find_duplicates::*;
+pub use merge_annotations::*;
+pub use transfer_highlights::*;
+pub use file_watcher::*;
+pub use watch_restore::*;
+pub use annotation_history::*;
+pub use diagnostics::*;
@@ -4,10 +4,13 @@ use std::path::Path;
 use std::process::Command;
 use tauri::command;
 
+#[cfg(not(target_os = "windows"))]
+use super::error::AppError;
+use super::error::CommandResult;
 use super::types::{OpenFileParams, OpenFileResult};
 
 #[command]
-pub async fn open_file(params: OpenFileParams) -> Result<OpenFileResult, String> {
+pub async fn open_file(params: OpenFileParams) -> CommandResult<OpenFileResult> {
     let OpenFileParams { path, page, program_path } = params;
 
     #[cfg(target_os = "windows")]
@@ -51,7 +54,7 @@ pub async fn open_file(params: OpenFileParams) -> Result<OpenFileResult, String>
                     let mut opened_with = None;
                     for adobe_path in &adobe_paths {
                         if Path::new(adobe_path).exists() {
-                            println!("Найден Adobe Acrobat: {}", adobe_path);
+                            log::info!("Найден Adobe Acrobat: {}", adobe_path);
                             match Command::new(adobe_path)
                                 .arg("/A")
                                 .arg(format!("page={}", page_num))
@@ -99,7 +102,7 @@ pub async fn open_file(params: OpenFileParams) -> Result<OpenFileResult, String>
     {
         match Command::new("open").arg(&path).spawn() {
             Ok(_) => Ok(OpenFileResult { success: true, path: "open".to_string() }),
-            Err(e) => Err(format!("Ошибка при открытии файла: {}", e)),
+            Err(e) => Err(AppError::io("Ошибка при открытии файла", &path, &e)),
         }
     }
 
@@ -107,7 +110,7 @@ pub async fn open_file(params: OpenFileParams) -> Result<OpenFileResult, String>
     {
         match Command::new("xdg-open").arg(&path).spawn() {
             Ok(_) => Ok(OpenFileResult { success: true, path: "xdg-open".to_string() }),
-            Err(e) => Err(format!("Ошибка при открытии файла: {}", e)),
+            Err(e) => Err(AppError::io("Ошибка при открытии файла", &path, &e)),
         }
     }
 }
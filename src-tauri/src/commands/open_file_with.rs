@@ -3,14 +3,18 @@
 use std::process::Command;
 use tauri::command;
 
+use super::error::{AppError, CommandResult};
+
 #[command]
-pub async fn open_file_with(path: String) -> Result<(), String> {
+pub async fn open_file_with(path: String) -> CommandResult<()> {
     #[cfg(target_os = "windows")]
     {
         Command::new("rundll32")
             .args(["shell32.dll,OpenAs_RunDLL", &path])
             .spawn()
-            .map_err(|e| format!("Не удалось открыть диалог 'Открыть с помощью': {}", e))?;
+            .map_err(|e| {
+                AppError::io("Не удалось открыть диалог 'Открыть с помощью'", &path, &e)
+            })?;
     }
 
     #[cfg(target_os = "macos")]
@@ -18,7 +22,7 @@ pub async fn open_file_with(path: String) -> Result<(), String> {
         Command::new("open")
             .args(["-a", "Choose Application", &path])
             .spawn()
-            .map_err(|e| format!("Не удалось открыть диалог выбора приложения: {}", e))?;
+            .map_err(|e| AppError::io("Не удалось открыть диалог выбора приложения", &path, &e))?;
     }
 
     #[cfg(target_os = "linux")]
@@ -27,7 +31,7 @@ pub async fn open_file_with(path: String) -> Result<(), String> {
         Command::new("xdg-open")
             .arg(&path)
             .spawn()
-            .map_err(|e| format!("Не удалось открыть файл: {}", e))?;
+            .map_err(|e| AppError::io("Не удалось открыть файл", &path, &e))?;
     }
 
     Ok(())
@@ -0,0 +1,108 @@
+// Library organiser command: move or copy files into a template folder tree
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use tauri::{command, AppHandle, State};
+
+use super::batch_rename::plan_message;
+use super::error::{AppError, CommandResult};
+use super::filename_patterns::load_filename_patterns;
+use super::journal::{record_operation, JournalState};
+use super::types::OrganizeResult;
+use anomad_core::journal::Operation;
+use anomad_core::organize::{
+    copy_file, move_file, plan_organize, OrganizeFile, OrganizeItem, OrganizeStatus, TransferMode,
+};
+
+// Шаблон задает папки внутри root ("{project}/{author_last}"), имя файла не меняется.
+// Совпадающие имена получают суффикс " (2)"; с dry_run ничего не переносится
+#[command]
+pub async fn organize_files(
+    app: AppHandle,
+    files: Vec<OrganizeFile>,
+    root: String,
+    template: String,
+    mode: Option<TransferMode>,
+    dry_run: bool,
+    journal: State<'_, JournalState>,
+) -> CommandResult<OrganizeResult> {
+    let root = PathBuf::from(&root);
+    if !root.is_dir() {
+        return Err(AppError::NotADirectory {
+            path: root.to_string_lossy().into_owned(),
+        });
+    }
+
+    let mode = mode.unwrap_or_default();
+    let patterns = load_filename_patterns(&app);
+    let mut taken = HashSet::new();
+    let plan: Vec<OrganizeItem> = files
+        .iter()
+        .map(|file| {
+            let mut item = plan_organize(file, &root, &template, &patterns, &mut taken);
+            item.message = item.reason.as_ref().map(plan_message);
+            item
+        })
+        .collect();
+
+    let items: Vec<OrganizeItem> = if dry_run {
+        plan
+    } else {
+        plan.into_iter().map(|item| apply_item(item, mode)).collect()
+    };
+
+    let done = [
+        OrganizeStatus::Ready,
+        OrganizeStatus::Moved,
+        OrganizeStatus::Copied,
+    ];
+    let mapping: BTreeMap<String, String> = items
+        .iter()
+        .filter(|item| done.contains(&item.status))
+        .filter_map(|item| Some((item.source.clone(), item.target.clone()?)))
+        .collect();
+
+    if !dry_run {
+        let operations = items
+            .iter()
+            .filter_map(|item| {
+                let (from, to) = (item.source.clone(), item.target.clone()?);
+                match item.status {
+                    OrganizeStatus::Moved => Some(Operation::Rename { from, to }),
+                    OrganizeStatus::Copied => Some(Operation::Copy { from, to }),
+                    _ => None,
+                }
+            })
+            .collect();
+        record_operation(&journal, "Раскладка по папкам", operations);
+    }
+
+    Ok(OrganizeResult { items, mapping })
+}
+
+fn apply_item(mut item: OrganizeItem, mode: TransferMode) -> OrganizeItem {
+    let target = match (&item.status, &item.target) {
+        (OrganizeStatus::Ready, Some(target)) => PathBuf::from(target),
+        _ => return item,
+    };
+    let source = Path::new(&item.source);
+
+    let result = match mode {
+        TransferMode::Move => move_file(source, &target),
+        TransferMode::Copy => copy_file(source, &target),
+    };
+
+    match result {
+        Ok(_) => {
+            item.status = match mode {
+                TransferMode::Move => OrganizeStatus::Moved,
+                TransferMode::Copy => OrganizeStatus::Copied,
+            };
+        }
+        Err(e) => {
+            item.status = OrganizeStatus::Failed;
+            item.message = Some(format!("Не удалось перенести файл: {:#}", e));
+        }
+    }
+    item
+}
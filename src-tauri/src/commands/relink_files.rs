@@ -0,0 +1,85 @@
+// Relink moved or renamed files command
+
+use std::collections::HashSet;
+use std::path::Path;
+use tauri::command;
+
+use super::error::{AppError, CommandResult};
+use super::types::{MissingFile, RelinkCandidate, RelinkResult};
+use anomad_core::everything_search::everything_paths;
+use anomad_core::fingerprint::{compute_fingerprint, match_fingerprint, FingerprintMatch};
+
+// Сколько результатов берем из каждого запроса к Everything
+const MAX_CANDIDATES: u32 = 200;
+
+// Кандидаты - файлы того же размера (перемещенная копия) и с тем же именем (файл мог меняться).
+// Подходят только те, у кого совпал хеш содержимого или /ID трейлера
+#[command]
+pub async fn relink_files(files: Vec<MissingFile>) -> CommandResult<Vec<RelinkResult>> {
+    let mut results = Vec::with_capacity(files.len());
+
+    for file in files {
+        if file.content_hash.is_none() && file.trailer_id.is_none() {
+            results.push(RelinkResult {
+                missing_path: file.full_path,
+                candidates: Vec::new(),
+            });
+            continue;
+        }
+
+        let path = Path::new(&file.full_path);
+        let ext_filter = path
+            .extension()
+            .map(|ext| format!("ext:{} ", ext.to_string_lossy()))
+            .unwrap_or_default();
+
+        let mut queries = Vec::new();
+        if let Some(size) = file.size {
+            queries.push(format!("{}size:{}", ext_filter, size));
+        }
+        if let Some(name) = path.file_name() {
+            queries.push(format!("wfn:\"{}\"", name.to_string_lossy()));
+        }
+
+        let mut seen = HashSet::new();
+        let mut candidates = Vec::new();
+
+        for query in queries {
+            let paths = everything_paths(&query, MAX_CANDIDATES)
+                .await
+                .ok_or(AppError::SearchUnavailable)?;
+
+            for candidate in paths {
+                if candidate == file.full_path || !seen.insert(candidate.clone()) {
+                    continue;
+                }
+
+                let fingerprint = match compute_fingerprint(Path::new(&candidate)) {
+                    Ok(fingerprint) => fingerprint,
+                    Err(_) => continue,
+                };
+
+                if let Some(match_kind) = match_fingerprint(
+                    file.content_hash.as_deref(),
+                    file.trailer_id.as_deref(),
+                    &fingerprint,
+                ) {
+                    candidates.push(RelinkCandidate {
+                        path: candidate,
+                        match_kind,
+                    });
+                }
+            }
+        }
+
+        // Точные совпадения первыми
+        candidates.sort_by_key(|c| c.match_kind != FingerprintMatch::Exact);
+
+        results.push(RelinkResult {
+            missing_path: file.full_path,
+            candidates,
+        });
+    }
+
+    Ok(results)
+}
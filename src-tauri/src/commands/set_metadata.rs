@@ -1,10 +1,106 @@
 // Set metadata command
 
-use tauri::command;
+use std::path::Path;
+use tauri::{command, State};
 
-use super::utils::set_creator::set_creator;
+use super::error::{AppError, CommandResult};
+use super::journal::{record_operation, JournalState};
+use super::types::SetMetadataResult;
+use anomad_core::identity::{ensure_identity, read_identity, IdentityStrategy};
+use anomad_core::journal::{Operation, UndoBackup};
 
+// Как отменить запись идентификатора, если он записывается впервые
+enum IdentityUndo {
+    Nothing,
+    Restore(UndoBackup),
+    Remove,
+}
+
+fn prepare_undo(path: &Path, strategy: IdentityStrategy) -> IdentityUndo {
+    if matches!(read_identity(path, strategy), Ok(Some(_))) {
+        return IdentityUndo::Nothing;
+    }
+
+    match strategy {
+        IdentityStrategy::Xmp => match UndoBackup::create(path) {
+            Ok(backup) => IdentityUndo::Restore(backup),
+            Err(e) => {
+                log::warn!("Не удалось сохранить копию для отмены: {}", e);
+                IdentityUndo::Nothing
+            }
+        },
+        IdentityStrategy::Sidecar | IdentityStrategy::Xattr => IdentityUndo::Remove,
+        IdentityStrategy::ContentHash => IdentityUndo::Nothing,
+    }
+}
+
+// Копия для отмены, чтение PDF и запись XMP идут вне главного потока,
+// иначе большая книга замораживает интерфейс
 #[command]
-pub fn set_metadata(file_path: String) -> String {
-    set_creator(&file_path).unwrap_or_default()
+pub async fn set_metadata(
+    file_path: String,
+    strategy: Option<IdentityStrategy>,
+    journal: State<'_, JournalState>,
+) -> CommandResult<SetMetadataResult> {
+    let journal = journal.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || write_identity(file_path, strategy, &journal))
+        .await
+        .map_err(|e| AppError::failed("Не удалось записать идентификатор", e))?
+}
+
+// Если выбранный способ не сработал (файл открыт, ФС без xattr и т.п.),
+// идентифицируем файл по хешу содержимого: strategy и fallback_reason сообщают об этом,
+// чтобы фронтенд показал предупреждение, а не обычный успех
+fn write_identity(
+    file_path: String,
+    strategy: Option<IdentityStrategy>,
+    journal: &JournalState,
+) -> CommandResult<SetMetadataResult> {
+    let path = Path::new(&file_path);
+    if !path.is_file() {
+        return Err(AppError::not_found(path));
+    }
+
+    let strategy = strategy.unwrap_or_default();
+    let undo = prepare_undo(path, strategy);
+    let error = match ensure_identity(path, strategy) {
+        Ok(id) => {
+            let operation = match undo {
+                IdentityUndo::Restore(backup) => backup.into_operation().ok(),
+                IdentityUndo::Remove => Some(Operation::RemoveIdentity {
+                    path: file_path.clone(),
+                    strategy,
+                }),
+                IdentityUndo::Nothing => None,
+            };
+            record_operation(journal, "Запись идентификатора", operation.into_iter().collect());
+            return Ok(SetMetadataResult {
+                id,
+                strategy,
+                fallback_reason: None,
+            });
+        }
+        Err(e) => e,
+    };
+
+    if let IdentityUndo::Restore(backup) = undo {
+        backup.discard();
+    }
+
+    log::warn!("Не удалось записать идентификатор ({:?}): {}", strategy, error);
+    if strategy == IdentityStrategy::ContentHash {
+        return Err(AppError::from_core(
+            "Не удалось прочитать файл",
+            path,
+            &error,
+        ));
+    }
+
+    ensure_identity(path, IdentityStrategy::ContentHash)
+        .map(|id| SetMetadataResult {
+            id,
+            strategy: IdentityStrategy::ContentHash,
+            fallback_reason: Some(error.to_string()),
+        })
+        .map_err(|e| AppError::from_core("Не удалось прочитать файл", path, &e))
 }
@@ -0,0 +1,27 @@
+// Reading frontend settings (store/settings) on the Rust side
+
+use serde::de::DeserializeOwned;
+use std::path::PathBuf;
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_store::StoreExt;
+
+// Файл настроек, который пишет фронтенд через LazyStore
+const SETTINGS_FILE: &str = "settings.json";
+const LIBRARY_ROOTS_KEY: &str = "libraryRoots";
+
+// None, если настройки нет или она другого типа
+pub fn read_setting<T: DeserializeOwned, R: Runtime>(app: &AppHandle<R>, key: &str) -> Option<T> {
+    let value = app.store(SETTINGS_FILE).ok()?.get(key)?;
+    serde_json::from_value(value).ok()
+}
+
+// Папки библиотеки, внутри которых разрешено удалять и раскладывать файлы
+pub fn library_roots<R: Runtime>(app: &AppHandle<R>) -> Vec<PathBuf> {
+    read_setting::<Vec<String>, R>(app, LIBRARY_ROOTS_KEY)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|root| root.trim().to_string())
+        .filter(|root| !root.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
@@ -3,14 +3,16 @@
 use std::process::Command;
 use tauri::command;
 
+use super::error::{AppError, CommandResult};
+
 #[command]
-pub async fn show_in_explorer(path: String) -> Result<(), String> {
+pub async fn show_in_explorer(path: String) -> CommandResult<()> {
     #[cfg(target_os = "windows")]
     {
         Command::new("explorer")
             .args(["/select,", &path])
             .spawn()
-            .map_err(|e| format!("Не удалось открыть проводник: {}", e))?;
+            .map_err(|e| AppError::io("Не удалось открыть проводник", &path, &e))?;
     }
 
     #[cfg(target_os = "macos")]
@@ -18,20 +20,20 @@ pub async fn show_in_explorer(path: String) -> Result<(), String> {
         Command::new("open")
             .args(["-R", &path])
             .spawn()
-            .map_err(|e| format!("Не удалось открыть Finder: {}", e))?;
+            .map_err(|e| AppError::io("Не удалось открыть Finder", &path, &e))?;
     }
 
     #[cfg(target_os = "linux")]
     {
         // Для Linux используем xdg-open или nautilus
-        let parent = Path::new(&path)
-            .parent()
-            .ok_or("Не удалось получить родительскую директорию")?;
+        let parent = std::path::Path::new(&path).parent().ok_or_else(|| {
+            AppError::invalid("path", "Не удалось получить родительскую директорию")
+        })?;
 
         Command::new("xdg-open")
             .arg(parent)
             .spawn()
-            .map_err(|e| format!("Не удалось открыть файловый менеджер: {}", e))?;
+            .map_err(|e| AppError::io("Не удалось открыть файловый менеджер", parent, &e))?;
     }
 
     Ok(())
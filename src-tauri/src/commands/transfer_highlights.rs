@@ -0,0 +1,92 @@
+// Transfer highlights to a new edition of the same book command
+
+use std::path::Path;
+use tauri::{command, State};
+
+use super::error::{AppError, CommandResult};
+use super::journal::{record_operation, JournalState};
+use super::types::TransferHighlightsResult;
+use anomad_core::annotation_merge::plan_merge;
+use anomad_core::annotations::{add_annotations, read_annotations};
+use anomad_core::highlight_transfer::{plan_transfer, DEFAULT_MIN_CONFIDENCE};
+use anomad_core::journal::UndoBackup;
+use anomad_core::pdf_metadata::load_pdf;
+use anomad_core::pdfium::load_pdfium;
+use anomad_core::safe_write::save_pdf;
+
+// Пометки old_path ищутся по тексту в new_path и создаются там заново.
+// min_confidence - порог от 0 до 1 (по умолчанию 0.8); с dry_run только отчет
+#[command]
+pub async fn transfer_highlights(
+    old_path: String,
+    new_path: String,
+    min_confidence: Option<f32>,
+    dry_run: bool,
+    journal: State<'_, JournalState>,
+) -> CommandResult<TransferHighlightsResult> {
+    let (old, new) = (Path::new(&old_path), Path::new(&new_path));
+    for path in [old, new] {
+        if !path.is_file() {
+            return Err(AppError::not_found(path));
+        }
+    }
+    if old == new {
+        return Err(AppError::invalid(
+            "new_path",
+            "Старое и новое издание - один и тот же файл",
+        ));
+    }
+
+    let (old_doc, _) =
+        load_pdf(old).map_err(|e| AppError::from_core("Не удалось открыть PDF", old, &e))?;
+    let annotations = read_annotations(&old_doc)
+        .map_err(|e| AppError::from_core("Не удалось прочитать аннотации", old, &e))?;
+
+    let pdfium =
+        load_pdfium().map_err(|e| AppError::from_core("Не удалось загрузить pdfium", new, &e))?;
+    let min_confidence = min_confidence
+        .unwrap_or(DEFAULT_MIN_CONFIDENCE)
+        .clamp(0.0, 1.0);
+    let plan = plan_transfer(&pdfium, old, new, &annotations, min_confidence)
+        .map_err(|e| AppError::from_core("Не удалось сопоставить текст изданий", new, &e))?;
+
+    let (mut doc, is_encrypted) =
+        load_pdf(new).map_err(|e| AppError::from_core("Не удалось открыть PDF", new, &e))?;
+    let existing = read_annotations(&doc)
+        .map_err(|e| AppError::from_core("Не удалось прочитать аннотации", new, &e))?;
+
+    // Повторный перенос не должен дублировать уже перенесенные пометки
+    let merge = plan_merge(&existing, &[(old_path.clone(), plan.to_add)]);
+    let mut written = 0;
+
+    if !dry_run && !merge.to_add.is_empty() {
+        let backup = UndoBackup::create(new)
+            .map_err(|e| AppError::from_core("Не удалось создать резервную копию", new, &e))?;
+
+        let result = add_annotations(&mut doc, &merge.to_add)
+            .and_then(|count| save_pdf(&mut doc, new, is_encrypted).map(|_| count));
+        match result {
+            Ok(count) => {
+                written = count;
+                let operation = backup.into_operation().ok();
+                record_operation(
+                    &journal,
+                    "Перенос пометок в новое издание",
+                    operation.into_iter().collect(),
+                );
+            }
+            Err(e) => {
+                backup.discard();
+                return Err(AppError::from_core("Не удалось сохранить PDF", new, &e));
+            }
+        }
+    }
+
+    Ok(TransferHighlightsResult {
+        target: new_path,
+        placed: plan.placed,
+        unplaced: plan.unplaced,
+        duplicates: merge.duplicates,
+        written: if dry_run { merge.to_add.len() } else { written },
+    })
+}
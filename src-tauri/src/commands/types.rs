@@ -2,34 +2,21 @@
 
 use serde::Deserialize;
 use serde::Serialize;
+use std::collections::BTreeMap;
 use std::path::Path;
 
-// Параметры для функции поиска
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct SearchParams {
-    pub query: String,
-    pub path: Option<String>,
-    pub count: Option<u32>,
-    pub include_highlights: Option<bool>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct FileResult {
-    pub file_name: String,
-    pub full_path: String,
-    pub title: String,
+use super::error::AppError;
+use anomad_core::annotation_merge::AnnotationConflict;
+use anomad_core::filename_patterns::{parse_file_name, FilenamePattern, ParsedFileName};
+use anomad_core::fingerprint::FingerprintMatch;
+use anomad_core::highlight_transfer::{TransferredHighlight, UnplacedHighlight};
+use anomad_core::identifiers::DocumentIdentifiers;
+use anomad_core::identity::IdentityStrategy;
+use anomad_core::metadata_edit::FieldChange;
+use anomad_core::organize::OrganizeItem;
 
-    pub size: Option<u64>,
-    pub created_date: String,
-    pub modified_date: String,
-    pub extension: Option<String>,
-    pub is_locked: bool,
-    pub id: Option<String>,
-    pub pdf_title: Option<String>,
-    pub pdf_author: Option<String>,
-    pub pdf_creator: Option<String>,
-    pub highlights: Option<Vec<crate::commands::utils::get_file_highlights::Highlight>>,
-}
+// Строки таблицы и параметры поиска определены в ядре
+pub use anomad_core::{FileResult, SearchParams};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SearchResult {
@@ -56,29 +43,152 @@ pub struct FileMetadata {
     pub file_name: String,
     pub full_path: String,
     pub title: String,
+    pub author: Option<String>,
+    pub year: Option<String>,
+    pub fields: BTreeMap<String, String>,
 }
 
-// Функция для парсинга метаданных файла, используется в watcher_commands
-pub fn parse_file_metadata(path: &Path) -> Option<FileMetadata> {
+// Функция для парсинга метаданных файла, используется в watcher_commands.
+// Файлы, имя которых не подходит ни под один шаблон, пропускаются
+pub fn parse_file_metadata(path: &Path, patterns: &[FilenamePattern]) -> Option<FileMetadata> {
     if !path.is_file() {
         return None;
     }
 
-    if let Some(file_name) = path.file_stem().and_then(|s| s.to_str()) {
-        let parts: Vec<&str> = file_name.splitn(4, ' ').collect();
-        if parts.len() != 4 {
-            return None;
-        }
+    let stem = path.file_stem().and_then(|s| s.to_str())?;
+    let parsed = parse_file_name(stem, patterns)?;
+
+    Some(FileMetadata {
+        file_name: path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into(),
+        full_path: path.to_string_lossy().into(), // Сохраняем полный путь
+        title: parsed.title.unwrap_or_else(|| stem.to_string()),
+        author: parsed.author,
+        year: parsed.year,
+        fields: parsed.fields,
+    })
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PatternTestResult {
+    pub file_name: String,
+    pub matched: bool,
+    pub parsed: Option<ParsedFileName>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportXfdfResult {
+    pub path: String,
+    pub imported: usize,
+    pub skipped: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BibtexEntry {
+    pub path: String,
+    pub key: String,
+    pub bibtex: String,
+    pub identifiers: DocumentIdentifiers,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportBibtexResult {
+    pub path: String,
+    pub exported: usize,
+    pub failed: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetMetadataResult {
+    pub id: String,
+    pub strategy: IdentityStrategy,
+    // Почему выбранный способ не сработал и использован хеш содержимого
+    pub fallback_reason: Option<String>,
+}
+
+// Поля совпадают с колонками таблицы files на фронте
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MissingFile {
+    pub full_path: String,
+    pub size: Option<u64>,
+    pub content_hash: Option<String>,
+    pub trailer_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RelinkCandidate {
+    pub path: String,
+    pub match_kind: FingerprintMatch,
+}
 
-        return Some(FileMetadata {
-            file_name: path
-                .file_name()
-                .unwrap_or_default()
-                .to_string_lossy()
-                .into(),
-            full_path: path.to_string_lossy().into(), // Сохраняем полный путь
-            title: parts[3].to_string(),
-        });
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RelinkResult {
+    pub missing_path: String,
+    pub candidates: Vec<RelinkCandidate>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MetadataEditResult {
+    pub path: String,
+    pub changes: Vec<FieldChange>,
+    pub written: bool,
+    pub error: Option<String>,
+}
+
+impl MetadataEditResult {
+    pub fn failed(path: String, error: String) -> Self {
+        MetadataEditResult {
+            path,
+            changes: Vec::new(),
+            written: false,
+            error: Some(error),
+        }
     }
-    None
+}
+
+#[derive(Debug, Serialize)]
+pub struct UndoResult {
+    pub id: String,
+    pub undone: bool,
+    // Операции пакета, которые отменить не удалось; повторная отмена попробует снова
+    pub errors: Vec<AppError>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeleteResult {
+    pub path: String,
+    pub permanent: bool,
+    // Идентификатор элемента корзины; None, если на этой платформе восстановление недоступно
+    pub trash_location: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrganizeResult {
+    pub items: Vec<OrganizeItem>,
+    // Старый путь -> новый для всех перенесенных (в dry_run - готовых к переносу) файлов
+    pub mapping: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MergeAnnotationsResult {
+    pub target: String,
+    pub added: usize,
+    pub duplicates: usize,
+    pub conflicts: Vec<AnnotationConflict>,
+    // Копии, которые не удалось прочитать или которые не совпадают с целевым файлом
+    pub failed: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TransferHighlightsResult {
+    pub target: String,
+    pub placed: Vec<TransferredHighlight>,
+    pub unplaced: Vec<UnplacedHighlight>,
+    // Пометки, которые уже есть в новом файле на найденном месте
+    pub duplicates: usize,
+    // Сколько аннотаций записано в файл (в dry_run - будет записано)
+    pub written: usize,
 }
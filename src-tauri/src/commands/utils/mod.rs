@@ -1,7 +0,0 @@
-pub mod get_file_info;
-pub mod get_file_highlights;
-pub mod get_file_info_all_meta;
-pub mod filetime_to_datetime;
-pub mod set_creator;
-pub mod file_info;
-pub mod everything_search;
\ No newline at end of file
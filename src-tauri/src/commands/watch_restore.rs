@@ -0,0 +1,138 @@
+// Restore persisted watchers at startup and report changes made while the app was closed
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{command, AppHandle, Emitter, Manager, State};
+
+use super::error::CommandResult;
+use super::file_watcher::{set_watched_files, FileWatcherState};
+use super::filename_patterns::load_filename_patterns;
+use super::watcher_commands::{watch_directory, FileChangeEvent, WatcherMap};
+use anomad_core::watch_config::{
+    compare_snapshots, directory_snapshot, files_snapshot, WatchConfig,
+};
+
+pub type WatchConfigState = Mutex<WatchConfig>;
+
+#[derive(Debug, Default, Serialize, Clone)]
+pub struct WatchRestoreReport {
+    // Папки и файлы из настроек, которых больше нет на диске
+    pub missing: Vec<String>,
+    // Папки, которые есть, но отслеживание не запустилось
+    pub failed: Vec<String>,
+    // Изменения, сделанные, пока приложение было закрыто
+    pub changes: Vec<FileChangeEvent>,
+}
+
+// None - восстановление еще идет
+pub type WatchRestoreState = Mutex<Option<WatchRestoreReport>>;
+
+// Запускается из setup. Снимки папок и извлечение пометок на большой библиотеке идут долго,
+// поэтому восстановление не задерживает открытие окна. Событие до загрузки фронтенда
+// потерялось бы, поэтому итог еще и хранится для команды get_watch_restore_report
+pub fn spawn_restore_watchers(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let report = restore_watchers(&app);
+        *app.state::<WatchRestoreState>().lock().unwrap() = Some(report.clone());
+        let _ = app.emit("watch-restore-finished", &report);
+    });
+}
+
+// Настройки блокируются только для копирования и записи снимков: снимки папок
+// и извлечение пометок идут долго, а команды отслеживания ждали бы все это время
+fn restore_watchers(app: &AppHandle) -> WatchRestoreReport {
+    let (directories, files, files_before) = {
+        let config = app.state::<WatchConfigState>();
+        let config = config.lock().unwrap();
+        (
+            config.directories().to_vec(),
+            config.files(),
+            config.files_snapshot().clone(),
+        )
+    };
+    let patterns = load_filename_patterns(app);
+    let mut report = WatchRestoreReport::default();
+    let mut changes = Vec::new();
+    let mut snapshots = Vec::new();
+
+    for directory in directories {
+        if !Path::new(&directory.dir_path).is_dir() {
+            log::warn!("Отслеживаемая папка не найдена: {}", directory.dir_path);
+            report.missing.push(directory.dir_path);
+            continue;
+        }
+        if let Err(e) = watch_directory(app, &app.state::<WatcherMap>(), &directory) {
+            log::warn!(
+                "Не удалось восстановить отслеживание {}: {}",
+                directory.dir_path,
+                e
+            );
+            report.failed.push(format!("{}: {}", directory.dir_path, e));
+            continue;
+        }
+        let snapshot = directory_snapshot(&directory);
+        changes.extend(compare_snapshots(&directory.snapshot, &snapshot));
+        snapshots.push((directory.dir_path, snapshot));
+    }
+
+    if !files.is_empty() {
+        match set_watched_files(app, &app.state::<FileWatcherState>(), files) {
+            Ok(result) => {
+                let current = files_snapshot(result.watched.iter().map(PathBuf::from));
+                // Об отсутствующих файлах сообщаем в missing, а не удалением
+                changes.extend(
+                    compare_snapshots(&files_before, &current)
+                        .into_iter()
+                        .filter(|change| {
+                            !result
+                                .missing
+                                .contains(&change.path.to_string_lossy().into_owned())
+                        }),
+                );
+                report.missing.extend(result.missing);
+            }
+            Err(e) => {
+                log::warn!("Не удалось восстановить отслеживание файлов: {}", e);
+                report.failed.push(e.to_string());
+            }
+        }
+    }
+
+    report.changes = changes
+        .into_iter()
+        .map(|change| FileChangeEvent::from_change(change, &patterns))
+        .collect();
+
+    // Изменения уже в отчете - следующий запуск сравнивает с текущим состоянием
+    let config = app.state::<WatchConfigState>();
+    let mut config = config.lock().unwrap();
+    for (dir_path, snapshot) in snapshots {
+        config.set_directory_snapshot(&dir_path, snapshot);
+    }
+    let files = config.files();
+    config.set_files(files.iter().map(PathBuf::from));
+    if let Err(e) = config.save() {
+        log::error!("Не удалось сохранить настройки отслеживания: {}", e);
+    }
+
+    report
+}
+
+// Снимок перед закрытием приложения
+pub fn save_watch_snapshots(config: &WatchConfigState) {
+    if let Ok(mut config) = config.lock() {
+        config.refresh_snapshots();
+        if let Err(e) = config.save() {
+            log::error!("Не удалось сохранить настройки отслеживания: {}", e);
+        }
+    }
+}
+
+#[command]
+pub async fn get_watch_restore_report(
+    report: State<'_, WatchRestoreState>,
+) -> CommandResult<Option<WatchRestoreReport>> {
+    Ok(report.lock().unwrap().clone())
+}
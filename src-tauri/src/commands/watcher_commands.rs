@@ -1,36 +1,154 @@
 // src/commands/directory_watcher.rs
-use notify::{Event, EventKind, RecursiveMode, Result as NotifyResult, Watcher};
+use notify::{Event, RecursiveMode, Result as NotifyResult, Watcher};
 use serde::Serialize;
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use tauri::{command, AppHandle, Emitter, State};
 use tokio::sync::mpsc;
 
+// Расширения, о которых сообщаем по умолчанию
+const DEFAULT_EXTENSIONS: [&str; 1] = ["pdf"];
+
+pub struct DirectoryWatcher {
+    // Отслеживание прекращается, когда watcher удаляется из карты
+    _watcher: notify::RecommendedWatcher,
+    recursive: bool,
+    extensions: Vec<String>,
+    project: Option<String>,
+}
+
 // Глобальное состояние для хранения активных watcher'ов
-pub type WatcherMap = Arc<Mutex<HashMap<String, notify::RecommendedWatcher>>>;
+pub type WatcherMap = Arc<Mutex<HashMap<String, DirectoryWatcher>>>;
+
+#[derive(Debug, Serialize, Clone)]
+pub struct WatchedDirectory {
+    pub dir_path: String,
+    pub recursive: bool,
+    pub extensions: Vec<String>,
+    pub project: Option<String>,
+}
 
 #[derive(Debug, Serialize, Clone)]
 pub struct FileChangeEvent {
-    pub event_type: String, // "created", "modified", "deleted"
+    pub event_type: String, // "created", "modified", "deleted", "renamed"
     pub file_path: String,
+    pub old_path: Option<String>, // Прежний путь для "renamed"
     pub metadata: Option<super::types::FileMetadata>, // Используем FileMetadata из types модуля
 }
 
+impl FileChangeEvent {
+    // Метаданные читаются для всех изменений, кроме удаления
+    pub fn from_change(change: LogicalChange, patterns: &[FilenamePattern]) -> Self {
+        let metadata = match change.kind {
+            ChangeKind::Deleted => None,
+            _ => parse_file_metadata(&change.path, patterns),
+        };
+        FileChangeEvent {
+            event_type: change.kind.as_str().to_string(),
+            file_path: change.path.to_string_lossy().to_string(),
+            old_path: change.old_path.map(|p| p.to_string_lossy().to_string()),
+            metadata,
+        }
+    }
+}
+
 // Импортируем функцию parse_file_metadata из types модуля
+use super::error::{AppError, CommandResult};
+use super::filename_patterns::load_filename_patterns;
 use super::types::parse_file_metadata;
+use super::watch_restore::WatchConfigState;
+use anomad_core::filename_patterns::FilenamePattern;
+use anomad_core::watch_config::{directory_snapshot, DirectoryConfig, Snapshot};
+use anomad_core::watch_events::{
+    list_files, ChangeBatcher, ChangeKind, LogicalChange, FLUSH_INTERVAL,
+};
 
+// Приводим расширения к виду "pdf": без точки, в нижнем регистре
+fn normalize_extensions(extensions: Option<Vec<String>>) -> Vec<String> {
+    let extensions = match extensions {
+        Some(extensions) => extensions,
+        None => DEFAULT_EXTENSIONS.iter().map(|e| e.to_string()).collect(),
+    };
+    let mut extensions: Vec<String> = extensions
+        .iter()
+        .map(|e| e.trim().trim_start_matches('.').to_lowercase())
+        .filter(|e| !e.is_empty())
+        .collect();
+    extensions.sort();
+    extensions.dedup();
+    extensions
+}
+
+// Изменения одной пачки уходят во фронтенд одним событием "files-changed"
+fn emit_changes(app_handle: &AppHandle, batcher: &mut ChangeBatcher, patterns: &[FilenamePattern]) {
+    let changes = batcher.flush(Instant::now(), |p| p.is_file());
+    if changes.is_empty() {
+        return;
+    }
+
+    let events: Vec<FileChangeEvent> = changes
+        .into_iter()
+        .map(|change| FileChangeEvent::from_change(change, patterns))
+        .collect();
+    let _ = app_handle.emit("files-changed", &events);
+}
+
+// recursive - отслеживать и вложенные папки (по умолчанию нет);
+// extensions - о каких файлах сообщать (по умолчанию только PDF, пустой список - обо всех);
+// project - к какому проекту относится папка. Настройки сохраняются и восстанавливаются при запуске
 #[command]
 pub async fn start_watching_directory(
     app_handle: AppHandle,
     dir_path: String,
+    recursive: Option<bool>,
+    extensions: Option<Vec<String>>,
+    project: Option<String>,
     watchers: State<'_, WatcherMap>,
-) -> Result<(), String> {
+    config: State<'_, WatchConfigState>,
+) -> CommandResult<()> {
+    let mut directory = DirectoryConfig {
+        dir_path,
+        recursive: recursive.unwrap_or(false),
+        extensions: normalize_extensions(extensions),
+        project,
+        snapshot: Snapshot::new(),
+    };
+    watch_directory(&app_handle, &watchers, &directory)?;
+
+    directory.snapshot = directory_snapshot(&directory);
+    let mut config = config.lock().unwrap();
+    config.set_directory(directory);
+    config.save().map_err(|e| {
+        AppError::from_core(
+            "Не удалось сохранить настройки отслеживания",
+            config.file(),
+            &e,
+        )
+    })
+}
+
+// Запуск отслеживания папки - и из команды, и при восстановлении после запуска
+pub fn watch_directory(
+    app_handle: &AppHandle,
+    watchers: &WatcherMap,
+    directory: &DirectoryConfig,
+) -> CommandResult<()> {
+    let dir_path = directory.dir_path.clone();
     let path = PathBuf::from(&dir_path);
     if !path.exists() || !path.is_dir() {
-        return Err("Указанный путь не существует или не является директорией".into());
+        return Err(AppError::NotADirectory { path: dir_path });
     }
 
+    // Проверяем, не отслеживается ли уже эта директория
+    if watchers.lock().unwrap().contains_key(&dir_path) {
+        return Err(AppError::AlreadyWatching { path: dir_path });
+    }
+
+    let recursive = directory.recursive;
+    let extensions = directory.extensions.clone();
+
     // Создаем канал для получения событий
     let (tx, mut rx) = mpsc::channel(100);
 
@@ -40,65 +158,64 @@ pub async fn start_watching_directory(
             let _ = tx.blocking_send(event);
         }
     })
-    .map_err(|e| e.to_string())?;
+    .map_err(|e| AppError::from_notify("Не удалось запустить отслеживание", &path, &e))?;
 
     // Начинаем отслеживание директории
+    let mode = if recursive {
+        RecursiveMode::Recursive
+    } else {
+        RecursiveMode::NonRecursive
+    };
     watcher
-        .watch(&path, RecursiveMode::NonRecursive)
-        .map_err(|e| e.to_string())?;
+        .watch(&path, mode)
+        .map_err(|e| AppError::from_notify("Не удалось отслеживать папку", &path, &e))?;
 
     // Сохраняем watcher в глобальном состоянии
     {
         let mut watchers_guard = watchers.lock().unwrap();
-        // Проверяем, не отслеживается ли уже эта директория
+        // Пока создавался watcher, ту же директорию мог начать отслеживать другой вызов
         if watchers_guard.contains_key(&dir_path) {
-            return Err(format!("Директория '{}' уже отслеживается.", dir_path));
+            return Err(AppError::AlreadyWatching { path: dir_path });
         }
-        watchers_guard.insert(dir_path.clone(), watcher);
+        watchers_guard.insert(
+            dir_path.clone(),
+            DirectoryWatcher {
+                _watcher: watcher,
+                recursive,
+                extensions: extensions.clone(),
+                project: directory.project.clone(),
+            },
+        );
     }
 
     // Создаем задачу для обработки событий
     let app_handle_clone = app_handle.clone();
+    // Шаблоны имен читаем один раз на время отслеживания
+    let patterns = load_filename_patterns(app_handle);
 
-    tokio::spawn(async move {
-        while let Some(event) = rx.recv().await {
-            match event.kind {
-                EventKind::Create(_) => {
-                    for path in event.paths {
-                        if let Some(metadata) = parse_file_metadata(&path) {
-                            let change_event = FileChangeEvent {
-                                event_type: "created".to_string(),
-                                file_path: path.to_string_lossy().to_string(),
-                                metadata: Some(metadata),
-                            };
-                            let _ = app_handle_clone.emit("file-changed", &change_event);
-                        }
-                    }
-                }
-                EventKind::Modify(_) => {
-                    for path in event.paths {
-                        if let Some(metadata) = parse_file_metadata(&path) {
-                            let change_event = FileChangeEvent {
-                                event_type: "modified".to_string(),
-                                file_path: path.to_string_lossy().to_string(),
-                                metadata: Some(metadata),
-                            };
-                            let _ = app_handle_clone.emit("file-changed", &change_event);
-                        }
-                    }
-                }
-                EventKind::Remove(_) => {
-                    for path in event.paths {
-                        let change_event = FileChangeEvent {
-                            event_type: "deleted".to_string(),
-                            file_path: path.to_string_lossy().to_string(),
-                            metadata: None,
-                        };
-                        let _ = app_handle_clone.emit("file-changed", &change_event);
+    // Не tokio::spawn: при восстановлении в setup мы еще не внутри рантайма
+    tauri::async_runtime::spawn(async move {
+        let mut batcher = ChangeBatcher::new(list_files(&path, recursive), extensions);
+        loop {
+            // Пока есть незавершенные пачки, просыпаемся по таймеру, чтобы выдать их
+            let event = if batcher.is_idle() {
+                rx.recv().await
+            } else {
+                match tokio::time::timeout(FLUSH_INTERVAL, rx.recv()).await {
+                    Ok(event) => event,
+                    Err(_) => {
+                        emit_changes(&app_handle_clone, &mut batcher, &patterns);
+                        continue;
                     }
                 }
-                _ => {}
+            };
+
+            match event {
+                Some(event) => batcher.push(&event, Instant::now()),
+                // Watcher удален - канал закрыт
+                None => break,
             }
+            emit_changes(&app_handle_clone, &mut batcher, &patterns);
         }
     });
 
@@ -109,11 +226,52 @@ pub async fn start_watching_directory(
 pub async fn stop_watching_directory(
     dir_path: String,
     watchers: State<'_, WatcherMap>,
-) -> Result<(), String> {
-    let mut watchers_guard = watchers.lock().unwrap();
-    if watchers_guard.remove(&dir_path).is_some() {
+    config: State<'_, WatchConfigState>,
+) -> CommandResult<()> {
+    let removed = watchers.lock().unwrap().remove(&dir_path).is_some();
+
+    // Папку, которую не удалось восстановить после запуска, тоже можно убрать из настроек
+    let mut config = config.lock().unwrap();
+    let configured = config.directories().iter().any(|d| d.dir_path == dir_path);
+    if configured {
+        config.remove_directory(&dir_path);
+        config.save().map_err(|e| {
+            AppError::from_core(
+                "Не удалось сохранить настройки отслеживания",
+                config.file(),
+                &e,
+            )
+        })?;
+    }
+
+    if removed || configured {
         Ok(())
     } else {
-        Err("Watcher для данной директории не найден".into())
+        Err(AppError::NotWatching { path: dir_path })
+    }
+}
+
+#[command]
+pub async fn list_watched_directories(
+    watchers: State<'_, WatcherMap>,
+) -> CommandResult<Vec<WatchedDirectory>> {
+    let watchers_guard = watchers.lock().unwrap();
+    let mut directories: Vec<WatchedDirectory> = watchers_guard
+        .iter()
+        .map(|(dir_path, watcher)| WatchedDirectory {
+            dir_path: dir_path.clone(),
+            recursive: watcher.recursive,
+            extensions: watcher.extensions.clone(),
+            project: watcher.project.clone(),
+        })
+        .collect();
+    directories.sort_by(|a, b| a.dir_path.cmp(&b.dir_path));
+    Ok(directories)
+}
+
+// Останавливает все watcher'ы; задачи обработки событий завершаются, когда закрывается канал
+pub fn stop_all_watchers(watchers: &WatcherMap) {
+    if let Ok(mut watchers_guard) = watchers.lock() {
+        watchers_guard.clear();
     }
 }
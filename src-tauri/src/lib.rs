@@ -6,12 +6,22 @@ use std::{
     collections::HashMap,
     sync::{Arc, Mutex},
 };
+use tauri::{Manager, WindowEvent};
 
 // Объявляем модуль commands, который будет искать src/commands/mod.rs
 mod commands;
 
+// Консольная версия (бинарник anomad-cli) использует те же команды без Tauri
+pub mod cli;
+
+// Лог в файл для окна (в релизе stdout и stderr не видны) и в stderr для консоли
+mod logging;
+
 // Импортируем команды и WatcherMap из нашего нового модуля
 use commands::*;
+use anomad_core::annotation_history::{AnnotationHistory, HISTORY_FILE};
+use anomad_core::journal::{Journal, JOURNAL_FILE};
+use anomad_core::watch_config::{WatchConfig, WATCH_CONFIG_FILE};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -23,6 +33,40 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_sql::Builder::new().build())
         .manage(watchers)
+        .manage(FileWatcherState::default())
+        .setup(|app| {
+            // Лог, журнал операций и история пометок хранятся в папке данных приложения
+            let data_dir = app.path().app_data_dir()?;
+            // Лог подключается первым, чтобы в него попали ошибки загрузки ниже
+            app.handle()
+                .plugin(logging::log_plugin(data_dir.join(logging::LOG_DIR)))?;
+            log::info!(
+                "AcademyNomad {} ({} {})",
+                app.package_info().version,
+                std::env::consts::OS,
+                std::env::consts::ARCH
+            );
+
+            let journal = Journal::load(data_dir.join(JOURNAL_FILE));
+            app.manage(JournalState::new(Mutex::new(journal)));
+            let history = AnnotationHistory::load(data_dir.join(HISTORY_FILE));
+            app.manage(HistoryState::new(Mutex::new(history)));
+
+            // Отслеживаемые папки и файлы восстанавливаются из прошлого запуска
+            let watch_config = WatchConfig::load(data_dir.join(WATCH_CONFIG_FILE));
+            app.manage(WatchConfigState::new(watch_config));
+            app.manage(WatchRestoreState::default());
+            spawn_restore_watchers(app.handle());
+            Ok(())
+        })
+        .on_window_event(|window, event| {
+            // При закрытии окна освобождаем watcher'ы, не дожидаясь завершения процесса
+            if let WindowEvent::Destroyed = event {
+                save_watch_snapshots(&window.state::<WatchConfigState>());
+                stop_all_watchers(&window.state::<WatcherMap>());
+                stop_file_watcher(&window.state::<FileWatcherState>());
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             // Здесь просто указываем имена функций
             get_everything,
@@ -34,6 +78,29 @@ pub fn run() {
             open_file_with,
             show_in_explorer,
             set_metadata,
+            export_xfdf,
+            import_xfdf,
+            get_bibtex,
+            export_bibtex,
+            relink_files,
+            preview_metadata_edit,
+            apply_metadata_edit,
+            test_filename_pattern,
+            batch_rename,
+            list_operations,
+            undo_operation,
+            organize_files,
+            find_duplicates,
+            merge_annotations,
+            transfer_highlights,
+            start_watching_directory,
+            stop_watching_directory,
+            list_watched_directories,
+            watch_files,
+            list_watched_files,
+            get_watch_restore_report,
+            get_annotation_timeline,
+            create_diagnostics_bundle,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
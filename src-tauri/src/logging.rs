@@ -0,0 +1,163 @@
+// Logging setup: rotating log file in the app data dir for the window app, stderr for the CLI
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use tauri::plugin::TauriPlugin;
+use tauri::Runtime;
+use tauri_plugin_log::{RotationStrategy, Target, TargetKind, TimezoneStrategy};
+
+// Папка логов внутри папки данных приложения
+pub const LOG_DIR: &str = "logs";
+const LOG_FILE_NAME: &str = "anomad";
+// Файл больше 1 МБ переименовывается с датой в имени, хранятся 5 старых
+const MAX_LOG_FILE_SIZE: u128 = 1024 * 1024;
+const KEPT_LOG_FILES: usize = 5;
+
+// Переопределение уровней без пересборки: ANOMAD_LOG="info,anomad_core::everything_search=debug"
+const LOG_ENV: &str = "ANOMAD_LOG";
+
+// Общий уровень и уровни отдельных модулей (префикс пути модуля, как в target записи)
+struct LogLevels {
+    default: LevelFilter,
+    modules: Vec<(String, LevelFilter)>,
+}
+
+impl LogLevels {
+    // Приложение: свой код подробно, зависимости (tauri, wry, sqlx, lopdf) - только предупреждения
+    fn app() -> Self {
+        let own = if cfg!(debug_assertions) {
+            LevelFilter::Debug
+        } else {
+            LevelFilter::Info
+        };
+        let mut levels = LogLevels {
+            default: LevelFilter::Warn,
+            modules: vec![
+                ("app_lib".to_string(), own),
+                ("anomad_core".to_string(), own),
+            ],
+        };
+        levels.apply_env();
+        levels
+    }
+
+    // Консоль: stdout занят результатом, в stderr - только предупреждения и ошибки
+    fn cli() -> Self {
+        let mut levels = LogLevels {
+            default: LevelFilter::Warn,
+            modules: Vec::new(),
+        };
+        levels.apply_env();
+        levels
+    }
+
+    fn apply_env(&mut self) {
+        if let Ok(spec) = std::env::var(LOG_ENV) {
+            self.apply(&spec);
+        }
+    }
+
+    // Части через запятую: "модуль=уровень" или просто "уровень" для всех остальных.
+    // Нераспознанные части пропускаются
+    fn apply(&mut self, spec: &str) {
+        for part in spec
+            .split(',')
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+        {
+            match part.split_once('=') {
+                Some((module, level)) => {
+                    if let Ok(level) = level.trim().parse() {
+                        let module = module.trim();
+                        self.modules.retain(|(name, _)| name != module);
+                        self.modules.push((module.to_string(), level));
+                    }
+                }
+                None => {
+                    if let Ok(level) = part.parse() {
+                        self.default = level;
+                    }
+                }
+            }
+        }
+    }
+
+    // Уровень для target записи: правило с самым длинным совпавшим префиксом модуля
+    fn level_for(&self, target: &str) -> LevelFilter {
+        self.modules
+            .iter()
+            .filter(|(module, _)| {
+                target == module
+                    || (target.starts_with(module.as_str())
+                        && target[module.len()..].starts_with("::"))
+            })
+            .max_by_key(|(module, _)| module.len())
+            .map(|(_, level)| *level)
+            .unwrap_or(self.default)
+    }
+
+    fn max_level(&self) -> LevelFilter {
+        self.modules
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.default, Ord::max)
+    }
+}
+
+// Плагин регистрируется в setup: папка данных известна только после запуска приложения
+pub fn log_plugin<R: Runtime>(log_dir: PathBuf) -> TauriPlugin<R> {
+    let levels = LogLevels::app();
+    let mut builder = tauri_plugin_log::Builder::new()
+        .clear_targets()
+        .target(Target::new(TargetKind::Folder {
+            path: log_dir,
+            file_name: Some(LOG_FILE_NAME.to_string()),
+        }))
+        .rotation_strategy(RotationStrategy::KeepSome(KEPT_LOG_FILES))
+        .max_file_size(MAX_LOG_FILE_SIZE)
+        .timezone_strategy(TimezoneStrategy::UseLocal)
+        .level(levels.default);
+    for (module, level) in levels.modules {
+        builder = builder.level_for(module, level);
+    }
+    // В отладочной сборке есть консоль - дублируем записи туда
+    if cfg!(debug_assertions) {
+        builder = builder.target(Target::new(TargetKind::Stderr));
+    }
+    builder.build()
+}
+
+struct StderrLogger(LogLevels);
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.0.level_for(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let level = match record.level() {
+            Level::Error => "ошибка",
+            Level::Warn => "предупреждение",
+            Level::Info => "info",
+            Level::Debug => "debug",
+            Level::Trace => "trace",
+        };
+        eprintln!("{}: {}", level, record.args());
+    }
+
+    fn flush(&self) {}
+}
+
+static CLI_LOGGER: OnceLock<StderrLogger> = OnceLock::new();
+
+// Логгер anomad-cli; повторный вызов (например, из тестов) ничего не меняет
+pub fn init_cli_logger() {
+    let logger = CLI_LOGGER.get_or_init(|| StderrLogger(LogLevels::cli()));
+    if log::set_logger(logger).is_ok() {
+        log::set_max_level(logger.0.max_level());
+    }
+}
@@ -43,7 +43,9 @@ async function createTables(db: Database) {
       pdf_creator TEXT,
       pdf_title TEXT,
       size INTEGER,
-      title TEXT
+      title TEXT,
+      content_hash TEXT,
+      trailer_id TEXT
     )
   `);
 
@@ -0,0 +1,23 @@
+import type { Migration } from './types';
+
+export const migration: Migration = {
+  name: 'add-fingerprint-to-files',
+  description: 'Add content_hash and trailer_id columns to files table for relinking moved files',
+
+  async up(db) {
+    console.log('Starting migration: add content_hash and trailer_id to files...');
+
+    const filesTableInfo = await db.select("PRAGMA table_info(files)") as Array<{name: string}>;
+
+    for (const column of ['content_hash', 'trailer_id']) {
+      if (!filesTableInfo.some((col) => col.name === column)) {
+        await db.execute(`ALTER TABLE files ADD COLUMN ${column} TEXT`);
+        console.log(`Added ${column} column to files table`);
+      } else {
+        console.log(`${column} column already exists`);
+      }
+    }
+
+    console.log('Migration add-fingerprint-to-files completed successfully');
+  }
+};
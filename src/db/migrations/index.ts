@@ -5,6 +5,7 @@ import { migration as removeListIdFromHighlights } from './003-remove-list-id-fr
 import { migration as createFileListsJunctionTable } from './004-create-file-lists-junction-table';
 import { migration as linkHighlightsToFilesAndRemoveRedundantFields } from './005-link-highlights-to-files-and-remove-redundant-fields';
 import { migration as movePinnedFieldsToFileLists } from './006-move-pinned-fields-to-file-lists';
+import { migration as addFingerprintToFiles } from './007-add-fingerprint-to-files';
 
 const migrations = [
   addFileNameToHighlights,
@@ -13,6 +14,7 @@ const migrations = [
   createFileListsJunctionTable,
   linkHighlightsToFilesAndRemoveRedundantFields,
   movePinnedFieldsToFileLists,
+  addFingerprintToFiles,
 ];
 
 export async function runMigrations() {
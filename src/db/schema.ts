@@ -22,6 +22,8 @@ export const files = sqliteTable('files', {
   pdf_title: text('pdf_title'),
   size: integer('size'),
   title: text('title'),
+  content_hash: text('content_hash'),
+  trailer_id: text('trailer_id'),
 });
 
 // Связующая таблица для отношений многие-ко-многим
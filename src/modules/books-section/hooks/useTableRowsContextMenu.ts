@@ -6,11 +6,18 @@ import toast from 'react-hot-toast';
 //   selectTypesQuery,
 //   selectExcludedQuery,
 // } from 'src/store/settings/settingsSelectors';
-import type { File, List, ListItem } from 'src/shared/types';
+import type {
+  File,
+  List,
+  ListItem,
+  SetMetadataResult,
+} from 'src/shared/types';
 import { useStore } from 'src/store';
+import { selectIdentityStrategy } from 'src/store/settings/settingsSelectors';
 import { useModal } from 'src/modules/modals/useModal';
 import { tryToFindFile } from 'src/shared/lib/everything';
 import { FilesDB } from 'src/db/FilesDB';
+import { errorMessage } from 'src/shared/lib/utils';
 
 interface ContextMenuItem {
   id: string;
@@ -21,6 +28,14 @@ interface ContextMenuItem {
 
 type MenuItem = ContextMenuItem | Submenu;
 
+// Хеш содержимого меняется при сохранении пометок, поэтому это не успех, а предупреждение
+function warnContentHashFallback(reason: string) {
+  toast(
+    `Не удалось записать идентификатор выбранным способом, использован хеш содержимого: ${reason}`,
+    { icon: '⚠️', duration: 6000 }
+  );
+}
+
 // async function searchFileByField(file: File, field: string): Promise<any> {
 //   const query = `${typesQuery} ${excludedQuery}`;
 //   const resultArr = (await invoke('get_everything', {
@@ -49,6 +64,7 @@ export const useTableRowsContextMenu = () => {
   const unpinItem = useStore((state) => state.unpinItem);
   const removeFromList = useStore((state) => state.removeFromList);
   const addToList = useStore((state) => state.addToList);
+  const identityStrategy = useStore(selectIdentityStrategy);
 
   const showFileContextMenu = async ({
     file,
@@ -67,21 +83,21 @@ export const useTableRowsContextMenu = () => {
         text: list,
         action: async () => {
           try {
-            const result = await invoke('set_metadata', {
+            const result = await invoke<SetMetadataResult>('set_metadata', {
               filePath: file.full_path,
+              strategy: identityStrategy,
             });
-            if (result) {
-              addToList({
-                listName: list as string,
-                itemData: { ...file, pdf_creator: result as string },
-              });
-              toast.success(`Файл добавлен в список ${list}`);
-            } else {
-              toast.error('Ошибка добавления файла в список');
+            addToList({
+              listName: list as string,
+              itemData: { ...file, pdf_creator: result.id },
+            });
+            toast.success(`Файл добавлен в список ${list}`);
+            if (result.fallback_reason) {
+              warnContentHashFallback(result.fallback_reason);
             }
           } catch (error) {
             console.error('Ошибка добавления файла в список:', error);
-            toast.error(`Ошибка добавления файла в список: ${error}`);
+            toast.error(`Ошибка добавления файла в список: ${errorMessage(error)}`);
           }
         },
       })),
@@ -107,7 +123,7 @@ export const useTableRowsContextMenu = () => {
         action: async () => {
           try {
             const confirmed = await confirm(
-              'Вы уверены, что хотите удалить этот файл?'
+              'Переместить этот файл в корзину?'
             );
             if (confirmed) {
               await invoke('delete_file', { path: file.full_path });
@@ -154,17 +170,18 @@ export const useTableRowsContextMenu = () => {
         showCondition: true,
         action: async () => {
           try {
-            const result = await invoke('set_metadata', {
+            const result = await invoke<SetMetadataResult>('set_metadata', {
               filePath: file.full_path,
+              strategy: identityStrategy,
             });
-            if (result) {
-              toast.success(`Метаданные обновлены`);
+            if (result.fallback_reason) {
+              warnContentHashFallback(result.fallback_reason);
             } else {
-              toast.error('Ошибка обновления метаданных');
+              toast.success(`Метаданные обновлены`);
             }
           } catch (error) {
             console.error('Ошибка открытия файла:', error);
-            toast.error(`Ошибка открытия файла: ${error}`);
+            toast.error(errorMessage(error));
           }
         },
       },
@@ -23,6 +23,7 @@ import {
   selectExcludedQuery,
 } from 'src/store/settings/settingsSelectors';
 import toast from 'react-hot-toast';
+import { errorMessage } from 'src/shared/lib/utils';
 import type { List } from 'src/shared/types';
 
 const COLUMNS: TableColumn[] = [
@@ -94,7 +95,7 @@ function Table() {
       }
     } catch (err) {
       console.error('Error searching file:', err);
-      toast.error(`Ошибка поиска файла: ${err}`);
+      toast.error(`Ошибка поиска файла: ${errorMessage(err)}`);
     } finally {
       setLoading(false);
     }
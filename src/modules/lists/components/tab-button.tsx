@@ -1,7 +1,8 @@
 import { invoke } from '@tauri-apps/api/core';
 import toast from 'react-hot-toast';
-import type { File } from 'src/shared/types';
+import type { File, SetMetadataResult } from 'src/shared/types';
 import { useStore } from 'src/store';
+import { errorMessage } from 'src/shared/lib/utils';
 import {
   searchOneFileByQuery,
   buildIncludeQuery,
@@ -9,6 +10,7 @@ import {
 import {
   selectTypesQuery,
   selectExcludedQuery,
+  selectIdentityStrategy,
 } from 'src/store/settings/settingsSelectors';
 
 interface TabButtonProps {
@@ -27,27 +29,24 @@ export function TabButton({
   const addToList = useStore((state) => state.addToList);
   const typesQuery = useStore(selectTypesQuery);
   const excludedQuery = useStore(selectExcludedQuery);
+  const identityStrategy = useStore(selectIdentityStrategy);
 
   const onDropToTab = async (fileData: File, tab: string) => {
     try {
       console.log('fileData', fileData);
-      const result = await invoke('set_metadata', {
+      const result = await invoke<SetMetadataResult>('set_metadata', {
         filePath: fileData.full_path,
+        strategy: identityStrategy,
       });
-      if (result) {
-        addToList({
-          listName: tab,
-          itemData: { ...fileData, pdf_creator: result as string },
-        });
+      addToList({
+        listName: tab,
+        itemData: { ...fileData, pdf_creator: result.id },
+      });
+      if (result.strategy === identityStrategy) {
         toast.success(`Файл добавлен в список ${tab}`);
       } else {
-        addToList({
-          listName: tab,
-          itemData: { ...fileData, pdf_creator: `error-${Date.now()}` },
-        });
-
         toast.error(
-          'Файл добавлен в проект только по имени из-за невозможности редактирования (скорее всего, файл открыт)',
+          'Файл добавлен в проект по хешу содержимого из-за невозможности записать идентификатор (скорее всего, файл открыт)',
           {
             duration: 5000,
           }
@@ -55,7 +54,7 @@ export function TabButton({
       }
     } catch (error) {
       console.error('Ошибка добавления файла в список:', error);
-      toast.error(`Ошибка добавления файла в список: ${error}`);
+      toast.error(`Ошибка добавления файла в список: ${errorMessage(error)}`);
     }
   };
 
@@ -1,6 +1,11 @@
 import { useState, useEffect } from 'react';
-import type { Settings as SettingsType } from 'src/shared/types';
+import { invoke } from '@tauri-apps/api/core';
+import type {
+  PatternTestResult,
+  Settings as SettingsType,
+} from 'src/shared/types';
 import { useStore } from 'src/store';
+import { errorMessage } from 'src/shared/lib/utils';
 
 interface SettingsPageProps {
   closeModal: () => void;
@@ -10,6 +15,8 @@ function SettingsPage({ closeModal }: SettingsPageProps) {
   const saveSettings = useStore((state) => state.saveSettings);
   const settings = useStore((state) => state.settings);
   const [tempSettings, setTempSettings] = useState<SettingsType>(settings);
+  const [sampleFileName, setSampleFileName] = useState('');
+  const [patternTestResult, setPatternTestResult] = useState('');
 
   useEffect(() => {
     setTempSettings(settings);
@@ -19,6 +26,29 @@ function SettingsPage({ closeModal }: SettingsPageProps) {
     setTempSettings((prev) => ({ ...prev, [key]: value }));
   };
 
+  // Проверяем шаблоны по порядку, как при разборе настоящих файлов
+  const handleTestPatterns = async () => {
+    const patterns = (tempSettings.filenamePatterns || []).filter(Boolean);
+    try {
+      for (const pattern of patterns) {
+        const [result] = (await invoke('test_filename_pattern', {
+          pattern,
+          fileNames: [sampleFileName],
+        })) as PatternTestResult[];
+        if (result?.parsed) {
+          const { title, author, year, fields } = result.parsed;
+          setPatternTestResult(
+            JSON.stringify({ pattern, title, author, year, ...fields })
+          );
+          return;
+        }
+      }
+      setPatternTestResult('Ни один шаблон не подошел');
+    } catch (error) {
+      setPatternTestResult(errorMessage(error));
+    }
+  };
+
   const handleSave = () => {
     saveSettings(tempSettings);
     closeModal();
@@ -54,6 +84,84 @@ function SettingsPage({ closeModal }: SettingsPageProps) {
         <p className="text-sm text-gray-500">
           Пример: C:\Program Files\Tracker Software\PDF Editor\PDFXEdit.exe
         </p>
+        <div className="flex items-center justify-between gap-2">
+          <div>
+            <h4 className="text-sm font-medium text-gray-700">
+              Идентификатор файла
+            </h4>
+          </div>
+          <select
+            className="border px-2 py-1 min-w-[300px] flex-1 max-w-xs"
+            value={tempSettings.identityStrategy || 'sidecar'}
+            onChange={(e) =>
+              handleInputChange('identityStrategy', e.target.value)
+            }
+          >
+            <option value="xmp">XMP-метаданные PDF</option>
+            <option value="sidecar">Файл рядом (.anomad)</option>
+            <option value="xattr">Атрибут файловой системы</option>
+            <option value="content_hash">Хеш содержимого</option>
+          </select>
+        </div>
+        <p className="text-sm text-gray-500">
+          Только XMP изменяет сам PDF, и только если в метаданных есть
+          запас места. Хеш содержимого меняется при редактировании файла.
+        </p>
+        <div className="flex items-start justify-between gap-2">
+          <div>
+            <h4 className="text-sm font-medium text-gray-700">
+              Шаблоны имен файлов
+            </h4>
+          </div>
+          <textarea
+            className="border px-2 py-1 min-w-[300px] flex-1 max-w-xs font-mono text-sm"
+            rows={3}
+            value={(tempSettings.filenamePatterns || []).join('\n')}
+            onChange={(e) =>
+              handleInputChange('filenamePatterns', e.target.value.split('\n'))
+            }
+          />
+        </div>
+        <p className="text-sm text-gray-500">
+          По одному на строку: {'{author} - {year} - {title}'} или регулярное
+          выражение с именованными группами после префикса re:
+        </p>
+        <div className="flex items-center justify-between gap-2">
+          <input
+            type="text"
+            className="border px-2 py-1 flex-1"
+            placeholder="Пример имени файла"
+            value={sampleFileName}
+            onChange={(e) => setSampleFileName(e.target.value)}
+          />
+          <button
+            onClick={handleTestPatterns}
+            className="px-3 py-1 text-gray-600 hover:text-gray-800 border rounded-md"
+          >
+            Проверить
+          </button>
+        </div>
+        {patternTestResult && (
+          <p className="text-sm text-gray-500 break-all">{patternTestResult}</p>
+        )}
+        <div className="flex items-start justify-between gap-2">
+          <div>
+            <h4 className="text-sm font-medium text-gray-700">
+              Папки библиотеки
+            </h4>
+          </div>
+          <textarea
+            className="border px-2 py-1 min-w-[300px] flex-1 max-w-xs font-mono text-sm"
+            rows={3}
+            value={(tempSettings.libraryRoots || []).join('\n')}
+            onChange={(e) =>
+              handleInputChange('libraryRoots', e.target.value.split('\n'))
+            }
+          />
+        </div>
+        <p className="text-sm text-gray-500">
+          По одной на строку. Удалять папки можно только внутри них.
+        </p>
       </div>
 
       <div className="flex justify-end gap-3 pt-4">
@@ -1,6 +1,6 @@
 import { invoke } from '@tauri-apps/api/core';
 import toast from 'react-hot-toast';
-import type { File } from '../types';
+import type { File, RelinkResult } from '../types';
 import { processDateToEverythingQuery } from './utils';
 import { buildIncludeQuery, searchOneFileByQuery } from './searchUtils';
 import { FilesDB } from 'src/db/FilesDB';
@@ -29,12 +29,30 @@ export async function tryToFindFile(file: File): Promise<any> {
     return;
   }
 
-  // Третья попытка: поиск на фронте
-  // result = await searchFileByField(file, 'pdf_creator');
-  // if (result) return result;
-  if (Array.isArray(result) && result.length > 1) {
-    toast.error('Найдено более одного файла при альтернативном поиске');
-    return;
+  // Третья попытка: по отпечатку содержимого (хеш и /ID трейлера PDF)
+  if (file.content_hash || file.trailer_id) {
+    const [relink] = await invoke<RelinkResult[]>('relink_files', {
+      files: [
+        {
+          full_path: file.full_path,
+          size: file.size ?? null,
+          content_hash: file.content_hash ?? null,
+          trailer_id: file.trailer_id ?? null,
+        },
+      ],
+    });
+    const exact = relink?.candidates.filter((c) => c.match_kind === 'exact');
+    const candidates = exact?.length ? exact : relink?.candidates;
+    if (candidates?.length > 1) {
+      toast.error('Найдено более одного файла по отпечатку');
+      return;
+    }
+    if (candidates?.length === 1) {
+      result = (await invoke('get_highlights_for_files', {
+        paths: [candidates[0].path],
+      })) as any[];
+      if (result?.length === 1) return result[0];
+    }
   }
 
   // Четвертая попытка: поиск по названию
@@ -1,6 +1,6 @@
 import { clsx, type ClassValue } from "clsx"
 import { twMerge } from "tailwind-merge"
-import type { Highlight } from 'src/shared/types'
+import type { AppError, Highlight } from 'src/shared/types'
 
 export function cn(...inputs: ClassValue[]) {
   return twMerge(clsx(inputs))
@@ -116,4 +116,19 @@ export function countHighlightsAndAnnotations(highlights: Highlight[]): [number,
   ).length;
   
   return [highlightsCount, annotationsCount];
-}
\ No newline at end of file
+}
+export function isAppError(error: unknown): error is AppError {
+  return (
+    typeof error === 'object' &&
+    error !== null &&
+    'code' in error &&
+    'message' in error
+  );
+}
+
+// Текст ошибки invoke для уведомлений
+export function errorMessage(error: unknown): string {
+  if (isAppError(error)) return error.message;
+  if (error instanceof Error) return error.message;
+  return String(error);
+}
@@ -29,6 +29,8 @@ export interface File {
   created_date?: string;
   pdf_creator?: string;
   size?: number;
+  content_hash?: string;
+  trailer_id?: string;
   is_locked?: boolean;
   highlights?: Highlight[];
   new_numbers?: boolean;
@@ -55,9 +57,33 @@ export interface FileResult {
   pdf_title?: string;
   pdf_author?: string;
   pdf_creator?: string;
+  pdf_details?: PdfDetails;
+  content_hash?: string;
+  trailer_id?: string;
   highlights?: Highlight[];
 }
 
+export interface RelinkCandidate {
+  path: string;
+  match_kind: 'exact' | 'same_document';
+}
+
+export interface RelinkResult {
+  missing_path: string;
+  candidates: RelinkCandidate[];
+}
+
+export interface PdfDetails {
+  subject?: string;
+  keywords?: string;
+  producer?: string;
+  pdf_version: string;
+  page_count: number;
+  is_encrypted: boolean;
+  has_text_layer: boolean;
+  is_tagged: boolean;
+}
+
 export interface ExcludedListItem {
   file_name?: string;
   path?: string;
@@ -80,9 +106,239 @@ export interface List {
 
 export type Lists = List[];
 
+export type IdentityStrategy = 'xmp' | 'sidecar' | 'xattr' | 'content_hash';
+
+export interface SetMetadataResult {
+  id: string;
+  strategy: IdentityStrategy;
+  // Причина, по которой вместо выбранного способа использован хеш содержимого
+  fallback_reason: string | null;
+}
+
+export interface ParsedFileName {
+  title: string | null;
+  author: string | null;
+  year: string | null;
+  fields: Record<string, string>;
+}
+
+export interface PatternTestResult {
+  file_name: string;
+  matched: boolean;
+  parsed: ParsedFileName | null;
+}
+
+export type RenameStatus =
+  | 'ready'
+  | 'unchanged'
+  | 'conflict'
+  | 'invalid'
+  | 'renamed'
+  | 'failed';
+
+export type PlanReason =
+  | { type: 'file_not_found' }
+  | { type: 'missing_fields'; fields: string[] }
+  | { type: 'invalid_name' }
+  | { type: 'name_taken' }
+  | { type: 'batch_collision' };
+
+export interface RenamePlanItem {
+  source: string;
+  target: string | null;
+  status: RenameStatus;
+  reason: PlanReason | null;
+  message: string | null;
+}
+
+export type JournalOperation = { undone: boolean } & (
+  | { type: 'rename'; from: string; to: string }
+  | { type: 'copy'; from: string; to: string }
+  | { type: 'delete'; path: string; trash_location: string }
+  | {
+      type: 'restore_backup';
+      path: string;
+      backup_path: string;
+      content_hash: string;
+    }
+  | { type: 'remove_identity'; path: string; strategy: IdentityStrategy }
+);
+
+export interface JournalEntry {
+  id: string;
+  timestamp: string;
+  description: string;
+  operations: JournalOperation[];
+  undone: boolean;
+}
+
+export interface UndoResult {
+  id: string;
+  undone: boolean;
+  errors: AppError[];
+}
+
+export interface DeleteResult {
+  path: string;
+  permanent: boolean;
+  trash_location: string | null;
+}
+
+export type TransferMode = 'move' | 'copy';
+
+export interface OrganizeFile {
+  path: string;
+  project: string | null;
+}
+
+export type OrganizeStatus =
+  | 'ready'
+  | 'unchanged'
+  | 'invalid'
+  | 'moved'
+  | 'copied'
+  | 'failed';
+
+export interface OrganizeItem {
+  source: string;
+  target: string | null;
+  status: OrganizeStatus;
+  reason: PlanReason | null;
+  message: string | null;
+}
+
+export interface OrganizeResult {
+  items: OrganizeItem[];
+  mapping: Record<string, string>;
+}
+
+export interface DuplicateFile {
+  path: string;
+  size: number;
+  highlight_count: number;
+  newest_annotation: string | null;
+}
+
+export interface DuplicateGroup {
+  kind: 'exact' | 'same_document';
+  key: string;
+  files: DuplicateFile[];
+  most_highlights: string | null;
+  newest_annotation: string | null;
+}
+
+export interface AnnotationConflict {
+  page: number;
+  subtype: string;
+  rect: [number, number, number, number];
+  variants: { source: string; contents: string | null }[];
+}
+
+export interface MergeAnnotationsResult {
+  target: string;
+  added: number;
+  duplicates: number;
+  conflicts: AnnotationConflict[];
+  failed: string[];
+}
+
+export interface TransferredHighlight {
+  old_page: number;
+  new_page: number;
+  subtype: string;
+  text: string;
+  confidence: number;
+}
+
+export interface UnplacedHighlight {
+  page: number;
+  subtype: string;
+  text: string | null;
+  contents: string | null;
+  reason: 'unsupported' | 'no_text' | 'not_found';
+}
+
+export interface TransferHighlightsResult {
+  target: string;
+  placed: TransferredHighlight[];
+  unplaced: UnplacedHighlight[];
+  duplicates: number;
+  written: number;
+}
+
+export interface WatchedDirectory {
+  dir_path: string;
+  recursive: boolean;
+  extensions: string[];
+  project: string | null;
+}
+
+export interface FileChangeEvent {
+  event_type: 'created' | 'modified' | 'deleted' | 'renamed';
+  file_path: string;
+  old_path: string | null;
+  metadata: Record<string, unknown> | null;
+}
+
+export interface WatchFilesResult {
+  watched: string[];
+  missing: string[];
+}
+
+export interface HighlightsChangedEvent {
+  file_path: string;
+  added: Highlight[];
+  changed: Highlight[];
+  removed: Highlight[];
+}
+
+export interface WatchRestoreReport {
+  missing: string[];
+  failed: string[];
+  changes: FileChangeEvent[];
+}
+
+export interface AnnotationHistoryEvent {
+  timestamp: string;
+  document: string;
+  path: string;
+  action: 'added' | 'edited' | 'deleted';
+  highlight: Highlight;
+}
+
+export type AppErrorCode =
+  | 'not_found'
+  | 'already_exists'
+  | 'not_a_directory'
+  | 'locked'
+  | 'io'
+  | 'pdf'
+  | 'encrypted'
+  | 'search_unavailable'
+  | 'unavailable'
+  | 'invalid_argument'
+  | 'already_watching'
+  | 'not_watching'
+  | 'outside_library'
+  | 'failed';
+
+// Ошибка, с которой завершается invoke; message - готовый текст для пользователя
+export interface AppError {
+  code: AppErrorCode;
+  message: string;
+  path?: string;
+  // Вид ошибки ОС для code = 'io': 'not_found', 'permission_denied', ...
+  kind?: string;
+  field?: string;
+  detail?: string;
+}
+
 export interface Settings {
   types: string[];
   excludedList: ExcludedListItem[];
   pdfReaderPath: string;
+  identityStrategy: IdentityStrategy;
+  filenamePatterns: string[];
+  libraryRoots: string[];
   [key: string]: any;
 }
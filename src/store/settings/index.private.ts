@@ -8,6 +8,9 @@ const defaultSettings: Settings = {
   types: ['pdf', 'djvu'],
   excludedList: [],
   pdfReaderPath: '',
+  identityStrategy: 'sidecar',
+  filenamePatterns: ['re:^\\S+ \\S+ \\S+ (?P<title>.+)$'],
+  libraryRoots: [],
 };
 
 export const createPrivateSlice = (
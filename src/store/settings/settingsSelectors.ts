@@ -24,4 +24,8 @@ export const selectExcludedQuery = (state: Store) => {
 export const selectPdfReaderPath = (state: Store) => {
   const settings = state.settings;
   return settings.pdfReaderPath;
-};
\ No newline at end of file
+};
+
+export const selectIdentityStrategy = (state: Store) => {
+  return state.settings.identityStrategy;
+};